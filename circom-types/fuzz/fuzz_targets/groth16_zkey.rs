@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taceo_circom_types::{
+    CheckElement, ParseOptions,
+    ark_bn254::Bn254,
+    groth16::{Zkey, ZkeyView},
+};
+
+fuzz_target!(|data: &[u8]| {
+    let options = ParseOptions::default().with_max_section_size(1 << 20);
+    let _ = Zkey::<Bn254>::from_reader_with_options(data, &options);
+    let _ = ZkeyView::<Bn254>::with_options(data, &options.with_check(CheckElement::No))
+        .and_then(|view| view.decode());
+});
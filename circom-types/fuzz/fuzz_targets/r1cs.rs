@@ -0,0 +1,11 @@
+#![no_main]
+
+use std::io::Cursor;
+
+use libfuzzer_sys::fuzz_target;
+use taceo_circom_types::{ParseOptions, R1CS, ark_bn254::Bn254};
+
+fuzz_target!(|data: &[u8]| {
+    let options = ParseOptions::default().with_max_section_size(1 << 20);
+    let _ = R1CS::<Bn254>::from_reader_with_options(Cursor::new(data), &options);
+});
@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use taceo_circom_types::{ParseOptions, Witness, ark_bn254::Fr};
+
+fuzz_target!(|data: &[u8]| {
+    let options = ParseOptions::default().with_max_section_size(1 << 20);
+    let _ = Witness::<Fr>::from_reader_with_options(data, &options);
+});
@@ -0,0 +1,561 @@
+use std::fs::File;
+use std::io::{BufReader, Cursor};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use ark_ec::pairing::Pairing;
+use ark_ff::{BigInteger, PrimeField};
+use clap::{Args, Parser, Subcommand};
+use eyre::Context;
+use memmap2::Mmap;
+use serde::Serialize;
+use taceo_circom_types::groth16::ZkeyView;
+use taceo_circom_types::traits::CircomArkworksPairingBridge;
+use taceo_circom_types::{
+    BinFileHeader, BinFileSection, CheckElement, PowersOfTauView, R1CS, Symbols, Unsatisfied,
+    Witness, plonk,
+};
+use tracing_subscriber::EnvFilter;
+
+/// Utility tools for working with the binary artifacts of Circom and snarkjs.
+#[derive(Debug, Parser)]
+#[command(version, about, long_about = None)]
+struct Config {
+    #[clap(subcommand)]
+    subcommand: SubCommand,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubCommand {
+    Inspect(InspectConfig),
+    CheckWitness(CheckWitnessConfig),
+}
+
+/// Prints the type, version, section table and header of a zkey, r1cs, wtns or ptau file.
+#[derive(Debug, Args)]
+struct InspectConfig {
+    /// Path to the file.
+    pub file: PathBuf,
+    /// Print the result as JSON.
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// Checks that a witness satisfies the constraints of an R1CS and prints the failing constraints.
+#[derive(Debug, Args)]
+struct CheckWitnessConfig {
+    /// Path to the R1CS file.
+    #[clap(long)]
+    pub r1cs: PathBuf,
+    /// Path to the witness file.
+    #[clap(long)]
+    pub witness: PathBuf,
+    /// Path to the symbols file produced by Circom with `--sym`, used to print signal names.
+    #[clap(long)]
+    pub sym: Option<PathBuf>,
+}
+
+/// A section of a binary file.
+#[derive(Debug, Serialize)]
+struct Section {
+    id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<&'static str>,
+    offset: u64,
+    size: u64,
+}
+
+/// The information of a binary file, fields not present in the file type are omitted.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Inspection {
+    file_type: String,
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    curve: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_vars: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_public: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_pub_out: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_pub_in: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_prv_in: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_labels: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_constraints: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_witness: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    domain_size: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    power: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ceremony_power: Option<usize>,
+    sections: Vec<Section>,
+}
+
+/// Returns the modulus of a prime field as big-endian hex.
+fn modulus_hex<F: PrimeField>() -> String {
+    let hex = F::MODULUS
+        .to_bytes_be()
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect::<String>();
+    format!("0x{hex}")
+}
+
+fn section_name(file_type: &str, protocol: Option<&str>, id: u32) -> Option<&'static str> {
+    let name = match (file_type, protocol, id) {
+        ("zkey", _, 1) => "header",
+        ("zkey", Some("groth16"), 2) => "groth16 header",
+        ("zkey", Some("groth16"), 3) => "IC",
+        ("zkey", Some("groth16"), 4) => "coefficients",
+        ("zkey", Some("groth16"), 5) => "A",
+        ("zkey", Some("groth16"), 6) => "B1",
+        ("zkey", Some("groth16"), 7) => "B2",
+        ("zkey", Some("groth16"), 8) => "C",
+        ("zkey", Some("groth16"), 9) => "H",
+        ("zkey", Some("groth16"), 10) => "contributions",
+        ("zkey", Some("plonk"), 2) => "plonk header",
+        ("zkey", Some("plonk"), 3) => "additions",
+        ("zkey", Some("plonk"), 4) => "A map",
+        ("zkey", Some("plonk"), 5) => "B map",
+        ("zkey", Some("plonk"), 6) => "C map",
+        ("zkey", Some("plonk"), 7) => "QM",
+        ("zkey", Some("plonk"), 8) => "QL",
+        ("zkey", Some("plonk"), 9) => "QR",
+        ("zkey", Some("plonk"), 10) => "QO",
+        ("zkey", Some("plonk"), 11) => "QC",
+        ("zkey", Some("plonk"), 12) => "sigma",
+        ("zkey", Some("plonk"), 13) => "lagrange",
+        ("zkey", Some("plonk"), 14) => "ptau",
+        ("r1cs", _, 1) => "header",
+        ("r1cs", _, 2) => "constraints",
+        ("r1cs", _, 3) => "wire to label map",
+        ("r1cs", _, 4) => "custom gates list",
+        ("r1cs", _, 5) => "custom gates application",
+        ("wtns", _, 1) => "header",
+        ("wtns", _, 2) => "witness",
+        ("ptau", _, 1) => "header",
+        ("ptau", _, 2) => "tauG1",
+        ("ptau", _, 3) => "tauG2",
+        ("ptau", _, 4) => "alphaTauG1",
+        ("ptau", _, 5) => "betaTauG1",
+        ("ptau", _, 6) => "betaG2",
+        ("ptau", _, 7) => "contributions",
+        ("ptau", _, 12) => "lagrange tauG1",
+        ("ptau", _, 13) => "lagrange tauG2",
+        ("ptau", _, 14) => "lagrange alphaTauG1",
+        ("ptau", _, 15) => "lagrange betaTauG1",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/// Fills the header fields of the inspection with the parsers of the curve `P`, fails if the
+/// file is not defined over `P`.
+fn inspect_on_curve<P: Pairing + CircomArkworksPairingBridge>(
+    bytes: &[u8],
+    inspection: &mut Inspection,
+) -> eyre::Result<()> {
+    match inspection.file_type.as_str() {
+        "zkey" => match inspection.protocol.as_deref() {
+            Some("groth16") => {
+                let zkey = ZkeyView::<P>::new(bytes, CheckElement::No)?;
+                // groth16 zkeys do not store the number of constraints, it is derived from the
+                // coefficients of the constraint matrices
+                let (n_constraints, _, _) = zkey.constraint_matrices()?;
+                inspection.prime = Some(modulus_hex::<P::BaseField>());
+                inspection.n_vars = Some(zkey.n_vars());
+                inspection.n_public = Some(zkey.n_public());
+                inspection.domain_size = Some(zkey.domain_size());
+                inspection.n_constraints = Some(n_constraints);
+            }
+            Some("plonk") => {
+                let zkey = plonk::Zkey::<P>::from_reader(bytes, CheckElement::No)?;
+                inspection.prime = Some(modulus_hex::<P::BaseField>());
+                inspection.n_vars = Some(zkey.n_vars);
+                inspection.n_public = Some(zkey.n_public);
+                inspection.domain_size = Some(zkey.domain_size);
+                inspection.n_constraints = Some(zkey.n_constraints);
+            }
+            // the headers of other protocols are not supported, only list the sections
+            _ => {}
+        },
+        "r1cs" => {
+            let r1cs = R1CS::<P>::from_reader(Cursor::new(bytes))?;
+            inspection.prime = Some(modulus_hex::<P::ScalarField>());
+            inspection.n_vars = Some(r1cs.num_variables);
+            inspection.n_public = Some((r1cs.n_pub_out + r1cs.n_pub_in) as usize);
+            inspection.n_pub_out = Some(r1cs.n_pub_out);
+            inspection.n_pub_in = Some(r1cs.n_pub_in);
+            inspection.n_prv_in = Some(r1cs.n_prv_in);
+            inspection.n_labels = Some(r1cs.n_labels);
+            inspection.n_constraints = Some(r1cs.n_constraints);
+        }
+        "wtns" => {
+            let witness = Witness::<P::ScalarField>::from_reader(bytes)?;
+            inspection.prime = Some(modulus_hex::<P::ScalarField>());
+            inspection.n_witness = Some(witness.values.len());
+        }
+        "ptau" => {
+            let ptau = PowersOfTauView::<P>::new(bytes, CheckElement::No)?;
+            inspection.prime = Some(modulus_hex::<P::BaseField>());
+            inspection.power = Some(ptau.power());
+            inspection.ceremony_power = Some(ptau.ceremony_power());
+            inspection.domain_size = Some(1 << ptau.power());
+        }
+        _ => unreachable!("checked when reading the header"),
+    }
+    Ok(())
+}
+
+fn inspect_file(path: PathBuf) -> eyre::Result<Inspection> {
+    let file = File::open(path).context("while opening file")?;
+    // SAFETY: the file is only read and not expected to be modified while inspecting it
+    let bytes = unsafe { Mmap::map(&file) }.context("while mapping file")?;
+    let file_type = String::from_utf8_lossy(bytes.get(..4).unwrap_or_default());
+    eyre::ensure!(
+        ["zkey", "r1cs", "wtns", "ptau"].contains(&file_type.as_ref()),
+        "unsupported file type {file_type:?}, expected a zkey, r1cs, wtns or ptau file"
+    );
+    let header = BinFileHeader::from_bytes(&bytes).context("while reading sections")?;
+    tracing::debug!(
+        "{} version {} with {} sections",
+        header.file_type,
+        header.version,
+        header.sections.len()
+    );
+
+    let protocol = if header.file_type == "zkey" {
+        let id = header
+            .section(&bytes, 1)
+            .and_then(|section| section.first_chunk::<4>())
+            .map(|id| u32::from_le_bytes(*id));
+        match id {
+            Some(1) => Some("groth16".to_owned()),
+            Some(2) => Some("plonk".to_owned()),
+            Some(10) => Some("fflonk".to_owned()),
+            Some(protocol) => {
+                eprintln!("warning: unknown protocol {protocol}");
+                None
+            }
+            None => None,
+        }
+    } else {
+        None
+    };
+    let mut inspection = Inspection {
+        file_type: header.file_type,
+        version: header.version,
+        protocol,
+        ..Default::default()
+    };
+
+    let result = inspect_on_curve::<ark_bn254::Bn254>(&bytes, &mut inspection).map(|()| "bn128");
+    let result = result.or_else(|err| {
+        inspect_on_curve::<ark_bls12_381::Bls12_381>(&bytes, &mut inspection)
+            .map(|()| "bls12381")
+            .map_err(|_| err)
+    });
+    match result {
+        Ok(curve) if inspection.prime.is_some() => inspection.curve = Some(curve),
+        Ok(_) => {}
+        // a damaged file still has a readable section table, which is usually what is needed to
+        // find the broken section
+        Err(err) => eprintln!(
+            "warning: could not parse the {} header: {err:#}",
+            inspection.file_type
+        ),
+    }
+
+    inspection.sections = header
+        .sections
+        .into_iter()
+        .map(|BinFileSection { id, offset, size }| Section {
+            id,
+            name: section_name(&inspection.file_type, inspection.protocol.as_deref(), id),
+            offset,
+            size,
+        })
+        .collect();
+    Ok(inspection)
+}
+
+fn print_inspection(inspection: &Inspection) {
+    let Inspection {
+        file_type,
+        version,
+        protocol,
+        curve,
+        prime,
+        n_vars,
+        n_public,
+        n_pub_out,
+        n_pub_in,
+        n_prv_in,
+        n_labels,
+        n_constraints,
+        n_witness,
+        domain_size,
+        power,
+        ceremony_power,
+        sections,
+    } = inspection;
+    match protocol {
+        Some(protocol) => println!("type:          {file_type} ({protocol}), version {version}"),
+        None => println!("type:          {file_type}, version {version}"),
+    }
+    let rows = [
+        ("curve", curve.map(str::to_owned)),
+        ("prime", prime.clone()),
+        ("nVars", n_vars.map(|x| x.to_string())),
+        ("nPublic", n_public.map(|x| x.to_string())),
+        ("nPubOut", n_pub_out.map(|x| x.to_string())),
+        ("nPubIn", n_pub_in.map(|x| x.to_string())),
+        ("nPrvIn", n_prv_in.map(|x| x.to_string())),
+        ("nLabels", n_labels.map(|x| x.to_string())),
+        ("nConstraints", n_constraints.map(|x| x.to_string())),
+        ("nWitness", n_witness.map(|x| x.to_string())),
+        ("domainSize", domain_size.map(|x| x.to_string())),
+        ("power", power.map(|x| x.to_string())),
+        ("ceremonyPower", ceremony_power.map(|x| x.to_string())),
+    ];
+    for (name, value) in rows {
+        if let Some(value) = value {
+            println!("{:<15}{value}", format!("{name}:"));
+        }
+    }
+    println!("sections:");
+    println!(
+        "  {:>4}  {:<26}{:>14}{:>14}",
+        "id", "name", "offset", "size"
+    );
+    for Section {
+        id,
+        name,
+        offset,
+        size,
+    } in sections
+    {
+        println!(
+            "  {id:>4}  {:<26}{offset:>14}{size:>14}",
+            name.unwrap_or("unknown")
+        );
+    }
+}
+
+fn inspect(config: InspectConfig) -> eyre::Result<ExitCode> {
+    let InspectConfig { file, json } = config;
+    let inspection = inspect_file(file)?;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&inspection)?);
+    } else {
+        print_inspection(&inspection);
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn check_witness(config: CheckWitnessConfig) -> eyre::Result<ExitCode> {
+    let CheckWitnessConfig { r1cs, witness, sym } = config;
+    let r1cs = R1CS::<ark_bn254::Bn254>::from_reader(BufReader::new(
+        File::open(r1cs).context("while opening r1cs file")?,
+    ))
+    .context("while parsing r1cs file")?;
+    let witness = Witness::<ark_bn254::Fr>::from_reader(BufReader::new(
+        File::open(witness).context("while opening witness file")?,
+    ))
+    .context("while parsing witness file")?;
+    let symbols = sym
+        .map(|sym| -> eyre::Result<Symbols> {
+            let file = File::open(sym).context("while opening sym file")?;
+            Symbols::from_reader(file).context("while parsing sym file")
+        })
+        .transpose()?
+        .unwrap_or_default();
+    match r1cs.check_witness(&witness) {
+        Ok(()) => {
+            println!(
+                "witness satisfies the {} constraints of the R1CS",
+                r1cs.constraints.len()
+            );
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            let err = err.with_symbols(&symbols);
+            println!("witness is invalid: {err}");
+            if let Unsatisfied::Constraints(failing) = &err {
+                for constraint in failing {
+                    if let Some(formula) = r1cs.display_constraint(constraint.index, &symbols) {
+                        println!("constraint {}: {formula}", constraint.index);
+                    }
+                }
+            }
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+fn main() -> eyre::Result<ExitCode> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_writer(std::io::stderr)
+        .init();
+    let config = Config::parse();
+    match config.subcommand {
+        SubCommand::Inspect(config) => inspect(config),
+        SubCommand::CheckWitness(config) => check_witness(config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn kat(name: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("kats/groth16/bn254")
+            .join(name)
+    }
+
+    fn section_table(inspection: &Inspection) -> Vec<(u32, Option<&'static str>, u64, u64)> {
+        inspection
+            .sections
+            .iter()
+            .map(|s| (s.id, s.name, s.offset, s.size))
+            .collect()
+    }
+
+    #[test]
+    fn can_inspect_bn254_zkey() {
+        let inspection = inspect_file(kat("circuit.zkey")).unwrap();
+        assert_eq!(inspection.file_type, "zkey");
+        assert_eq!(inspection.version, 1);
+        assert_eq!(inspection.protocol.as_deref(), Some("groth16"));
+        assert_eq!(inspection.curve, Some("bn128"));
+        assert_eq!(
+            inspection.prime.as_deref(),
+            Some("0x30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47")
+        );
+        assert_eq!(inspection.n_vars, Some(4));
+        assert_eq!(inspection.n_public, Some(1));
+        assert_eq!(inspection.n_constraints, Some(1));
+        assert_eq!(inspection.domain_size, Some(4));
+        assert_eq!(
+            section_table(&inspection),
+            vec![
+                (1, Some("header"), 24, 4),
+                (2, Some("groth16 header"), 40, 660),
+                (3, Some("IC"), 712, 128),
+                (4, Some("coefficients"), 852, 180),
+                (5, Some("A"), 1044, 256),
+                (6, Some("B1"), 1312, 256),
+                (7, Some("B2"), 1580, 512),
+                (8, Some("C"), 2104, 128),
+                (9, Some("H"), 2244, 256),
+                (10, Some("contributions"), 2512, 482),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_inspect_bn254_r1cs() {
+        let inspection = inspect_file(kat("circuit.r1cs")).unwrap();
+        assert_eq!(inspection.file_type, "r1cs");
+        assert_eq!(inspection.version, 1);
+        assert_eq!(inspection.protocol, None);
+        assert_eq!(inspection.curve, Some("bn128"));
+        assert_eq!(
+            inspection.prime.as_deref(),
+            Some("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+        );
+        assert_eq!(inspection.n_vars, Some(4));
+        assert_eq!(inspection.n_public, Some(1));
+        assert_eq!(inspection.n_pub_out, Some(1));
+        assert_eq!(inspection.n_pub_in, Some(0));
+        assert_eq!(inspection.n_prv_in, Some(2));
+        assert_eq!(inspection.n_labels, Some(4));
+        assert_eq!(inspection.n_constraints, Some(1));
+        // circom writes the constraints before the header
+        assert_eq!(
+            section_table(&inspection),
+            vec![
+                (2, Some("constraints"), 24, 120),
+                (1, Some("header"), 156, 64),
+                (3, Some("wire to label map"), 232, 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn can_inspect_bn254_witness() {
+        let inspection = inspect_file(kat("witness.wtns")).unwrap();
+        assert_eq!(inspection.file_type, "wtns");
+        assert_eq!(inspection.version, 2);
+        assert_eq!(inspection.curve, Some("bn128"));
+        assert_eq!(
+            inspection.prime.as_deref(),
+            Some("0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+        );
+        assert_eq!(inspection.n_witness, Some(4));
+        assert_eq!(
+            section_table(&inspection),
+            vec![(1, Some("header"), 24, 40), (2, Some("witness"), 76, 128)]
+        );
+    }
+
+    #[test]
+    fn can_inspect_bls12_381_r1cs() {
+        let path =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("kats/groth16/bls12_381/circuit.r1cs");
+        let inspection = inspect_file(path).unwrap();
+        assert_eq!(inspection.file_type, "r1cs");
+        assert_eq!(inspection.curve, Some("bls12381"));
+        assert_eq!(
+            inspection.prime.as_deref(),
+            Some("0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001")
+        );
+        assert_eq!(inspection.n_constraints, Some(1));
+    }
+
+    #[test]
+    fn lists_sections_of_damaged_files() {
+        let mut bytes = std::fs::read(kat("circuit.r1cs")).unwrap();
+        // overwrite the prime in the header section, no curve can parse the file anymore
+        bytes[160..192].fill(0xff);
+        let path = std::env::temp_dir().join(format!("damaged-{}.r1cs", std::process::id()));
+        std::fs::write(&path, &bytes).unwrap();
+        let inspection = inspect_file(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        let inspection = inspection.unwrap();
+        assert_eq!(inspection.file_type, "r1cs");
+        assert_eq!(inspection.version, 1);
+        assert_eq!(inspection.curve, None);
+        assert_eq!(inspection.prime, None);
+        assert_eq!(inspection.n_constraints, None);
+        assert_eq!(
+            section_table(&inspection),
+            vec![
+                (2, Some("constraints"), 24, 120),
+                (1, Some("header"), 156, 64),
+                (3, Some("wire to label map"), 232, 32),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_files() {
+        let err = inspect_file(kat("verification_key.json")).unwrap_err();
+        assert!(err.to_string().contains("unsupported file type"));
+    }
+}
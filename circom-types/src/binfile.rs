@@ -1,14 +1,15 @@
-use std::{
-    io::{Cursor, Read},
-    marker::PhantomData,
-};
+use std::io::Read;
+#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
+use std::{collections::BTreeMap, marker::PhantomData};
 
+#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
 use ark_ec::pairing::Pairing;
 use ark_serialize::SerializationError;
 use byteorder::{LittleEndian, ReadBytesExt};
 use thiserror::Error;
 
-use crate::traits::CircomArkworksPairingBridge;
+#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
+use crate::{ParseOptions, traits::CircomArkworksPairingBridge};
 
 pub(crate) type ZkeyParserResult<T> = std::result::Result<T, ZkeyParserError>;
 
@@ -30,56 +31,74 @@ pub enum ZkeyParserError {
     /// generic bin file error
     #[error("bin file corrupted: \"{0}\"")]
     CorruptedBinFile(String),
+    /// The file exceeds the limits configured in [`ParseOptions`](crate::ParseOptions)
+    #[error(transparent)]
+    LimitExceeded(#[from] crate::LimitExceeded),
 }
 
+#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
 #[derive(Debug)]
 pub(crate) struct BinFile<P: Pairing + CircomArkworksPairingBridge> {
     #[expect(dead_code)]
     ftype: String,
     #[expect(dead_code)]
     version: u32,
-    sections: Vec<Vec<u8>>,
+    sections: BTreeMap<u32, Vec<u8>>,
     phantom_data: PhantomData<P>,
 }
 
+fn read_bin_header<R: Read>(reader: &mut R) -> ZkeyParserResult<(String, u32, usize)> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    let ftype = std::str::from_utf8(&magic[..])
+        .map_err(|_| ZkeyParserError::CorruptedBinFile("cannot parse magic number".to_owned()))?
+        .to_string();
+    tracing::debug!("file type for binfile: \"{ftype}\"");
+
+    let version = reader.read_u32::<LittleEndian>()?;
+    tracing::debug!("binfile version {}", version);
+
+    let num_sections: usize = reader
+        .read_u32::<LittleEndian>()?
+        .try_into()
+        .expect("u32 fits into usize");
+    tracing::debug!("we got {} sections in binfile", num_sections);
+    Ok((ftype, version, num_sections))
+}
+
+#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
 impl<P: Pairing + CircomArkworksPairingBridge> BinFile<P> {
-    pub(crate) fn new<R: Read>(reader: &mut R) -> ZkeyParserResult<Self> {
+    pub(crate) fn new<R: Read>(reader: &mut R, options: &ParseOptions) -> ZkeyParserResult<Self> {
         tracing::debug!("reading bin file");
-        let mut magic = [0u8; 4];
-        reader.read_exact(&mut magic)?;
-        let ftype = std::str::from_utf8(&magic[..])
-            .map_err(|_| ZkeyParserError::CorruptedBinFile("cannot parse magic number".to_owned()))?
-            .to_string();
-        tracing::debug!("file type for binfile: \"{ftype}\"");
-
-        let version = reader.read_u32::<LittleEndian>()?;
-        tracing::debug!("binfile version {}", version);
-
-        let num_sections: usize = reader
-            .read_u32::<LittleEndian>()?
-            .try_into()
-            .expect("u32 fits into usize");
-        tracing::debug!("we got {} sections in binfile", num_sections);
-        let mut sections = vec![vec![]; num_sections];
+        let (ftype, version, num_sections) = read_bin_header(reader)?;
+        let mut sections = BTreeMap::new();
 
         for _ in 0..num_sections {
-            let section_id: usize = reader
-                .read_u32::<LittleEndian>()?
-                .try_into()
-                .expect("u32 fits into usize");
-            let section_length: usize = reader
-                .read_u64::<LittleEndian>()?
-                .try_into()
-                .expect("u64 fits into usize");
-
-            let section = &mut sections[section_id - 1];
-            if !section.is_empty() {
+            let section_id = reader.read_u32::<LittleEndian>()?;
+            let section_length = reader.read_u64::<LittleEndian>()?;
+            if section_id == 0 {
                 return Err(ZkeyParserError::CorruptedBinFile(
-                    "sections are empty!".to_owned(),
+                    "invalid section id 0".to_owned(),
                 ));
             }
-            section.resize(section_length, 0);
-            reader.read_exact(section)?;
+            if sections.contains_key(&section_id) {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "section {section_id} occurs twice"
+                )));
+            }
+            options.check_section_size(section_id, section_length)?;
+            // read incrementally instead of allocating the announced length up front
+            let mut section = Vec::new();
+            reader
+                .by_ref()
+                .take(section_length)
+                .read_to_end(&mut section)?;
+            if section.len() as u64 != section_length {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "section {section_id} exceeds file size"
+                )));
+            }
+            sections.insert(section_id, section);
         }
         tracing::debug!("successfully read bin file!");
         Ok(Self {
@@ -90,12 +109,300 @@ impl<P: Pairing + CircomArkworksPairingBridge> BinFile<P> {
         })
     }
 
-    pub(crate) fn take_section(&mut self, id: usize) -> Cursor<Vec<u8>> {
-        Cursor::new(std::mem::take(&mut self.sections[id - 1]))
+    #[cfg(all(feature = "zkey", feature = "plonk"))]
+    pub(crate) fn take_section(&mut self, id: u32) -> ZkeyParserResult<std::io::Cursor<Vec<u8>>> {
+        self.take_section_raw(id).map(std::io::Cursor::new)
+    }
+
+    #[cfg(all(feature = "zkey", feature = "plonk"))]
+    pub(crate) fn take_section_raw(&mut self, id: u32) -> ZkeyParserResult<Vec<u8>> {
+        self.sections
+            .remove(&id)
+            .ok_or_else(|| ZkeyParserError::CorruptedBinFile(format!("missing section {id}")))
+    }
+
+    #[cfg(all(feature = "zkey", feature = "groth16"))]
+    pub(crate) fn view(&self) -> BinFileView<'_> {
+        BinFileView {
+            sections: self
+                .sections
+                .iter()
+                .map(|(id, section)| (*id, section.as_slice()))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+pub(crate) fn read_array<const N: usize, R: Read>(mut reader: R) -> std::io::Result<[u8; N]> {
+    let mut buf = [0u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+/// The optional parameters of a contribution to a snarkjs ceremony, shared by
+/// the contributions of powers of tau and zkeys.
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+#[derive(Debug, Default)]
+pub(crate) struct ContributionParams {
+    pub(crate) name: Option<String>,
+    pub(crate) num_iterations_exp: Option<u8>,
+    pub(crate) beacon_hash: Option<Vec<u8>>,
+}
+
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+impl ContributionParams {
+    const NAME: u8 = 1;
+    const NUM_ITERATIONS_EXP: u8 = 2;
+    const BEACON_HASH: u8 = 3;
+
+    /// Reads the length-prefixed list of parameters.
+    pub(crate) fn read<R: Read>(mut reader: R) -> ZkeyParserResult<Self> {
+        let params_len = reader.read_u32::<LittleEndian>()?;
+        // read incrementally instead of allocating the announced length up front
+        let mut params = Vec::new();
+        reader
+            .by_ref()
+            .take(u64::from(params_len))
+            .read_to_end(&mut params)?;
+        if params.len() as u64 != u64::from(params_len) {
+            return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+        }
+        let mut params = params.as_slice();
+
+        let mut result = Self::default();
+        let mut last_param = 0;
+        while !params.is_empty() {
+            let param = params.read_u8()?;
+            if param <= last_param {
+                return Err(ZkeyParserError::CorruptedBinFile(
+                    "parameters in the contribution must be sorted".to_owned(),
+                ));
+            }
+            last_param = param;
+            match param {
+                Self::NAME => {
+                    let name = read_length_prefixed(&mut params)?;
+                    result.name = Some(String::from_utf8(name).map_err(|_| {
+                        ZkeyParserError::CorruptedBinFile(
+                            "contributor name is not valid UTF-8".to_owned(),
+                        )
+                    })?);
+                }
+                Self::NUM_ITERATIONS_EXP => result.num_iterations_exp = Some(params.read_u8()?),
+                Self::BEACON_HASH => result.beacon_hash = Some(read_length_prefixed(&mut params)?),
+                _ => {
+                    return Err(ZkeyParserError::CorruptedBinFile(format!(
+                        "unknown contribution parameter {param}"
+                    )));
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    /// Writes the length-prefixed list of parameters. Inverse of [`ContributionParams::read`].
+    pub(crate) fn write<W: std::io::Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        use byteorder::WriteBytesExt;
+        let mut params = Vec::new();
+        if let Some(name) = &self.name {
+            params.push(Self::NAME);
+            write_length_prefixed(&mut params, name.as_bytes())?;
+        }
+        if let Some(num_iterations_exp) = self.num_iterations_exp {
+            params.push(Self::NUM_ITERATIONS_EXP);
+            params.push(num_iterations_exp);
+        }
+        if let Some(beacon_hash) = &self.beacon_hash {
+            params.push(Self::BEACON_HASH);
+            write_length_prefixed(&mut params, beacon_hash)?;
+        }
+        writer.write_u32::<LittleEndian>(
+            u32::try_from(params.len()).expect("at most three short parameters"),
+        )?;
+        writer.write_all(&params)?;
+        Ok(())
+    }
+}
+
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+fn read_length_prefixed(params: &mut &[u8]) -> ZkeyParserResult<Vec<u8>> {
+    let len = usize::from(params.read_u8()?);
+    let mut buf = vec![0u8; len];
+    params.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+fn write_length_prefixed(params: &mut Vec<u8>, bytes: &[u8]) -> ZkeyParserResult<()> {
+    let len = u8::try_from(bytes.len()).map_err(|_| {
+        ZkeyParserError::CorruptedBinFile("contribution parameter exceeds 255 bytes".to_owned())
+    })?;
+    params.push(len);
+    params.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Borrowed counterpart of [`BinFile`]. Only records where the sections are located,
+/// so that they can be decoded on demand (e.g. from a memory-mapped file).
+///
+/// Section ids may exceed the number of sections, as some files (e.g. prepared powers of
+/// tau) skip ids.
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+#[derive(Debug, Clone)]
+pub(crate) struct BinFileView<'a> {
+    sections: std::collections::BTreeMap<u32, &'a [u8]>,
+}
+
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+impl<'a> BinFileView<'a> {
+    #[cfg(all(feature = "zkey", feature = "groth16"))]
+    pub(crate) fn new(bytes: &'a [u8]) -> ZkeyParserResult<Self> {
+        Ok(Self::with_header(bytes)?.2)
+    }
+
+    /// Same as [`BinFileView::new`], but additionally returns the file type and version.
+    pub(crate) fn with_header(bytes: &'a [u8]) -> ZkeyParserResult<(String, u32, Self)> {
+        tracing::debug!("reading bin file view");
+        let mut reader = bytes;
+        let (ftype, version, num_sections) = read_bin_header(&mut reader)?;
+        let mut sections = std::collections::BTreeMap::new();
+
+        for _ in 0..num_sections {
+            let section_id = reader.read_u32::<LittleEndian>()?;
+            let section_length = usize::try_from(reader.read_u64::<LittleEndian>()?)
+                .ok()
+                .filter(|len| *len <= reader.len())
+                .ok_or_else(|| {
+                    ZkeyParserError::CorruptedBinFile(format!(
+                        "section {section_id} exceeds file size"
+                    ))
+                })?;
+            if section_id == 0 {
+                return Err(ZkeyParserError::CorruptedBinFile(
+                    "invalid section id 0".to_owned(),
+                ));
+            }
+            let (content, rest) = reader.split_at(section_length);
+            if sections.insert(section_id, content).is_some() {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "section {section_id} occurs twice"
+                )));
+            }
+            reader = rest;
+        }
+        tracing::debug!("successfully read bin file view!");
+        Ok((ftype, version, Self { sections }))
+    }
+
+    /// Returns the section with the given id, or an empty slice if the file has no such section.
+    pub(crate) fn section(&self, id: u32) -> &'a [u8] {
+        self.sections.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Returns the ids and contents of all sections, in ascending order of the ids.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (u32, &'a [u8])> + '_ {
+        self.sections.iter().map(|(id, section)| (*id, *section))
+    }
+}
+
+/// The file type, version and section table of a binary file of Circom or snarkjs, e.g., a zkey,
+/// r1cs, wtns or ptau file.
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinFileHeader {
+    /// The magic number identifying the type of the file, e.g. `zkey`
+    pub file_type: String,
+    /// The version of the file format
+    pub version: u32,
+    /// The sections in the order they appear in the file
+    pub sections: Vec<BinFileSection>,
+}
+
+/// The location of a section within a binary file, see [`BinFileHeader`].
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BinFileSection {
+    /// The id of the section
+    pub id: u32,
+    /// The offset of the content of the section from the start of the file
+    pub offset: u64,
+    /// The size of the content of the section in bytes
+    pub size: u64,
+}
+
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+impl BinFileHeader {
+    /// Reads the header and section table of the file contained in `bytes` without decoding any
+    /// section. Fails if a section exceeds the file or occurs twice.
+    pub fn from_bytes(bytes: &[u8]) -> ZkeyParserResult<Self> {
+        let (file_type, version, view) = BinFileView::with_header(bytes)?;
+        let mut sections = view
+            .sections
+            .iter()
+            .map(|(id, section)| BinFileSection {
+                id: *id,
+                offset: (section.as_ptr() as usize - bytes.as_ptr() as usize) as u64,
+                size: section.len() as u64,
+            })
+            .collect::<Vec<_>>();
+        sections.sort_by_key(|section| section.offset);
+        Ok(Self {
+            file_type,
+            version,
+            sections,
+        })
+    }
+
+    /// Returns the content of the section with the given id within `bytes`, the file this header
+    /// was read from.
+    pub fn section<'a>(&self, bytes: &'a [u8], id: u32) -> Option<&'a [u8]> {
+        let section = self.sections.iter().find(|section| section.id == id)?;
+        let start = usize::try_from(section.offset).ok()?;
+        let end = start.checked_add(usize::try_from(section.size).ok()?)?;
+        bytes.get(start..end)
+    }
+}
+
+/// Writes the binary section container shared by the Circom/snarkjs file formats.
+///
+/// The number of sections is fixed up front and every section is emitted in one piece,
+/// as the container stores the length of a section before its content.
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+#[derive(Debug)]
+pub(crate) struct BinFileWriter<W: std::io::Write> {
+    writer: W,
+}
+
+#[cfg(any(feature = "groth16", feature = "ptau"))]
+impl<W: std::io::Write> BinFileWriter<W> {
+    pub(crate) fn new(
+        mut writer: W,
+        ftype: &str,
+        version: u32,
+        num_sections: u32,
+    ) -> std::io::Result<Self> {
+        use byteorder::WriteBytesExt;
+        tracing::debug!("writing bin file \"{ftype}\" with {num_sections} sections");
+        assert_eq!(ftype.len(), 4, "magic number must have four bytes");
+        writer.write_all(ftype.as_bytes())?;
+        writer.write_u32::<LittleEndian>(version)?;
+        writer.write_u32::<LittleEndian>(num_sections)?;
+        Ok(Self { writer })
+    }
+
+    pub(crate) fn write_section(&mut self, id: u32, section: &[u8]) -> std::io::Result<()> {
+        use byteorder::WriteBytesExt;
+        tracing::trace!("writing section {id} with {} bytes", section.len());
+        self.writer.write_u32::<LittleEndian>(id)?;
+        self.writer.write_u64::<LittleEndian>(
+            u64::try_from(section.len()).expect("usize fits into u64"),
+        )?;
+        self.writer.write_all(section)
     }
 
-    #[cfg(feature = "plonk")]
-    pub(crate) fn take_section_raw(&mut self, id: usize) -> Vec<u8> {
-        std::mem::take(&mut self.sections[id - 1])
+    pub(crate) fn finish(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
     }
 }
@@ -0,0 +1,97 @@
+//! The ChaCha based random number generator of ffjavascript.
+//!
+//! snarkjs seeds it with a hash to derive curve points deterministically (e.g. the G2 part of the
+//! public key of a phase-2 contribution), so we need a bit-exact port to recompute those points.
+
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646E, 0x79622D32, 0x6B206574];
+
+#[derive(Debug, Clone)]
+pub(crate) struct ChaCha {
+    state: [u32; 16],
+    buff: [u32; 16],
+    idx: usize,
+}
+
+fn quarter_round(st: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    st[a] = st[a].wrapping_add(st[b]);
+    st[d] = (st[d] ^ st[a]).rotate_left(16);
+    st[c] = st[c].wrapping_add(st[d]);
+    st[b] = (st[b] ^ st[c]).rotate_left(12);
+    st[a] = st[a].wrapping_add(st[b]);
+    st[d] = (st[d] ^ st[a]).rotate_left(8);
+    st[c] = st[c].wrapping_add(st[d]);
+    st[b] = (st[b] ^ st[c]).rotate_left(7);
+}
+
+fn double_round(st: &mut [u32; 16]) {
+    quarter_round(st, 0, 4, 8, 12);
+    quarter_round(st, 1, 5, 9, 13);
+    quarter_round(st, 2, 6, 10, 14);
+    quarter_round(st, 3, 7, 11, 15);
+
+    quarter_round(st, 0, 5, 10, 15);
+    quarter_round(st, 1, 6, 11, 12);
+    quarter_round(st, 2, 7, 8, 13);
+    quarter_round(st, 3, 4, 9, 14);
+}
+
+impl ChaCha {
+    pub(crate) fn new(seed: [u32; 8]) -> Self {
+        let mut state = [0; 16];
+        state[..4].copy_from_slice(&CONSTANTS);
+        state[4..12].copy_from_slice(&seed);
+        Self {
+            state,
+            buff: [0; 16],
+            idx: 16,
+        }
+    }
+
+    /// Seeds the generator with the first 32 bytes of `hash`, read as big-endian words.
+    pub(crate) fn from_hash(hash: &[u8]) -> Self {
+        let mut seed = [0; 8];
+        for (word, bytes) in seed.iter_mut().zip(hash.chunks_exact(4)) {
+            *word = u32::from_be_bytes(bytes.try_into().expect("chunk has four bytes"));
+        }
+        Self::new(seed)
+    }
+
+    pub(crate) fn next_u32(&mut self) -> u32 {
+        if self.idx == 16 {
+            self.update();
+        }
+        let next = self.buff[self.idx];
+        self.idx += 1;
+        next
+    }
+
+    /// The first word is the most significant one.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        let hi = u64::from(self.next_u32());
+        let lo = u64::from(self.next_u32());
+        (hi << 32) | lo
+    }
+
+    pub(crate) fn next_bool(&mut self) -> bool {
+        self.next_u32() & 1 == 1
+    }
+
+    fn update(&mut self) {
+        self.buff = self.state;
+        for _ in 0..10 {
+            double_round(&mut self.buff);
+        }
+        for (b, s) in self.buff.iter_mut().zip(self.state.iter()) {
+            *b = b.wrapping_add(*s);
+        }
+        self.idx = 0;
+
+        // 128 bit counter in the last four words
+        for word in &mut self.state[12..] {
+            *word = word.wrapping_add(1);
+            if *word != 0 {
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! This module defines [`CircomCircuit`], which implements arkworks' [`ConstraintSynthesizer`] for an [`R1CS`] produced by Circom.
+
+use ark_ec::pairing::Pairing;
+use ark_relations::{
+    lc,
+    r1cs::{
+        ConstraintSynthesizer, ConstraintSystemRef, LinearCombination, SynthesisError, Variable,
+    },
+};
+
+use crate::{R1CS, Witness};
+
+/// A Circom circuit, consisting of the [`R1CS`] and optionally a [`Witness`] for it.
+///
+/// Implements [`ConstraintSynthesizer`], so Circom circuits can be used with arkworks' Groth16 setup and
+/// prover or be debugged with a `ConstraintSystem`. The witness is only required for proving and can be
+/// `None` for the setup.
+#[derive(Clone, Debug)]
+pub struct CircomCircuit<P: Pairing> {
+    /// The constraints of the circuit
+    pub r1cs: R1CS<P>,
+    /// The witness, as computed by the witness generator of Circom
+    pub witness: Option<Witness<P::ScalarField>>,
+}
+
+impl<P: Pairing> ConstraintSynthesizer<P::ScalarField> for CircomCircuit<P> {
+    fn generate_constraints(
+        self,
+        cs: ConstraintSystemRef<P::ScalarField>,
+    ) -> Result<(), SynthesisError> {
+        let num_inputs = self.r1cs.num_inputs;
+        let value = |wire: usize| {
+            self.witness
+                .as_ref()
+                .and_then(|witness| witness.values.get(wire).copied())
+                .ok_or(SynthesisError::AssignmentMissing)
+        };
+        // wire 0 is the constant one, which arkworks allocates itself
+        for wire in 1..num_inputs {
+            cs.new_input_variable(|| value(wire))?;
+        }
+        for wire in num_inputs..self.r1cs.num_variables {
+            cs.new_witness_variable(|| value(wire))?;
+        }
+
+        let variable = |wire: usize| match wire {
+            0 => Variable::One,
+            wire if wire < num_inputs => Variable::Instance(wire),
+            wire => Variable::Witness(wire - num_inputs),
+        };
+        let lc = |vec: &[(usize, P::ScalarField)]| {
+            vec.iter().fold(
+                lc!(),
+                |lc: LinearCombination<P::ScalarField>, (wire, coeff)| {
+                    lc + (*coeff, variable(*wire))
+                },
+            )
+        };
+        for (a, b, c) in &self.r1cs.constraints {
+            cs.enforce_constraint(lc(a), lc(b), lc(c))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use std::fs::File;
+
+    use ark_bn254::{Bn254, Fr};
+    use ark_relations::r1cs::ConstraintSystem;
+
+    use super::*;
+    use crate::tests::groth16_bn254_kats;
+
+    fn circuit() -> CircomCircuit<Bn254> {
+        let r1cs = File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let witness = File::open(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        CircomCircuit {
+            r1cs: R1CS::from_reader(r1cs).unwrap(),
+            witness: Some(Witness::from_reader(witness).unwrap()),
+        }
+    }
+
+    #[test]
+    fn can_synthesize_circom_circuit() {
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        circuit().generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.num_instance_variables(), 2);
+        assert_eq!(cs.num_witness_variables(), 2);
+        assert_eq!(cs.num_constraints(), 1);
+        assert!(cs.is_satisfied().unwrap());
+
+        let mut invalid = circuit();
+        invalid.witness.as_mut().unwrap().values[3] += Fr::from(1u64);
+        let cs = ConstraintSystem::<Fr>::new_ref();
+        invalid.generate_constraints(cs.clone()).unwrap();
+        assert_eq!(cs.which_is_unsatisfied().unwrap(), Some("0".to_owned()));
+    }
+
+    #[test]
+    #[cfg(feature = "proof")]
+    fn can_prove_circom_circuit_with_arkworks() {
+        use ark_groth16::Groth16;
+
+        let mut rng = ark_std::test_rng();
+        let setup = CircomCircuit {
+            witness: None,
+            ..circuit()
+        };
+        let pk =
+            Groth16::<Bn254>::generate_random_parameters_with_reduction(setup, &mut rng).unwrap();
+        let circuit = circuit();
+        let public_inputs = circuit.witness.as_ref().unwrap().values[1..2].to_vec();
+        let proof =
+            Groth16::<Bn254>::create_random_proof_with_reduction(circuit, &pk, &mut rng).unwrap();
+        let pvk = ark_groth16::prepare_verifying_key(&pk.vk);
+        assert!(Groth16::<Bn254>::verify_proof(&pvk, &proof, &public_inputs).unwrap());
+        assert!(!Groth16::<Bn254>::verify_proof(&pvk, &proof, &[Fr::from(34u64)]).unwrap());
+    }
+}
@@ -0,0 +1,404 @@
+//! This module defines the [`ZkeyContributions`] struct, the phase-2 MPC transcript stored in
+//! section 10 of a Circom zkey, and the verification of the contribution chain.
+use std::io::{Read, Write};
+
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_serde_compat::CheckElement;
+use blake2::{Blake2b512, Digest};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use thiserror::Error;
+
+use crate::{
+    ParseOptions,
+    binfile::{ContributionParams, ZkeyParserError, ZkeyParserResult, read_array},
+    traits::CircomArkworksPairingBridge,
+};
+
+/// Error type describing an invalid contribution chain, see [`ZkeyContributions::verify_contribution_chain`].
+#[derive(Debug, Error)]
+pub enum ContributionChainError {
+    /// The transcript of the contribution does not match the preceding contributions
+    #[error("contribution {0} has an inconsistent transcript")]
+    InconsistentTranscript(usize),
+    /// The G1 and G2 parts of the public key of the contribution do not have the same ratio
+    #[error("public key of contribution {0} has different ratios in G1 and G2")]
+    InvalidPublicKey(usize),
+    /// The delta after the contribution is not the delta before the contribution scaled by its public key
+    #[error("deltaAfter of contribution {0} does not follow its public key")]
+    InvalidDeltaTransition(usize),
+    /// delta in G1 of the zkey is not the delta after the last contribution
+    #[error("delta in G1 does not match the last contribution")]
+    DeltaMismatch,
+    /// delta in G1 and delta in G2 of the zkey do not have the same discrete logarithm
+    #[error("delta in G1 and delta in G2 are inconsistent")]
+    InconsistentDelta,
+}
+
+/// The phase-2 MPC parameters of a Circom zkey: the hash of the circuit and the history of all contributions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkeyContributions<P: Pairing> {
+    /// The hash of the circuit (`csHash` in snarkjs)
+    pub cs_hash: [u8; 64],
+    /// The contributions in the order they were applied
+    pub contributions: Vec<ZkeyContribution<P>>,
+}
+
+/// A single contribution to the phase-2 MPC.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ZkeyContribution<P: Pairing> {
+    /// delta in G1 after applying this contribution
+    pub delta_after: P::G1Affine,
+    /// the random point `s` of the public key
+    pub g1_s: P::G1Affine,
+    /// `s` multiplied by the secret of the contribution
+    pub g1_sx: P::G1Affine,
+    /// `sp` multiplied by the secret of the contribution, where `sp` is derived from the transcript
+    pub g2_spx: P::G2Affine,
+    /// hash of the preceding contributions and of `g1_s` and `g1_sx`
+    pub transcript: [u8; 64],
+    /// `0` for a regular contribution and `1` for a beacon
+    pub contribution_type: u32,
+    /// the optional name of the contributor
+    pub name: Option<String>,
+    /// the number of iterations of the beacon as power of two, if this is a beacon
+    pub num_iterations_exp: Option<u8>,
+    /// the hash of the beacon, if this is a beacon
+    pub beacon_hash: Option<Vec<u8>>,
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> ZkeyContributions<P> {
+    /// Deserializes the content of section 10 of a zkey.
+    pub fn from_reader<R: Read>(reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default().with_check(check))
+    }
+
+    /// Deserializes the content of section 10 of a zkey with the given [`ParseOptions`].
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        let cs_hash = read_array(&mut reader)?;
+        let num_contributions = reader.read_u32::<LittleEndian>()?;
+        options.check_element_count("contributions", num_contributions as usize)?;
+        let contributions = (0..num_contributions)
+            .map(|_| ZkeyContribution::from_reader(&mut reader, options.check))
+            .collect::<ZkeyParserResult<Vec<_>>>()?;
+        Ok(Self {
+            cs_hash,
+            contributions,
+        })
+    }
+
+    /// Serializes into the content of section 10 of a zkey. Inverse of [`ZkeyContributions::from_reader`].
+    pub fn write_to<W: Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        writer.write_all(&self.cs_hash)?;
+        let num_contributions = u32::try_from(self.contributions.len())
+            .map_err(|_| ZkeyParserError::CorruptedBinFile("too many contributions".to_owned()))?;
+        writer.write_u32::<LittleEndian>(num_contributions)?;
+        for contribution in &self.contributions {
+            contribution.write_to(&mut writer)?;
+        }
+        Ok(())
+    }
+
+    /// Verifies the chain of contributions the same way `snarkjs zkey verify` does.
+    ///
+    /// Starting from the generator of G1, every contribution must scale delta by the secret
+    /// committed to in its public key, which is checked with pairings. Additionally, the
+    /// transcripts must be consistent with the preceding contributions and `delta_g1`/`delta_g2`
+    /// of the zkey must be the result of the last contribution.
+    ///
+    /// Beacons are only checked like regular contributions, i.e., the beacon is not recomputed.
+    pub fn verify_contribution_chain(
+        &self,
+        delta_g1: &P::G1Affine,
+        delta_g2: &P::G2Affine,
+    ) -> Result<(), ContributionChainError> {
+        let mut accumulated_hasher = Blake2b512::new();
+        accumulated_hasher.update(self.cs_hash);
+        let mut cur_delta = P::G1Affine::generator();
+        for (i, contribution) in self.contributions.iter().enumerate() {
+            let mut hasher = accumulated_hasher.clone();
+            hash_point(&mut hasher, &contribution.g1_s);
+            hash_point(&mut hasher, &contribution.g1_sx);
+            if hasher.finalize()[..] != contribution.transcript[..] {
+                return Err(ContributionChainError::InconsistentTranscript(i));
+            }
+
+            let g2_sp = P::hash_to_g2(&contribution.transcript);
+            if !same_ratio::<P>(
+                contribution.g1_s,
+                contribution.g1_sx,
+                g2_sp,
+                contribution.g2_spx,
+            ) {
+                return Err(ContributionChainError::InvalidPublicKey(i));
+            }
+            if !same_ratio::<P>(
+                cur_delta,
+                contribution.delta_after,
+                g2_sp,
+                contribution.g2_spx,
+            ) {
+                return Err(ContributionChainError::InvalidDeltaTransition(i));
+            }
+
+            contribution.hash_public_key(&mut accumulated_hasher);
+            cur_delta = contribution.delta_after;
+        }
+
+        if cur_delta != *delta_g1 {
+            return Err(ContributionChainError::DeltaMismatch);
+        }
+        if !same_ratio::<P>(
+            P::G1Affine::generator(),
+            *delta_g1,
+            P::G2Affine::generator(),
+            *delta_g2,
+        ) {
+            return Err(ContributionChainError::InconsistentDelta);
+        }
+        Ok(())
+    }
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> ZkeyContributions<P> {
+    /// Appends a contribution with the secret `delta` to the chain, where `s` is the random
+    /// scalar of its public key. `delta_after` is the delta in G1 after applying the contribution.
+    pub(crate) fn push(
+        &mut self,
+        delta_after: P::G1Affine,
+        delta: P::ScalarField,
+        s: P::ScalarField,
+    ) -> &ZkeyContribution<P> {
+        let mut hasher = Blake2b512::new();
+        hasher.update(self.cs_hash);
+        for contribution in &self.contributions {
+            contribution.hash_public_key(&mut hasher);
+        }
+        let g1_s = (P::G1Affine::generator() * s).into_affine();
+        let g1_sx = (g1_s * delta).into_affine();
+        hash_point(&mut hasher, &g1_s);
+        hash_point(&mut hasher, &g1_sx);
+        let transcript: [u8; 64] = hasher.finalize().into();
+        let g2_spx = (P::hash_to_g2(&transcript) * delta).into_affine();
+        self.contributions.push(ZkeyContribution {
+            delta_after,
+            g1_s,
+            g1_sx,
+            g2_spx,
+            transcript,
+            contribution_type: 0,
+            name: None,
+            num_iterations_exp: None,
+            beacon_hash: None,
+        });
+        self.contributions.last().expect("was just pushed")
+    }
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> ZkeyContribution<P> {
+    fn from_reader<R: Read>(mut reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        let delta_after = P::g1_from_reader(&mut reader, check)?;
+        let g1_s = P::g1_from_reader(&mut reader, check)?;
+        let g1_sx = P::g1_from_reader(&mut reader, check)?;
+        let g2_spx = P::g2_from_reader(&mut reader, check)?;
+        let transcript = read_array(&mut reader)?;
+        let contribution_type = reader.read_u32::<LittleEndian>()?;
+
+        let ContributionParams {
+            name,
+            num_iterations_exp,
+            beacon_hash,
+        } = ContributionParams::read(&mut reader)?;
+        Ok(Self {
+            delta_after,
+            g1_s,
+            g1_sx,
+            g2_spx,
+            transcript,
+            contribution_type,
+            name,
+            num_iterations_exp,
+            beacon_hash,
+        })
+    }
+
+    fn write_to<W: Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        P::g1_to_writer(&self.delta_after, &mut writer)?;
+        P::g1_to_writer(&self.g1_s, &mut writer)?;
+        P::g1_to_writer(&self.g1_sx, &mut writer)?;
+        P::g2_to_writer(&self.g2_spx, &mut writer)?;
+        writer.write_all(&self.transcript)?;
+        writer.write_u32::<LittleEndian>(self.contribution_type)?;
+
+        ContributionParams {
+            name: self.name.clone(),
+            num_iterations_exp: self.num_iterations_exp,
+            beacon_hash: self.beacon_hash.clone(),
+        }
+        .write(writer)
+    }
+
+    /// The hash identifying this contribution, as printed by snarkjs.
+    pub fn contribution_hash(&self) -> [u8; 64] {
+        let mut hasher = Blake2b512::new();
+        self.hash_public_key(&mut hasher);
+        hasher.finalize().into()
+    }
+
+    fn hash_public_key(&self, hasher: &mut Blake2b512) {
+        hash_point(hasher, &self.delta_after);
+        hash_point(hasher, &self.g1_s);
+        hash_point(hasher, &self.g1_sx);
+        hash_point(hasher, &self.g2_spx);
+        hasher.update(self.transcript);
+    }
+}
+
+/// Hashes a point in the uncompressed big-endian encoding of snarkjs (`toRprUncompressed`).
+/// Extension field elements start with the highest coefficient and the point at infinity is
+/// encoded as all zeros with the second most significant bit set.
+pub(crate) fn hash_point<A: AffineRepr>(hasher: &mut Blake2b512, p: &A) {
+    let n8 = <A::BaseField as Field>::BasePrimeField::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+    let coordinate_size = n8
+        * usize::try_from(A::BaseField::extension_degree())
+            .expect("extension degree fits into usize");
+    let mut buf = Vec::with_capacity(2 * coordinate_size);
+    match p.xy() {
+        Some((x, y)) => {
+            for coordinate in [x, y] {
+                let elements = coordinate
+                    .to_base_prime_field_elements()
+                    .collect::<Vec<_>>();
+                for element in elements.into_iter().rev() {
+                    let bytes = element.into_bigint().to_bytes_be();
+                    buf.extend_from_slice(&bytes[bytes.len() - n8..]);
+                }
+            }
+        }
+        None => {
+            buf.resize(2 * coordinate_size, 0);
+            buf[0] = 0x40;
+        }
+    }
+    hasher.update(&buf);
+}
+
+fn same_ratio<P: Pairing>(
+    g1_a: P::G1Affine,
+    g1_b: P::G1Affine,
+    g2_a: P::G2Affine,
+    g2_b: P::G2Affine,
+) -> bool {
+    P::multi_pairing([g1_a.into_group(), -g1_b.into_group()], [g2_b, g2_a]).is_zero()
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+
+    use super::*;
+    use crate::{binfile::BinFileView, tests::groth16_bn254_kats};
+
+    fn kat() -> (Vec<u8>, G1Affine, G2Affine) {
+        let bytes = std::fs::read(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = crate::groth16::Zkey::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes)
+            .unwrap();
+        (bytes, zkey.delta_g1, zkey.delta_g2)
+    }
+
+    #[test]
+    fn can_read_and_write_bn254_section() {
+        let (bytes, delta_g1, delta_g2) = kat();
+        let section = BinFileView::new(&bytes).unwrap().section(10);
+        let contributions =
+            ZkeyContributions::<Bn254>::from_reader(section, CheckElement::Yes).unwrap();
+        assert_eq!(contributions.contributions.len(), 1);
+        assert_eq!(contributions.contributions[0].delta_after, delta_g1);
+        contributions
+            .verify_contribution_chain(&delta_g1, &delta_g2)
+            .unwrap();
+
+        let mut written = Vec::new();
+        contributions.write_to(&mut written).unwrap();
+        assert_eq!(written, section);
+
+        let options = ParseOptions::default().with_max_elements(0);
+        assert!(matches!(
+            ZkeyContributions::<Bn254>::from_reader_with_options(section, &options),
+            Err(ZkeyParserError::LimitExceeded(_))
+        ));
+        assert!(
+            ZkeyContributions::<Bn254>::from_reader(
+                &section[..section.len() - 1],
+                CheckElement::Yes
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn detects_tampered_bn254_contributions() {
+        let (bytes, delta_g1, delta_g2) = kat();
+        let section = BinFileView::new(&bytes).unwrap().section(10);
+        let kat = ZkeyContributions::<Bn254>::from_reader(section, CheckElement::Yes).unwrap();
+
+        // extend the chain by a second contribution, so that tampering with the first one
+        // must be detected in the middle of the chain
+        let mut contributions = kat.clone();
+        let delta = Fr::from(5u64);
+        let delta_g1 = (delta_g1 * delta).into_affine();
+        let delta_g2 = (delta_g2 * delta).into_affine();
+        contributions.push(delta_g1, delta, Fr::from(3u64));
+        contributions
+            .verify_contribution_chain(&delta_g1, &delta_g2)
+            .unwrap();
+
+        let other = G1Affine::generator();
+        let mut tampered = contributions.clone();
+        tampered.contributions[0].delta_after = other;
+        assert!(matches!(
+            tampered.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::InvalidDeltaTransition(0))
+        ));
+
+        let mut tampered = contributions.clone();
+        tampered.contributions[1].delta_after = other;
+        assert!(matches!(
+            tampered.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::InvalidDeltaTransition(1))
+        ));
+
+        // the proof of knowledge of the secret
+        let mut tampered = contributions.clone();
+        tampered.contributions[0].g2_spx = (tampered.contributions[0].g2_spx * delta).into_affine();
+        assert!(matches!(
+            tampered.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::InvalidPublicKey(0))
+        ));
+
+        let mut tampered = contributions.clone();
+        tampered.contributions[0].g1_sx = other;
+        assert!(matches!(
+            tampered.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::InconsistentTranscript(0))
+        ));
+
+        // the transcript of the second contribution covers the public key of the first one
+        let mut tampered = contributions.clone();
+        tampered.contributions[0].transcript[0] ^= 1;
+        assert!(matches!(
+            tampered.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::InconsistentTranscript(0))
+        ));
+
+        assert!(matches!(
+            kat.verify_contribution_chain(&delta_g1, &delta_g2),
+            Err(ContributionChainError::DeltaMismatch)
+        ));
+    }
+}
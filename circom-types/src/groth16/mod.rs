@@ -1,4 +1,6 @@
 //! This module defines types related to Groth16 used in Circom and utilities to read these types from files.
+#[cfg(feature = "zkey")]
+mod contributions;
 #[cfg(feature = "proof")]
 mod proof;
 #[cfg(feature = "public-input")]
@@ -7,9 +9,15 @@ mod public_input;
 mod verification_key;
 #[cfg(feature = "zkey")]
 mod zkey;
+#[cfg(all(feature = "zkey", feature = "r1cs", feature = "ptau"))]
+mod zkey_setup;
 #[cfg(feature = "zkey")]
 mod zkey_to_ark;
+#[cfg(all(feature = "zkey", feature = "r1cs", feature = "ptau"))]
+mod zkey_verify;
 
+#[cfg(feature = "zkey")]
+pub use contributions::{ContributionChainError, ZkeyContribution, ZkeyContributions};
 #[cfg(feature = "proof")]
 pub use proof::Proof;
 #[cfg(feature = "public-input")]
@@ -17,6 +25,12 @@ pub use public_input::PublicInput;
 #[cfg(feature = "verification-key")]
 pub use verification_key::VerificationKey;
 #[cfg(feature = "zkey")]
-pub use zkey::Zkey;
+pub use zkey::{Zkey, ZkeyView};
+#[cfg(all(feature = "zkey", feature = "r1cs", feature = "ptau"))]
+pub use zkey_setup::ZkeySetupError;
+#[cfg(all(feature = "zkey", feature = "r1cs"))]
+pub use zkey_to_ark::R1CSMismatchError;
 #[cfg(feature = "zkey")]
 pub use zkey_to_ark::{ArkZkey, ConstraintMatricesWrapper};
+#[cfg(all(feature = "zkey", feature = "r1cs", feature = "ptau"))]
+pub use zkey_verify::{SectionReport, ZkeySection, ZkeyVerificationError, ZkeyVerificationReport};
@@ -6,11 +6,18 @@ use ark_groth16::VerifyingKey;
 use ark_serde_compat::CanonicalJsonSerialize;
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "zkey")]
+use crate::groth16::Zkey;
+use crate::traits::CircomArkworksPairingBridge;
+
 /// Represents a verification key in JSON format that was created by Circom. Supports de/serialization using [`serde`].
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct VerificationKey<P: Pairing + CanonicalJsonSerialize> {
     /// The protocol used to generate the proof (always `"groth16"`)
     pub protocol: String,
+    /// The curve used to generate the verification key, empty if the key does not name it
+    #[serde(default)]
+    pub curve: String,
     /// The number of public inputs
     #[serde(rename = "nPublic")]
     pub n_public: usize,
@@ -53,6 +60,26 @@ impl<P: Pairing + CanonicalJsonSerialize> VerificationKey<P> {
     }
 }
 
+impl<P: Pairing + CircomArkworksPairingBridge> VerificationKey<P> {
+    /// Creates a [`VerificationKey`] from an arkworks [`VerifyingKey`].
+    ///
+    /// Computes the pairing of α and β and uses the Circom name of the curve. Inverse of the
+    /// conversion into [`VerifyingKey`].
+    pub fn from_ark(vk: VerifyingKey<P>) -> Self {
+        Self {
+            protocol: "groth16".to_owned(),
+            curve: P::get_circom_name(),
+            n_public: vk.gamma_abc_g1.len().saturating_sub(1),
+            alpha_1: vk.alpha_g1,
+            beta_2: vk.beta_g2,
+            gamma_2: vk.gamma_g2,
+            delta_2: vk.delta_g2,
+            alpha_beta_gt: P::pairing(vk.alpha_g1, vk.beta_g2).0,
+            ic: vk.gamma_abc_g1,
+        }
+    }
+}
+
 impl<P: Pairing + CanonicalJsonSerialize> From<VerificationKey<P>> for VerifyingKey<P> {
     fn from(vk: VerificationKey<P>) -> Self {
         VerifyingKey {
@@ -65,6 +92,24 @@ impl<P: Pairing + CanonicalJsonSerialize> From<VerificationKey<P>> for Verifying
     }
 }
 
+#[cfg(feature = "zkey")]
+impl<P: Pairing + CircomArkworksPairingBridge> From<&Zkey<P>> for VerificationKey<P> {
+    /// Extracts the verification key from a zkey, the equivalent of `snarkjs zkey export verificationkey`.
+    fn from(zkey: &Zkey<P>) -> Self {
+        Self {
+            protocol: "groth16".to_owned(),
+            curve: P::get_circom_name(),
+            n_public: zkey.n_public,
+            alpha_1: zkey.alpha_g1,
+            beta_2: zkey.beta_g2,
+            gamma_2: zkey.gamma_g2,
+            delta_2: zkey.delta_g2,
+            alpha_beta_gt: P::pairing(zkey.alpha_g1, zkey.beta_g2).0,
+            ic: zkey.ic.clone(),
+        }
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "bn254")]
 mod bn254_test {
@@ -136,6 +181,7 @@ mod bn254_test {
         )));
 
         assert_eq!(vk.protocol, "groth16");
+        assert_eq!(vk.curve, "bn128");
         assert_eq!(vk.n_public, 1);
         assert_eq!(vk.alpha_1, alpha_1);
         assert_eq!(vk.beta_2, beta_2);
@@ -147,6 +193,31 @@ mod bn254_test {
         let ser_vk = serde_json::to_string(&vk).unwrap();
         let der_vk = serde_json::from_str::<VerificationKey<Bn254>>(&ser_vk).unwrap();
         assert_eq!(der_vk, vk);
+
+        let ark_vk = ark_groth16::VerifyingKey::from(vk.clone());
+        assert_eq!(VerificationKey::from_ark(ark_vk), vk);
+        // keys that do not name the curve
+        let mut json = serde_json::to_value(&vk).unwrap();
+        json.as_object_mut().unwrap().remove("curve");
+        let without_curve = serde_json::from_value::<VerificationKey<Bn254>>(json).unwrap();
+        assert_eq!(without_curve.curve, "");
+        assert_eq!(without_curve.ic, vk.ic);
+    }
+
+    #[test]
+    #[cfg(feature = "zkey")]
+    fn can_extract_vk_from_zkey() {
+        let kats = groth16_bn254_kats();
+        let vk = VerificationKey::<Bn254>::from_reader(
+            std::fs::File::open(kats.join("verification_key.json")).unwrap(),
+        )
+        .unwrap();
+        let zkey = crate::groth16::Zkey::<Bn254>::from_reader(
+            std::fs::File::open(kats.join("circuit.zkey")).unwrap(),
+            ark_serde_compat::CheckElement::Yes,
+        )
+        .unwrap();
+        assert_eq!(VerificationKey::from(&zkey), vk);
     }
 }
 
@@ -225,6 +296,7 @@ mod bls12_381_test {
         )));
 
         assert_eq!(vk.protocol, "groth16");
+        assert_eq!(vk.curve, "bls12381");
         assert_eq!(vk.n_public, 1);
         assert_eq!(vk.alpha_1, alpha_1);
         assert_eq!(vk.beta_2, beta_2);
@@ -236,6 +308,25 @@ mod bls12_381_test {
         let ser_vk = serde_json::to_string(&vk).unwrap();
         let der_vk = serde_json::from_str::<VerificationKey<Bls12_381>>(&ser_vk).unwrap();
         assert_eq!(der_vk, vk);
+
+        let ark_vk = ark_groth16::VerifyingKey::from(vk.clone());
+        assert_eq!(VerificationKey::from_ark(ark_vk), vk);
+    }
+
+    #[test]
+    #[cfg(feature = "zkey")]
+    fn can_extract_vk_from_zkey() {
+        let kats = groth16_bls12_381_kats();
+        let vk = VerificationKey::<Bls12_381>::from_reader(
+            std::fs::File::open(kats.join("verification_key.json")).unwrap(),
+        )
+        .unwrap();
+        let zkey = crate::groth16::Zkey::<Bls12_381>::from_reader(
+            std::fs::File::open(kats.join("circuit.zkey")).unwrap(),
+            ark_serde_compat::CheckElement::Yes,
+        )
+        .unwrap();
+        assert_eq!(VerificationKey::from(&zkey), vk);
     }
 }
 
@@ -24,17 +24,24 @@
 //IN CONNECTION WITH THE SOFTWARE O THE USE OR OTHER
 //DEALINGS IN THE SOFTWARE.R
 
-//! This module defines the [`Zkey`] struct that implements deserialization of Circom zkey files via [`Zkey::from_reader`].
+//! This module defines the [`Zkey`] struct that implements deserialization of Circom zkey files via [`Zkey::from_reader`]
+//! and serialization via [`Zkey::write_to`].
 //! Inspired by <https://github.com/arkworks-rs/circom-compat/blob/170b10fc9ed182b5f72ecf379033dda023d0bf07/src/zkey.rs>
-use ark_ec::pairing::Pairing;
-use ark_ff::PrimeField;
+use ark_ec::{CurveGroup, pairing::Pairing};
+use ark_ff::{Field, PrimeField};
 use ark_relations::r1cs::Matrix;
-use ark_serialize::CanonicalDeserialize;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use blake2::{Blake2b512, Digest};
 
-use std::io::Read;
+use std::io::{Read, Write};
+#[cfg(feature = "mmap")]
+use std::path::Path;
 
 use crate::{
-    binfile::{BinFile, ZkeyParserError, ZkeyParserResult},
+    ParseOptions,
+    binfile::{BinFile, BinFileView, BinFileWriter, ZkeyParserError, ZkeyParserResult},
+    groth16::{ContributionChainError, ZkeyContributions},
+    options::scope,
     traits::CircomArkworksPairingBridge,
 };
 
@@ -45,6 +52,19 @@ macro_rules! u32_to_usize {
         usize::try_from($x).expect("u32 fits into usize")
     };
 }
+
+macro_rules! usize_to_u32 {
+    ($x: expr) => {
+        u32::try_from($x).map_err(|_| {
+            ZkeyParserError::CorruptedBinFile(format!("{} does not fit into u32", $x))
+        })?
+    };
+}
+
+const ZKEY_HEADER: &str = "zkey";
+const ZKEY_VERSION: u32 = 1;
+const GROTH16_PROTOCOL_ID: u32 = 1;
+
 /// Represents a zkey in the format defined by Circom. Implements [`Zkey::from_reader`] to deserialize a zkey from a reader.
 #[derive(Clone)]
 pub struct Zkey<P: Pairing> {
@@ -82,11 +102,10 @@ pub struct Zkey<P: Pairing> {
     pub a_matrix: Matrix<P::ScalarField>,
     /// The constraint matrices B
     pub b_matrix: Matrix<P::ScalarField>,
+    /// The phase-2 MPC parameters (hash of the circuit and the contributions). `None` if the zkey has no such section.
+    pub contributions: Option<ZkeyContributions<P>>,
 }
 
-/// A constraint matrix used in Groth16.
-type ConstraintMatrixAB<F> = (usize, Matrix<F>, Matrix<F>);
-
 #[derive(Clone, Debug)]
 struct HeaderGroth<P: Pairing> {
     n_vars: usize,
@@ -113,25 +132,291 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
     /// only with care.
     ///
     /// See [`CheckElement`].
-    pub fn from_reader<R: Read>(mut reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
-        let mut binfile = BinFile::<P>::new(&mut reader)?;
+    pub fn from_reader<R: Read>(reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default().with_check(check))
+    }
 
-        tracing::debug!("start transforming bin file into zkey...");
-        let header = HeaderGroth::<P>::read(&mut binfile.take_section(2), check)?;
-        let n_vars = header.n_vars;
-        let n_public = header.n_public;
-        let domain_size = usize::try_from(header.domain_size).expect("fits into usize");
+    /// Deserializes a [`Zkey`] from a reader with the given [`ParseOptions`].
+    ///
+    /// The sections are decoded in parallel on the configured thread pool.
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        let binfile = BinFile::<P>::new(&mut reader, options)?;
+        ZkeyView::from_binfile(binfile.view(), options)?.decode()
+    }
+
+    /// Deserializes a [`Zkey`] from the file at `path` by memory-mapping it.
+    ///
+    /// In contrast to [`Zkey::from_reader`], the raw sections are not copied into memory
+    /// before decoding them. Use [`ZkeyView`] directly to decode the sections on demand.
+    ///
+    /// See [`Zkey::from_reader`] for the semantics of `check`.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap(path: impl AsRef<Path>, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_mmap_with_options(path, &ParseOptions::default().with_check(check))
+    }
+
+    /// Deserializes a [`Zkey`] from the file at `path` by memory-mapping it, with the given
+    /// [`ParseOptions`].
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap_with_options(
+        path: impl AsRef<Path>,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map only lives for the duration of this function. Modifying the
+        // file concurrently is undefined behaviour, as with every memory-mapped file.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        ZkeyView::<P>::with_options(&mmap, options)?.decode()
+    }
+
+    /// Serializes the [`Zkey`] into a writer in the format produced by snarkjs.
+    ///
+    /// Writing a [`Zkey`] obtained from [`Zkey::from_reader`] reproduces the original file.
+    pub fn write_to<W: Write>(&self, writer: W) -> ZkeyParserResult<()> {
+        tracing::debug!("writing groth16 zkey...");
+        let num_sections = if self.contributions.is_some() { 10 } else { 9 };
+        let mut binfile = BinFileWriter::new(writer, ZKEY_HEADER, ZKEY_VERSION, num_sections)?;
+        binfile.write_section(1, &GROTH16_PROTOCOL_ID.to_le_bytes())?;
+
+        let mut section = Vec::new();
+        self.write_header(&mut section)?;
+        binfile.write_section(2, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.ic, &mut section)?;
+        binfile.write_section(3, &section)?;
+
+        section.clear();
+        self.write_coefficients(&mut section)?;
+        binfile.write_section(4, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.a_query, &mut section)?;
+        binfile.write_section(5, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.b_g1_query, &mut section)?;
+        binfile.write_section(6, &section)?;
+
+        section.clear();
+        P::g2_vec_to_writer(&self.b_g2_query, &mut section)?;
+        binfile.write_section(7, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.l_query, &mut section)?;
+        binfile.write_section(8, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.h_query, &mut section)?;
+        binfile.write_section(9, &section)?;
 
-        // parse proving key
+        if let Some(contributions) = &self.contributions {
+            section.clear();
+            contributions.write_to(&mut section)?;
+            binfile.write_section(10, &section)?;
+        }
+        binfile.finish()?;
+        tracing::debug!("groth16 zkey writing done!");
+        Ok(())
+    }
+
+    /// Verifies the phase-2 contributions of the [`Zkey`], see [`ZkeyContributions::verify_contribution_chain`].
+    ///
+    /// A zkey without contributions must still have the generators as delta.
+    pub fn verify_contribution_chain(&self) -> Result<(), ContributionChainError> {
+        match &self.contributions {
+            Some(contributions) => {
+                contributions.verify_contribution_chain(&self.delta_g1, &self.delta_g2)
+            }
+            None => ZkeyContributions::<P> {
+                cs_hash: [0; 64],
+                contributions: vec![],
+            }
+            .verify_contribution_chain(&self.delta_g1, &self.delta_g2),
+        }
+    }
+
+    /// Applies a phase-2 contribution to the [`Zkey`], the equivalent of `snarkjs zkey contribute`.
+    ///
+    /// The secret of the contribution is derived from `entropy`, so callers must provide enough
+    /// fresh randomness. Delta is multiplied by the secret and the l_query and h_query are divided
+    /// by it. The contribution is appended to [`Zkey::contributions`], which is created with an
+    /// all-zero hash of the circuit if the zkey has no contributions section.
+    ///
+    /// Returns the hash of the contribution, as printed by snarkjs.
+    pub fn contribute(&mut self, entropy: &[u8]) -> [u8; 64] {
+        let derive = |domain: &[u8]| {
+            let hash = Blake2b512::new()
+                .chain_update(domain)
+                .chain_update(entropy)
+                .finalize();
+            P::ScalarField::from_le_bytes_mod_order(&hash)
+        };
+        let delta = derive(b"delta");
+        let delta_inv = delta
+            .inverse()
+            .expect("secret derived from a hash is not zero");
 
-        let ic_section = binfile.take_section(3);
-        let matrices_section = binfile.take_section(4);
-        let a_section = binfile.take_section(5);
-        let b_g1_section = binfile.take_section(6);
-        let b_g2_section = binfile.take_section(7);
-        let l_section = binfile.take_section(8);
-        let h_section = binfile.take_section(9);
+        self.delta_g1 = (self.delta_g1 * delta).into_affine();
+        self.delta_g2 = (self.delta_g2 * delta).into_affine();
+        let scale = |points: &[P::G1Affine]| {
+            P::G1::normalize_batch(&points.iter().map(|p| *p * delta_inv).collect::<Vec<_>>())
+        };
+        self.l_query = scale(&self.l_query);
+        self.h_query = scale(&self.h_query);
 
+        self.contributions
+            .get_or_insert_with(|| ZkeyContributions {
+                cs_hash: [0; 64],
+                contributions: vec![],
+            })
+            .push(self.delta_g1, delta, derive(b"s"))
+            .contribution_hash()
+    }
+
+    fn write_header<W: Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        P::BaseField::MODULUS_BIT_SIZE
+            .div_ceil(8)
+            .serialize_uncompressed(&mut writer)?;
+        <P::BaseField as PrimeField>::MODULUS.serialize_uncompressed(&mut writer)?;
+        P::ScalarField::MODULUS_BIT_SIZE
+            .div_ceil(8)
+            .serialize_uncompressed(&mut writer)?;
+        <P::ScalarField as PrimeField>::MODULUS.serialize_uncompressed(&mut writer)?;
+        usize_to_u32!(self.a_query.len()).serialize_uncompressed(&mut writer)?;
+        usize_to_u32!(self.n_public).serialize_uncompressed(&mut writer)?;
+        usize_to_u32!(1usize << self.pow).serialize_uncompressed(&mut writer)?;
+        P::g1_to_writer(&self.alpha_g1, &mut writer)?;
+        P::g1_to_writer(&self.beta_g1, &mut writer)?;
+        P::g2_to_writer(&self.beta_g2, &mut writer)?;
+        P::g2_to_writer(&self.gamma_g2, &mut writer)?;
+        P::g1_to_writer(&self.delta_g1, &mut writer)?;
+        P::g2_to_writer(&self.delta_g2, &mut writer)?;
+        Ok(())
+    }
+
+    fn write_coefficients<W: Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        // snarkjs emits the coefficients constraint by constraint (first A then B),
+        // followed by the constraints it adds for the public inputs
+        let num_coeffs = self.a_matrix.iter().map(Vec::len).sum::<usize>()
+            + self.b_matrix.iter().map(Vec::len).sum::<usize>()
+            + self.n_public
+            + 1;
+        usize_to_u32!(num_coeffs).serialize_uncompressed(&mut writer)?;
+        let mut write_coeff = |matrix: u32,
+                               constraint: usize,
+                               signal: usize,
+                               value: &P::ScalarField|
+         -> ZkeyParserResult<()> {
+            matrix.serialize_uncompressed(&mut writer)?;
+            usize_to_u32!(constraint).serialize_uncompressed(&mut writer)?;
+            usize_to_u32!(signal).serialize_uncompressed(&mut writer)?;
+            P::fr_to_writer_for_groth16_zkey(value, &mut writer)?;
+            Ok(())
+        };
+        for (constraint, (a, b)) in self.a_matrix.iter().zip(self.b_matrix.iter()).enumerate() {
+            for (value, signal) in a {
+                write_coeff(0, constraint, *signal, value)?;
+            }
+            for (value, signal) in b {
+                write_coeff(1, constraint, *signal, value)?;
+            }
+        }
+        for signal in 0..=self.n_public {
+            write_coeff(
+                0,
+                self.num_constraints + signal,
+                signal,
+                &P::ScalarField::ONE,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A lazily decoded view on a zkey in the format defined by Circom.
+///
+/// Creating the view only decodes the header. All other sections are borrowed from the
+/// underlying bytes and decoded on demand, e.g., from a memory-mapped file:
+/// ```no_run
+/// # #[cfg(feature = "bn254")]
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// use taceo_circom_types::{CheckElement, ark_bn254::Bn254, groth16::ZkeyView};
+/// let file = std::fs::File::open("circuit.zkey")?;
+/// let mmap = unsafe { memmap2::Mmap::map(&file)? };
+/// let zkey = ZkeyView::<Bn254>::new(&mmap, CheckElement::Yes)?;
+/// let h_query = zkey.h_query()?;
+/// # Ok(())
+/// # }
+/// # #[cfg(not(feature = "bn254"))]
+/// # fn main() {}
+/// ```
+/// Use [`ZkeyView::decode`] to decode all sections into a [`Zkey`].
+#[derive(Clone, Debug)]
+pub struct ZkeyView<'a, P: Pairing> {
+    header: HeaderGroth<P>,
+    sections: BinFileView<'a>,
+    options: ParseOptions,
+}
+
+impl<'a, P: Pairing + CircomArkworksPairingBridge> ZkeyView<'a, P> {
+    /// Creates a [`ZkeyView`] on the zkey contained in `bytes`.
+    ///
+    /// See [`Zkey::from_reader`] for the semantics of `check`, which applies to
+    /// all elements decoded through this view.
+    pub fn new(bytes: &'a [u8], check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::with_options(bytes, &ParseOptions::default().with_check(check))
+    }
+
+    /// Creates a [`ZkeyView`] on the zkey contained in `bytes` with the given [`ParseOptions`],
+    /// which apply to all elements decoded through this view.
+    pub fn with_options(bytes: &'a [u8], options: &ParseOptions) -> ZkeyParserResult<Self> {
+        Self::from_binfile(BinFileView::new(bytes)?, options)
+    }
+
+    fn from_binfile(sections: BinFileView<'a>, options: &ParseOptions) -> ZkeyParserResult<Self> {
+        tracing::debug!("start transforming bin file into zkey...");
+        let header = HeaderGroth::<P>::read(&mut sections.section(2), options.check)?;
+        options.check_element_count("variables", header.n_vars)?;
+        options.check_element_count("public inputs", header.n_public)?;
+        options.check_element_count("domain elements", u32_to_usize!(header.domain_size))?;
+        for (id, section) in sections.iter() {
+            options.check_section_size(id, section.len() as u64)?;
+        }
+        // the sizes of the point sections are determined by the header, so checking them
+        // up front bounds everything allocated while decoding by the size of the file
+        let g1 = P::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED;
+        let g2 = P::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED;
+        let n_private = header.n_vars - header.n_public - 1;
+        for (id, num, size) in [
+            (3, header.n_public + 1, g1),
+            (5, header.n_vars, g1),
+            (6, header.n_vars, g1),
+            (7, header.n_vars, g2),
+            (8, n_private, g1),
+            (9, u32_to_usize!(header.domain_size), g1),
+        ] {
+            if num
+                .checked_mul(size)
+                .is_none_or(|len| sections.section(id).len() < len)
+            {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "section {id} is too short for {num} points"
+                )));
+            }
+        }
+        Ok(Self {
+            header,
+            sections,
+            options: options.clone(),
+        })
+    }
+
+    /// Decodes all sections into a [`Zkey`], in parallel on the thread pool of the [`ParseOptions`].
+    pub fn decode(&self) -> ZkeyParserResult<Zkey<P>> {
         let mut ic = None;
         let mut a_query = None;
         let mut b_g1_query = None;
@@ -141,99 +426,148 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
         let mut matrices = None;
 
         tracing::debug!("parsing zkey sections...");
-        std::thread::scope(|s| {
-            s.spawn(|| ic = Some(Self::ic(n_public + 1, ic_section, check)));
-            s.spawn(|| a_query = Some(Self::a_query(n_vars, a_section, check)));
-            s.spawn(|| b_g1_query = Some(Self::b_g1_query(n_vars, b_g1_section, check)));
-            s.spawn(|| b_g2_query = Some(Self::b_g2_query(n_vars, b_g2_section, check)));
-            s.spawn(|| l_query = Some(Self::l_query(n_vars - n_public - 1, l_section, check)));
-            s.spawn(|| h_query = Some(Self::h_query(domain_size, h_section, check)));
-            s.spawn(|| {
-                matrices = Some(Self::constraint_matrices(
-                    domain_size,
-                    n_public,
-                    matrices_section,
-                ))
-            });
+        self.options.install(|| {
+            scope(|s| {
+                s.spawn(|| ic = Some(self.ic()));
+                s.spawn(|| a_query = Some(self.a_query()));
+                s.spawn(|| b_g1_query = Some(self.b_g1_query()));
+                s.spawn(|| b_g2_query = Some(self.b_g2_query()));
+                s.spawn(|| l_query = Some(self.l_query()));
+                s.spawn(|| h_query = Some(self.h_query()));
+                s.spawn(|| matrices = Some(self.constraint_matrices()));
+            })
         });
         let (num_constraints, a_matrix, b_matrix) = matrices.unwrap()?;
 
         tracing::debug!("groth16 zkey parsing done!");
         Ok(Zkey {
-            n_public: header.n_public,
-            pow: u32_to_usize!(header.pow),
+            n_public: self.header.n_public,
+            pow: self.header.pow,
             num_constraints,
-            beta_g1: header.beta_g1,
-            delta_g1: header.delta_g1,
+            beta_g1: self.header.beta_g1,
+            delta_g1: self.header.delta_g1,
             // unwrap is fine, because we are guaranteed to have a Some value
             a_query: a_query.unwrap()?,
             b_g1_query: b_g1_query.unwrap()?,
             b_g2_query: b_g2_query.unwrap()?,
             h_query: h_query.unwrap()?,
             l_query: l_query.unwrap()?,
-            alpha_g1: header.alpha_g1,
-            beta_g2: header.beta_g2,
-            delta_g2: header.delta_g2,
-            gamma_g2: header.gamma_g2,
+            alpha_g1: self.header.alpha_g1,
+            beta_g2: self.header.beta_g2,
+            delta_g2: self.header.delta_g2,
+            gamma_g2: self.header.gamma_g2,
             a_matrix,
             b_matrix,
             ic: ic.unwrap()?,
+            contributions: self.contributions()?,
         })
     }
 
-    fn a_query<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
-        Ok(P::g1_vec_from_reader(reader, n_vars, check)?)
+    /// The amount of public inputs
+    pub fn n_public(&self) -> usize {
+        self.header.n_public
     }
 
-    fn ic<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
-        Ok(P::g1_vec_from_reader(reader, n_vars, check)?)
+    /// The amount of variables (signals) in the circuit
+    pub fn n_vars(&self) -> usize {
+        self.header.n_vars
     }
 
-    fn b_g1_query<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
-        Ok(P::g1_vec_from_reader(reader, n_vars, check)?)
+    /// ld(domain size)
+    pub fn pow(&self) -> usize {
+        self.header.pow
     }
 
-    fn b_g2_query<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G2Affine>> {
-        Ok(P::g2_vec_from_reader(reader, n_vars, check)?)
+    /// The domain size (power of two)
+    pub fn domain_size(&self) -> usize {
+        u32_to_usize!(self.header.domain_size)
     }
 
-    fn l_query<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
-        Ok(P::g1_vec_from_reader(reader, n_vars, check)?)
+    /// alpha_g1
+    pub fn alpha_g1(&self) -> P::G1Affine {
+        self.header.alpha_g1
     }
 
-    fn h_query<R: Read>(
-        n_vars: usize,
-        reader: R,
-        check: CheckElement,
-    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
-        Ok(P::g1_vec_from_reader(reader, n_vars, check)?)
+    /// beta_g1
+    pub fn beta_g1(&self) -> P::G1Affine {
+        self.header.beta_g1
     }
 
-    fn constraint_matrices<R: Read>(
-        domain_size: usize,
-        n_public: usize,
-        mut matrices_section: R,
-    ) -> ZkeyParserResult<ConstraintMatrixAB<P::ScalarField>> {
+    /// beta_g2
+    pub fn beta_g2(&self) -> P::G2Affine {
+        self.header.beta_g2
+    }
+
+    /// gamma_g2
+    pub fn gamma_g2(&self) -> P::G2Affine {
+        self.header.gamma_g2
+    }
+
+    /// delta_g1
+    pub fn delta_g1(&self) -> P::G1Affine {
+        self.header.delta_g1
+    }
+
+    /// delta_g2
+    pub fn delta_g2(&self) -> P::G2Affine {
+        self.header.delta_g2
+    }
+
+    /// Decodes the elements used to bind the public inputs to the proof
+    pub fn ic(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_section(3, self.header.n_public + 1)
+    }
+
+    /// Decodes the a_query
+    pub fn a_query(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_section(5, self.header.n_vars)
+    }
+
+    /// Decodes the b_query in G1
+    pub fn b_g1_query(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_section(6, self.header.n_vars)
+    }
+
+    /// Decodes the b_query in G2
+    pub fn b_g2_query(&self) -> ZkeyParserResult<Vec<P::G2Affine>> {
+        let bytes = Self::prefix(
+            self.sections.section(7),
+            self.header.n_vars * P::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED,
+        )?;
+        let check = self.options.check;
+        Ok(self
+            .options
+            .install(|| P::g2_vec_from_bytes(bytes, check))?)
+    }
+
+    /// Decodes the l_query
+    pub fn l_query(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_section(8, self.header.n_vars - self.header.n_public - 1)
+    }
+
+    /// Decodes the h_query
+    pub fn h_query(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_section(9, self.domain_size())
+    }
+
+    /// Decodes the phase-2 MPC parameters, see [`Zkey::contributions`]
+    pub fn contributions(&self) -> ZkeyParserResult<Option<ZkeyContributions<P>>> {
+        let section = self.sections.section(10);
+        if section.is_empty() {
+            return Ok(None);
+        }
+        ZkeyContributions::from_reader_with_options(section, &self.options).map(Some)
+    }
+
+    /// Decodes the amount of constraints and the constraint matrices A and B.
+    /// The constraints snarkjs adds for the public inputs are removed, as arkworks adds them later.
+    #[expect(clippy::type_complexity)]
+    pub fn constraint_matrices(
+        &self,
+    ) -> ZkeyParserResult<(usize, Matrix<P::ScalarField>, Matrix<P::ScalarField>)> {
+        let domain_size = self.domain_size();
+        let n_public = self.header.n_public;
+        let mut matrices_section = self.sections.section(4);
         // this function (an all following uses) assumes that values are encoded in little-endian
         let num_coeffs = u32::deserialize_uncompressed(&mut matrices_section)?;
 
@@ -248,11 +582,31 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
             let signal = u32::deserialize_uncompressed(&mut matrices_section)?;
 
             let value = P::fr_from_reader_for_groth16_zkey(&mut matrices_section)?;
+            if u32_to_usize!(signal) >= self.header.n_vars {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "coefficient references signal {signal}, but there are only {} signals",
+                    self.header.n_vars
+                )));
+            }
             max_constraint_index = std::cmp::max(max_constraint_index, constraint);
-            matrices[matrix as usize][constraint as usize].push((value, signal as usize));
+            let row = matrices
+                .get_mut(u32_to_usize!(matrix))
+                .and_then(|m| m.get_mut(u32_to_usize!(constraint)))
+                .ok_or_else(|| {
+                    ZkeyParserError::CorruptedBinFile(format!(
+                        "invalid coefficient position: matrix {matrix}, constraint {constraint}"
+                    ))
+                })?;
+            row.push((value, u32_to_usize!(signal)));
         }
 
-        let num_constraints = max_constraint_index as usize - n_public;
+        let num_constraints = u32_to_usize!(max_constraint_index)
+            .checked_sub(n_public)
+            .ok_or_else(|| {
+                ZkeyParserError::CorruptedBinFile(
+                    "missing the constraints of the public inputs".to_owned(),
+                )
+            })?;
         // Remove the public input constraints, Arkworks adds them later
         matrices.iter_mut().for_each(|m| {
             m.truncate(num_constraints);
@@ -261,6 +615,23 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
         let [a, b] = matrices;
         Ok((num_constraints, a, b))
     }
+
+    fn g1_section(&self, id: u32, num: usize) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        let bytes = Self::prefix(
+            self.sections.section(id),
+            num * P::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED,
+        )?;
+        let check = self.options.check;
+        Ok(self
+            .options
+            .install(|| P::g1_vec_from_bytes(bytes, check))?)
+    }
+
+    fn prefix(section: &[u8], len: usize) -> ZkeyParserResult<&[u8]> {
+        section
+            .get(..len)
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into())
+    }
 }
 
 impl<P: Pairing + CircomArkworksPairingBridge> HeaderGroth<P> {
@@ -296,7 +667,12 @@ impl<P: Pairing + CircomArkworksPairingBridge> HeaderGroth<P> {
         let n_public = u32_to_usize!(u32::deserialize_uncompressed(&mut reader)?);
         let domain_size = u32::deserialize_uncompressed(&mut reader)?;
         tracing::debug!("n_vars: {n_vars}; n_public: {n_public}, domain_size: {domain_size}");
-        if domain_size & (domain_size - 1) == 0 && domain_size > 0 {
+        if n_public >= n_vars {
+            return Err(ZkeyParserError::CorruptedBinFile(format!(
+                "{n_public} public inputs, but only {n_vars} variables"
+            )));
+        }
+        if domain_size.is_power_of_two() {
             let alpha_g1 = P::g1_from_reader(&mut reader, check)?;
             let beta_g1 = P::g1_from_reader(&mut reader, check)?;
             let beta_g2 = P::g2_from_reader(&mut reader, check)?;
@@ -437,6 +813,31 @@ mod bls12_381_tests {
             assert_eq!(delta_g2, pk.delta_g2);
         }
     }
+
+    #[test]
+    fn can_verify_bls12_381_contributions() {
+        use crate::tests::groth16_bls12_381_kats;
+        use ark_bls12_381::Bls12_381;
+        let zkey = Zkey::<Bls12_381>::from_reader(
+            std::fs::File::open(groth16_bls12_381_kats().join("circuit.zkey")).unwrap(),
+            CheckElement::Yes,
+        )
+        .unwrap();
+        let contributions = zkey.contributions.as_ref().unwrap();
+        assert_eq!(contributions.contributions.len(), 1);
+        zkey.verify_contribution_chain().unwrap();
+    }
+
+    #[test]
+    fn can_write_bls12_381_mult2_key() {
+        use crate::tests::groth16_bls12_381_kats;
+        use ark_bls12_381::Bls12_381;
+        let should = std::fs::read(groth16_bls12_381_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bls12_381>::from_reader(should.as_slice(), CheckElement::Yes).unwrap();
+        let mut is = Vec::new();
+        zkey.write_to(&mut is).unwrap();
+        assert_eq!(is, should);
+    }
 }
 
 #[cfg(test)]
@@ -571,6 +972,199 @@ mod bn254_tests {
             assert_eq!(b, pk.b_matrix);
         }
     }
+
+    #[test]
+    fn can_write_bn254_mult2_key() {
+        let should = std::fs::read(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bn254>::from_reader(should.as_slice(), CheckElement::Yes).unwrap();
+        let mut is = Vec::new();
+        zkey.write_to(&mut is).unwrap();
+        assert_eq!(is, should);
+
+        // a zkey without MPC section can be written and read again
+        let stripped = Zkey {
+            contributions: None,
+            ..zkey
+        };
+        let mut buf = Vec::new();
+        stripped.write_to(&mut buf).unwrap();
+        let read = Zkey::<Bn254>::from_reader(buf.as_slice(), CheckElement::Yes).unwrap();
+        assert!(read.contributions.is_none());
+        assert_eq!(read.ic, stripped.ic);
+        assert_eq!(read.h_query, stripped.h_query);
+        assert_eq!(read.a_matrix, stripped.a_matrix);
+        assert_eq!(read.b_matrix, stripped.b_matrix);
+    }
+
+    #[test]
+    fn can_verify_bn254_contributions() {
+        let zkey = Zkey::<Bn254>::from_reader(
+            File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap(),
+            CheckElement::Yes,
+        )
+        .unwrap();
+        let contributions = zkey.contributions.as_ref().unwrap();
+        assert_eq!(contributions.contributions.len(), 1);
+        let contribution = &contributions.contributions[0];
+        assert_eq!(contribution.contribution_type, 0);
+        assert_eq!(contribution.delta_after, zkey.delta_g1);
+        zkey.verify_contribution_chain().unwrap();
+
+        let mut tampered = zkey.clone();
+        tampered.contributions.as_mut().unwrap().contributions[0].g1_sx = zkey.alpha_g1;
+        assert!(matches!(
+            tampered.verify_contribution_chain(),
+            Err(ContributionChainError::InconsistentTranscript(0))
+        ));
+        let mut tampered = zkey.clone();
+        tampered.contributions.as_mut().unwrap().contributions[0].delta_after = zkey.alpha_g1;
+        assert!(matches!(
+            tampered.verify_contribution_chain(),
+            Err(ContributionChainError::InvalidDeltaTransition(0))
+        ));
+        let tampered = Zkey {
+            contributions: None,
+            ..zkey
+        };
+        assert!(matches!(
+            tampered.verify_contribution_chain(),
+            Err(ContributionChainError::DeltaMismatch)
+        ));
+    }
+
+    #[test]
+    fn can_view_bn254_mult2_key() {
+        let bytes = std::fs::read(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes).unwrap();
+        let view = ZkeyView::<Bn254>::new(&bytes, CheckElement::Yes).unwrap();
+        assert_eq!(view.n_public(), 1);
+        assert_eq!(view.n_vars(), 4);
+        assert_eq!(view.domain_size(), 4);
+        assert_eq!(view.pow(), zkey.pow);
+        assert_eq!(view.alpha_g1(), zkey.alpha_g1);
+        assert_eq!(view.delta_g2(), zkey.delta_g2);
+        assert_eq!(view.ic().unwrap(), zkey.ic);
+        assert_eq!(view.a_query().unwrap(), zkey.a_query);
+        assert_eq!(view.b_g2_query().unwrap(), zkey.b_g2_query);
+        assert_eq!(view.l_query().unwrap(), zkey.l_query);
+        assert_eq!(view.h_query().unwrap(), zkey.h_query);
+        let (num_constraints, a, b) = view.constraint_matrices().unwrap();
+        assert_eq!(num_constraints, zkey.num_constraints);
+        assert_eq!(a, zkey.a_matrix);
+        assert_eq!(b, zkey.b_matrix);
+
+        // truncated files are rejected when creating the view, before decoding any section
+        let truncated = &bytes[..bytes.len() - 1];
+        assert!(ZkeyView::<Bn254>::new(truncated, CheckElement::Yes).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "mmap")]
+    fn can_mmap_bn254_mult2_key() {
+        let path = groth16_bn254_kats().join("circuit.zkey");
+        let zkey = Zkey::<Bn254>::from_mmap(&path, CheckElement::Yes).unwrap();
+        let should = std::fs::read(&path).unwrap();
+        let mut is = Vec::new();
+        zkey.write_to(&mut is).unwrap();
+        assert_eq!(is, should);
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn can_deser_bn254_mult2_key_on_thread_pool() {
+        use crate::options::tests::decode_on_blocked_thread_pool;
+
+        let should = std::fs::read(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let written = |zkey: Zkey<Bn254>| {
+            let mut is = Vec::new();
+            zkey.write_to(&mut is).unwrap();
+            is
+        };
+        let zkey = decode_on_blocked_thread_pool(|options| {
+            Zkey::<Bn254>::from_reader_with_options(should.as_slice(), options)
+        });
+        assert_eq!(written(zkey.unwrap()), should);
+
+        let zkey = decode_on_blocked_thread_pool(|options| {
+            ZkeyView::<Bn254>::with_options(&should, options)?.decode()
+        });
+        assert_eq!(written(zkey.unwrap()), should);
+    }
+
+    #[test]
+    #[cfg(all(feature = "mmap", feature = "parallel"))]
+    fn can_mmap_bn254_mult2_key_with_options() {
+        use crate::options::tests::decode_on_blocked_thread_pool;
+
+        let path = groth16_bn254_kats().join("circuit.zkey");
+        let zkey = decode_on_blocked_thread_pool(|options| {
+            Zkey::<Bn254>::from_mmap_with_options(&path, &options.clone().with_max_elements(4))
+        });
+        let mut is = Vec::new();
+        zkey.unwrap().write_to(&mut is).unwrap();
+        assert_eq!(is, std::fs::read(&path).unwrap());
+
+        let options = ParseOptions::default().with_max_elements(3);
+        assert!(matches!(
+            Zkey::<Bn254>::from_mmap_with_options(&path, &options),
+            Err(ZkeyParserError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_bn254_zkey() {
+        let bytes = std::fs::read(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        for malformed in crate::tests::malformed(&bytes) {
+            // must not panic, corruptions of points are only detected with checks
+            let _ = Zkey::<Bn254>::from_reader(malformed.as_slice(), CheckElement::No);
+            let _ =
+                ZkeyView::<Bn254>::new(&malformed, CheckElement::No).and_then(|view| view.decode());
+        }
+
+        // a header announcing more variables than the sections contain
+        let mut huge = bytes.clone();
+        let n_vars = 12 + 12 + 4 + 12 + 4 + 32 + 4 + 32;
+        huge[n_vars..n_vars + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Zkey::<Bn254>::from_reader(huge.as_slice(), CheckElement::No),
+            Err(ZkeyParserError::CorruptedBinFile(_))
+        ));
+        // an unreduced coordinate of alpha
+        let mut unreduced = bytes.clone();
+        unreduced[n_vars + 12..n_vars + 44].fill(0xff);
+        assert!(matches!(
+            Zkey::<Bn254>::from_reader(unreduced.as_slice(), CheckElement::Yes),
+            Err(ZkeyParserError::SerializationError(_))
+        ));
+        // a section id of 0
+        let mut invalid_id = bytes.clone();
+        invalid_id[12..16].copy_from_slice(&0u32.to_le_bytes());
+        assert!(matches!(
+            Zkey::<Bn254>::from_reader(invalid_id.as_slice(), CheckElement::No),
+            Err(ZkeyParserError::CorruptedBinFile(_))
+        ));
+        assert!(matches!(
+            Zkey::<Bn254>::from_reader_with_options(
+                bytes.as_slice(),
+                &ParseOptions::default().with_max_section_size(256)
+            ),
+            Err(ZkeyParserError::LimitExceeded(_))
+        ));
+        assert!(matches!(
+            ZkeyView::<Bn254>::with_options(&bytes, &ParseOptions::default().with_max_elements(2)),
+            Err(ZkeyParserError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn can_ser_g1_g2() {
+        let mut buf = Vec::new();
+        <Bn254 as CircomArkworksPairingBridge>::g1_to_writer(&g1_one(), &mut buf).unwrap();
+        assert_eq!(buf, g1_buf());
+        buf.clear();
+        <Bn254 as CircomArkworksPairingBridge>::g2_to_writer(&g2_one(), &mut buf).unwrap();
+        assert_eq!(buf, g2_buf());
+    }
     fn fq_from_str(s: &str) -> Fq {
         BigInteger256::try_from(BigUint::from_str(s).unwrap())
             .unwrap()
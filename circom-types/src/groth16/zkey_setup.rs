@@ -0,0 +1,264 @@
+//! This module implements [`Zkey::setup`], the equivalent of `snarkjs groth16 setup`, which
+//! derives a zkey from an [`R1CS`] and a [`PowersOfTau`].
+use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+use ark_ff::Zero;
+use blake2::{Blake2b512, Digest};
+use thiserror::Error;
+
+use crate::{
+    PowersOfTau, R1CS,
+    groth16::{Zkey, ZkeyContributions, contributions::hash_point},
+    traits::CircomArkworksPairingBridge,
+};
+
+/// snarkjs hashes the h_query in chunks of this many points
+const H_HASH_CHUNK_SIZE: usize = 1 << 14;
+
+/// Error type describing why a zkey cannot be derived from a powers-of-tau file.
+#[derive(Debug, Error)]
+pub enum ZkeySetupError {
+    /// The powers of tau do not contain the Lagrange sections
+    #[error("powers of tau is not prepared for phase 2")]
+    PtauNotPrepared,
+    /// The circuit is too large for the powers of tau
+    #[error("circuit needs a domain of size 2^{0}, but powers of tau only supports 2^{1}")]
+    PtauTooSmall(usize, usize),
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
+    /// Derives the [`Zkey`] of the `r1cs` from the powers of tau `ptau`, the same way as
+    /// `snarkjs groth16 setup` does.
+    ///
+    /// The queries are computed from the Lagrange sections of `ptau` with delta set to the
+    /// generators, i.e., the zkey still needs contributions (see [`Zkey::contribute`]) before it
+    /// can be used securely. The hash of the circuit is computed like snarkjs does and stored
+    /// in [`Zkey::contributions`] together with an empty list of contributions.
+    ///
+    /// Returns an error if `ptau` is not prepared for phase 2 or too small for the circuit. As
+    /// the hash of the circuit covers powers of tau beyond the domain, a circuit with a domain
+    /// larger than `2^14` needs a `ptau` of a larger power than its domain.
+    pub fn setup(r1cs: &R1CS<P>, ptau: &PowersOfTau<P>) -> Result<Self, ZkeySetupError> {
+        let n_public =
+            usize::try_from(r1cs.n_pub_out + r1cs.n_pub_in).expect("u32 fits into usize");
+        let n_vars = r1cs.num_variables;
+        let power = usize::try_from(
+            (r1cs.n_constraints + n_public + 1)
+                .next_power_of_two()
+                .ilog2(),
+        )
+        .expect("u32 fits into usize");
+        if !ptau.is_prepared() {
+            return Err(ZkeySetupError::PtauNotPrepared);
+        }
+        if power > ptau.power {
+            return Err(ZkeySetupError::PtauTooSmall(power, ptau.power));
+        }
+        tracing::debug!(
+            "deriving zkey with power {power} from ptau with power {}",
+            ptau.power
+        );
+        let lagrange = PowersOfTau::<P>::lagrange_range(power);
+        let tau_g1 = &ptau.lagrange_tau_g1[lagrange.clone()];
+        let tau_g2 = &ptau.lagrange_tau_g2[lagrange.clone()];
+        let alpha_tau_g1 = &ptau.lagrange_alpha_tau_g1[lagrange.clone()];
+        let beta_tau_g1 = &ptau.lagrange_beta_tau_g1[lagrange];
+
+        let mut a_query = vec![P::G1::zero(); n_vars];
+        let mut b_g1_query = vec![P::G1::zero(); n_vars];
+        let mut b_g2_query = vec![P::G2::zero(); n_vars];
+        // beta * A + alpha * B + C, split into the IC and the l_query afterwards
+        let mut c_query = vec![P::G1::zero(); n_vars];
+        let mut a_matrix = Vec::with_capacity(r1cs.n_constraints);
+        let mut b_matrix = Vec::with_capacity(r1cs.n_constraints);
+        for (k, (a, b, c)) in r1cs.constraints.iter().enumerate() {
+            for (s, coeff) in a {
+                a_query[*s] += tau_g1[k] * coeff;
+                c_query[*s] += beta_tau_g1[k] * coeff;
+            }
+            for (s, coeff) in b {
+                b_g1_query[*s] += tau_g1[k] * coeff;
+                b_g2_query[*s] += tau_g2[k] * coeff;
+                c_query[*s] += alpha_tau_g1[k] * coeff;
+            }
+            for (s, coeff) in c {
+                c_query[*s] += tau_g1[k] * coeff;
+            }
+            a_matrix.push(a.iter().map(|(s, coeff)| (*coeff, *s)).collect());
+            b_matrix.push(b.iter().map(|(s, coeff)| (*coeff, *s)).collect());
+        }
+        // snarkjs adds the constraints 0 * 0 = x_i for all public inputs
+        for s in 0..=n_public {
+            a_query[s] += tau_g1[r1cs.n_constraints + s];
+            c_query[s] += beta_tau_g1[r1cs.n_constraints + s];
+        }
+        // the h_query consists of the odd Lagrange basis elements of the domain of twice the size
+        let h_query = ptau.lagrange_tau_g1[PowersOfTau::<P>::lagrange_range(power + 1)]
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let c_query = P::G1::normalize_batch(&c_query);
+        let mut zkey = Zkey {
+            n_public,
+            pow: power,
+            num_constraints: r1cs.n_constraints,
+            beta_g1: ptau.beta_tau_g1[0],
+            delta_g1: P::G1Affine::generator(),
+            a_query: P::G1::normalize_batch(&a_query),
+            b_g1_query: P::G1::normalize_batch(&b_g1_query),
+            b_g2_query: P::G2::normalize_batch(&b_g2_query),
+            h_query,
+            l_query: c_query[n_public + 1..].to_vec(),
+            alpha_g1: ptau.alpha_tau_g1[0],
+            beta_g2: ptau.beta_g2,
+            delta_g2: P::G2Affine::generator(),
+            gamma_g2: P::G2Affine::generator(),
+            ic: c_query[..=n_public].to_vec(),
+            a_matrix,
+            b_matrix,
+            contributions: None,
+        };
+        zkey.contributions = Some(ZkeyContributions {
+            cs_hash: zkey.circuit_hash(ptau)?,
+            contributions: vec![],
+        });
+        Ok(zkey)
+    }
+
+    /// Computes the hash of the circuit (`csHash`) of a zkey without contributions.
+    ///
+    /// snarkjs hashes the points in their uncompressed big-endian encoding, every query
+    /// prefixed with its length. Instead of the h_query, it hashes `(tau^n - 1) * tau^i`.
+    fn circuit_hash(&self, ptau: &PowersOfTau<P>) -> Result<[u8; 64], ZkeySetupError> {
+        let mut hasher = Blake2b512::new();
+        let hash_len = |hasher: &mut Blake2b512, len: usize| {
+            hasher.update(
+                u32::try_from(len)
+                    .expect("length fits into u32")
+                    .to_be_bytes(),
+            );
+        };
+        hash_point(&mut hasher, &self.alpha_g1);
+        hash_point(&mut hasher, &self.beta_g1);
+        hash_point(&mut hasher, &self.beta_g2);
+        hash_point(&mut hasher, &self.gamma_g2);
+        hash_point(&mut hasher, &self.delta_g1);
+        hash_point(&mut hasher, &self.delta_g2);
+
+        hash_len(&mut hasher, self.ic.len());
+        self.ic.iter().for_each(|p| hash_point(&mut hasher, p));
+
+        // snarkjs announces domain_size - 1 points, but hashes full chunks of the h_query. For
+        // the largest domain of the ptau, the last chunk exceeds the powers of tau and snarkjs
+        // reads whatever follows them in the file, which cannot be reproduced.
+        let domain_size: usize = 1 << self.pow;
+        let num_chunks = (domain_size - 1).div_ceil(H_HASH_CHUNK_SIZE);
+        let num_hashed = num_chunks * (domain_size - 1).min(H_HASH_CHUNK_SIZE);
+        let high = ptau
+            .tau_g1
+            .get(domain_size..domain_size + num_hashed)
+            .ok_or(ZkeySetupError::PtauTooSmall(self.pow + 1, ptau.power))?;
+        hash_len(&mut hasher, domain_size - 1);
+        for (high, low) in high.iter().zip(&ptau.tau_g1) {
+            let point = (*high - low).into_affine();
+            hash_point(&mut hasher, &point);
+        }
+
+        for query in [&self.l_query, &self.a_query, &self.b_g1_query] {
+            hash_len(&mut hasher, query.len());
+            query.iter().for_each(|p| hash_point(&mut hasher, p));
+        }
+        hash_len(&mut hasher, self.b_g2_query.len());
+        self.b_g2_query
+            .iter()
+            .for_each(|p| hash_point(&mut hasher, p));
+        Ok(hasher.finalize().into())
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use std::fs::File;
+
+    use ark_bn254::{Bn254, Fr};
+    use ark_serde_compat::CheckElement;
+
+    use crate::tests::groth16_bn254_kats;
+
+    use super::*;
+
+    #[test]
+    fn can_setup_and_contribute() {
+        let r1cs = R1CS::<Bn254>::from_reader(
+            File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap(),
+        )
+        .unwrap();
+        let kat = Zkey::<Bn254>::from_reader(
+            File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap(),
+            CheckElement::Yes,
+        )
+        .unwrap();
+        let ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            3,
+            Fr::from(1337u64),
+            Fr::from(42u64),
+            Fr::from(4711u64),
+        );
+        let mut rng = ark_std::test_rng();
+
+        let mut zkey = Zkey::setup(&r1cs, &ptau).unwrap();
+        assert_eq!(zkey.n_public, kat.n_public);
+        assert_eq!(zkey.pow, kat.pow);
+        assert_eq!(zkey.num_constraints, kat.num_constraints);
+        assert_eq!(zkey.a_matrix, kat.a_matrix);
+        assert_eq!(zkey.b_matrix, kat.b_matrix);
+        assert!(zkey.verify(&r1cs, &ptau, &mut rng).unwrap().is_valid());
+        let cs_hash = zkey.contributions.as_ref().unwrap().cs_hash;
+        assert_eq!(
+            Zkey::setup(&r1cs, &ptau)
+                .unwrap()
+                .contributions
+                .unwrap()
+                .cs_hash,
+            cs_hash
+        );
+
+        let first = zkey.contribute(b"first");
+        let second = zkey.contribute(b"second");
+        assert_ne!(first, second);
+        let contributions = zkey.contributions.as_ref().unwrap();
+        assert_eq!(contributions.cs_hash, cs_hash);
+        assert_eq!(contributions.contributions.len(), 2);
+        assert_eq!(contributions.contributions[1].contribution_hash(), second);
+        zkey.verify_contribution_chain().unwrap();
+        assert!(zkey.verify(&r1cs, &ptau, &mut rng).unwrap().is_valid());
+
+        let mut bytes = Vec::new();
+        zkey.write_to(&mut bytes).unwrap();
+        let read = Zkey::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes).unwrap();
+        assert_eq!(read.contributions, zkey.contributions);
+        assert!(read.verify(&r1cs, &ptau, &mut rng).unwrap().is_valid());
+
+        let small = PowersOfTau::<Bn254>::from_toxic_waste(
+            1,
+            Fr::from(1337u64),
+            Fr::from(42u64),
+            Fr::from(4711u64),
+        );
+        assert!(matches!(
+            Zkey::setup(&r1cs, &small),
+            Err(ZkeySetupError::PtauTooSmall(2, 1))
+        ));
+
+        // the hash of the circuit needs the powers of tau beyond the domain
+        let mut truncated = ptau.clone();
+        truncated.tau_g1.truncate((1 << kat.pow) + 2);
+        assert!(matches!(
+            Zkey::setup(&r1cs, &truncated),
+            Err(ZkeySetupError::PtauTooSmall(3, 3))
+        ));
+    }
+}
@@ -1,9 +1,14 @@
 use ark_ec::pairing::Pairing;
 use ark_ff::PrimeField;
 use ark_groth16::{ProvingKey, VerifyingKey};
-use ark_relations::r1cs::ConstraintMatrices;
+use ark_relations::r1cs::{ConstraintMatrices, Matrix};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Valid};
 
+#[cfg(feature = "r1cs")]
+use thiserror::Error;
+
+#[cfg(feature = "r1cs")]
+use crate::R1CS;
 use crate::groth16::Zkey;
 
 /// Wrapper type to serialize [`ConstraintMatrices`] and [`ProvingKey`]s as a combined type.
@@ -153,14 +158,16 @@ impl<P: Pairing> From<ArkZkey<P>> for (ConstraintMatrices<P::ScalarField>, Provi
 }
 
 impl<P: Pairing> From<Zkey<P>> for (ConstraintMatrices<P::ScalarField>, ProvingKey<P>) {
+    /// Splits the [`Zkey`] into arkworks types. As the zkey does not contain the C matrix, it is
+    /// left empty, see [`ArkZkey::from_zkey_with_r1cs`] to reconstruct it.
     fn from(zkey: Zkey<P>) -> Self {
         (
             ConstraintMatrices {
                 num_instance_variables: zkey.n_public + 1,
                 num_witness_variables: zkey.a_query.len() - zkey.n_public - 1,
                 num_constraints: zkey.num_constraints,
-                a_num_non_zero: zkey.a_matrix.len(),
-                b_num_non_zero: zkey.b_matrix.len(),
+                a_num_non_zero: num_non_zero(&zkey.a_matrix),
+                b_num_non_zero: num_non_zero(&zkey.b_matrix),
                 c_num_non_zero: 0,
                 a: zkey.a_matrix,
                 b: zkey.b_matrix,
@@ -187,39 +194,205 @@ impl<P: Pairing> From<Zkey<P>> for (ConstraintMatrices<P::ScalarField>, ProvingK
 }
 
 impl<P: Pairing> From<Zkey<P>> for ArkZkey<P> {
+    /// Converts the [`Zkey`] with an empty C matrix, see [`ArkZkey::from_zkey_with_r1cs`] to
+    /// reconstruct it.
     fn from(zkey: Zkey<P>) -> Self {
-        let (matrices, pk) = (
-            ConstraintMatrices {
-                num_instance_variables: zkey.n_public + 1,
-                num_witness_variables: zkey.a_query.len() - zkey.n_public - 1,
-                num_constraints: zkey.num_constraints,
-                a_num_non_zero: zkey.a_matrix.len(),
-                b_num_non_zero: zkey.b_matrix.len(),
-                c_num_non_zero: 0,
-                a: zkey.a_matrix,
-                b: zkey.b_matrix,
-                c: vec![],
-            },
-            ProvingKey {
-                vk: VerifyingKey {
-                    alpha_g1: zkey.alpha_g1,
-                    beta_g2: zkey.beta_g2,
-                    gamma_g2: zkey.gamma_g2,
-                    delta_g2: zkey.delta_g2,
-                    gamma_abc_g1: zkey.ic,
-                },
-                beta_g1: zkey.beta_g1,
-                delta_g1: zkey.delta_g1,
-                a_query: zkey.a_query,
-                b_g1_query: zkey.b_g1_query,
-                b_g2_query: zkey.b_g2_query,
-                h_query: zkey.h_query,
-                l_query: zkey.l_query,
-            },
-        );
+        let (matrices, pk) = zkey.into();
         Self {
-            matrices: matrices.into(),
+            matrices: ConstraintMatricesWrapper(matrices),
             pk,
         }
     }
 }
+
+fn num_non_zero<F>(matrix: &Matrix<F>) -> usize {
+    matrix.iter().map(Vec::len).sum()
+}
+
+/// Error type describing why a [`Zkey`] does not match an [`R1CS`].
+#[cfg(feature = "r1cs")]
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum R1CSMismatchError {
+    /// The number of constraints differs
+    #[error("zkey has {zkey} constraints, but the R1CS has {r1cs}")]
+    ConstraintCount {
+        /// Number of constraints of the zkey
+        zkey: usize,
+        /// Number of constraints of the R1CS
+        r1cs: usize,
+    },
+    /// The number of public inputs differs
+    #[error("zkey has {zkey} public inputs, but the R1CS has {r1cs}")]
+    PublicInputCount {
+        /// Number of public inputs of the zkey
+        zkey: usize,
+        /// Number of public inputs of the R1CS
+        r1cs: usize,
+    },
+    /// The number of variables differs
+    #[error("zkey has {zkey} variables, but the R1CS has {r1cs}")]
+    VariableCount {
+        /// Number of variables of the zkey
+        zkey: usize,
+        /// Number of variables of the R1CS
+        r1cs: usize,
+    },
+    /// A row of the A matrix differs
+    #[error("A matrix of the zkey does not match the R1CS in constraint {0}")]
+    MatrixA(usize),
+    /// A row of the B matrix differs
+    #[error("B matrix of the zkey does not match the R1CS in constraint {0}")]
+    MatrixB(usize),
+}
+
+#[cfg(feature = "r1cs")]
+impl<P: Pairing> ArkZkey<P> {
+    /// Converts the [`Zkey`] like [`ArkZkey::from`], but additionally reconstructs the C matrix,
+    /// which the zkey does not contain, from the matching `r1cs`.
+    ///
+    /// Returns an [`R1CSMismatchError`] if the sizes or the A and B matrices of the zkey do not
+    /// match the `r1cs`.
+    pub fn from_zkey_with_r1cs(zkey: Zkey<P>, r1cs: &R1CS<P>) -> Result<Self, R1CSMismatchError> {
+        let expected = ConstraintMatrices::from(r1cs);
+        if zkey.num_constraints != expected.num_constraints {
+            return Err(R1CSMismatchError::ConstraintCount {
+                zkey: zkey.num_constraints,
+                r1cs: expected.num_constraints,
+            });
+        }
+        if zkey.n_public + 1 != expected.num_instance_variables {
+            return Err(R1CSMismatchError::PublicInputCount {
+                zkey: zkey.n_public,
+                r1cs: expected.num_instance_variables - 1,
+            });
+        }
+        if zkey.a_query.len() != r1cs.num_variables {
+            return Err(R1CSMismatchError::VariableCount {
+                zkey: zkey.a_query.len(),
+                r1cs: r1cs.num_variables,
+            });
+        }
+        let mismatch = |zkey: &Matrix<P::ScalarField>, r1cs: &Matrix<P::ScalarField>| {
+            (0..r1cs.len()).find(|i| zkey.get(*i) != r1cs.get(*i))
+        };
+        if let Some(constraint) = mismatch(&zkey.a_matrix, &expected.a) {
+            return Err(R1CSMismatchError::MatrixA(constraint));
+        }
+        if let Some(constraint) = mismatch(&zkey.b_matrix, &expected.b) {
+            return Err(R1CSMismatchError::MatrixB(constraint));
+        }
+        let (mut matrices, pk) = <(ConstraintMatrices<P::ScalarField>, ProvingKey<P>)>::from(zkey);
+        matrices.c = expected.c;
+        matrices.c_num_non_zero = expected.c_num_non_zero;
+        Ok(Self {
+            matrices: ConstraintMatricesWrapper(matrices),
+            pk,
+        })
+    }
+}
+
+impl<P: Pairing> From<ArkZkey<P>> for Zkey<P> {
+    /// Converts back into a [`Zkey`]. As arkworks does not keep the MPC contributions,
+    /// the resulting [`Zkey`] has none.
+    ///
+    /// The domain size is derived from the length of the `h_query`, so this expects the
+    /// [`ArkZkey`] to originate from a Circom zkey.
+    fn from(value: ArkZkey<P>) -> Self {
+        let ArkZkey { matrices, pk } = value;
+        let matrices = matrices.into_inner();
+        Zkey {
+            n_public: matrices.num_instance_variables - 1,
+            pow: pk.h_query.len().next_power_of_two().ilog2() as usize,
+            num_constraints: matrices.num_constraints,
+            beta_g1: pk.beta_g1,
+            delta_g1: pk.delta_g1,
+            a_query: pk.a_query,
+            b_g1_query: pk.b_g1_query,
+            b_g2_query: pk.b_g2_query,
+            h_query: pk.h_query,
+            l_query: pk.l_query,
+            alpha_g1: pk.vk.alpha_g1,
+            beta_g2: pk.vk.beta_g2,
+            delta_g2: pk.vk.delta_g2,
+            gamma_g2: pk.vk.gamma_g2,
+            ic: pk.vk.gamma_abc_g1,
+            a_matrix: matrices.a,
+            b_matrix: matrices.b,
+            contributions: None,
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use std::fs::File;
+
+    use ark_bn254::Bn254;
+    use ark_serde_compat::CheckElement;
+
+    use super::*;
+    use crate::tests::groth16_bn254_kats;
+
+    #[test]
+    fn can_convert_ark_zkey_back() {
+        let zkey = File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bn254>::from_reader(zkey, CheckElement::Yes).unwrap();
+        let ark_zkey = ArkZkey::from(zkey.clone());
+        let back = Zkey::from(ark_zkey.clone());
+        assert!(back.contributions.is_none());
+
+        let mut buf = Vec::new();
+        back.write_to(&mut buf).unwrap();
+        let read = Zkey::<Bn254>::from_reader(buf.as_slice(), CheckElement::Yes).unwrap();
+        assert_eq!(read.pow, zkey.pow);
+        assert_eq!(read.num_constraints, zkey.num_constraints);
+        let (is_matrices, is_pk) = ArkZkey::from(read).into_inner();
+        let (should_matrices, should_pk) = ark_zkey.into_inner();
+        assert_eq!(is_pk, should_pk);
+        assert_eq!(is_matrices.a, should_matrices.a);
+        assert_eq!(is_matrices.b, should_matrices.b);
+    }
+
+    #[test]
+    fn counts_non_zero_entries() {
+        let zkey = File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bn254>::from_reader(zkey, CheckElement::Yes).unwrap();
+        let (matrices, _) = ArkZkey::from(zkey).into_inner();
+        assert_eq!(matrices.a_num_non_zero, 1);
+        assert_eq!(matrices.b_num_non_zero, 1);
+        assert!(matrices.c.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "r1cs")]
+    fn can_convert_with_r1cs() {
+        let zkey = File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap();
+        let zkey = Zkey::<Bn254>::from_reader(zkey, CheckElement::Yes).unwrap();
+        let r1cs = File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<Bn254>::from_reader(r1cs).unwrap();
+
+        let (matrices, pk) = ArkZkey::from_zkey_with_r1cs(zkey.clone(), &r1cs)
+            .unwrap()
+            .into_inner();
+        let expected = ConstraintMatrices::from(&r1cs);
+        assert_eq!(matrices.a, expected.a);
+        assert_eq!(matrices.b, expected.b);
+        assert_eq!(matrices.c, expected.c);
+        assert_eq!(matrices.c_num_non_zero, 1);
+        assert_eq!(pk, ArkZkey::from(zkey.clone()).pk);
+
+        let mut tampered = zkey.clone();
+        tampered.b_matrix[0][0].0 += ark_bn254::Fr::from(1u64);
+        assert_eq!(
+            ArkZkey::from_zkey_with_r1cs(tampered, &r1cs).err(),
+            Some(R1CSMismatchError::MatrixB(0))
+        );
+        let mut tampered = zkey;
+        tampered.n_public = 2;
+        assert_eq!(
+            ArkZkey::from_zkey_with_r1cs(tampered, &r1cs).err(),
+            Some(R1CSMismatchError::PublicInputCount { zkey: 2, r1cs: 1 })
+        );
+    }
+}
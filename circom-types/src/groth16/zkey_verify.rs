@@ -0,0 +1,446 @@
+//! This module implements [`Zkey::verify`], the equivalent of `snarkjs zkey verify`, which checks
+//! that a zkey was derived from an [`R1CS`] and a [`PowersOfTau`].
+use std::fmt;
+
+use ark_ec::{AffineRepr, VariableBaseMSM, pairing::Pairing};
+use ark_ff::{UniformRand, Zero};
+use ark_std::rand::Rng;
+use thiserror::Error;
+
+use crate::{PowersOfTau, R1CS, groth16::Zkey, traits::CircomArkworksPairingBridge};
+
+/// Error type describing why a zkey cannot be verified against a powers-of-tau file.
+#[derive(Debug, Error)]
+pub enum ZkeyVerificationError {
+    /// The powers of tau do not contain the Lagrange sections
+    #[error("powers of tau is not prepared for phase 2")]
+    PtauNotPrepared,
+    /// The circuit is too large for the powers of tau
+    #[error("circuit needs a domain of size 2^{0}, but powers of tau only supports 2^{1}")]
+    PtauTooSmall(usize, usize),
+}
+
+/// The parts of a zkey checked by [`Zkey::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZkeySection {
+    /// The sizes of the circuit, alpha, beta and gamma
+    Header,
+    /// The phase-2 contributions, i.e., delta
+    Contributions,
+    /// The points binding the public inputs
+    Ic,
+    /// The coefficients of the constraint matrices A and B
+    Coefficients,
+    /// The a_query
+    AQuery,
+    /// The b_query in G1
+    BG1Query,
+    /// The b_query in G2
+    BG2Query,
+    /// The l_query
+    LQuery,
+    /// The h_query
+    HQuery,
+}
+
+impl fmt::Display for ZkeySection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Header => write!(f, "header"),
+            Self::Contributions => write!(f, "contributions"),
+            Self::Ic => write!(f, "IC"),
+            Self::Coefficients => write!(f, "coefficients"),
+            Self::AQuery => write!(f, "a_query"),
+            Self::BG1Query => write!(f, "b_g1_query"),
+            Self::BG2Query => write!(f, "b_g2_query"),
+            Self::LQuery => write!(f, "l_query"),
+            Self::HQuery => write!(f, "h_query"),
+        }
+    }
+}
+
+/// The result of checking a single [`ZkeySection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionReport {
+    /// The checked section
+    pub section: ZkeySection,
+    /// Describes the mismatch, `None` if the section is valid
+    pub mismatch: Option<String>,
+}
+
+/// The result of [`Zkey::verify`], containing one [`SectionReport`] per [`ZkeySection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZkeyVerificationReport {
+    /// The reports of all checked sections
+    pub sections: Vec<SectionReport>,
+}
+
+impl ZkeyVerificationReport {
+    /// Returns `true` if all sections are valid.
+    pub fn is_valid(&self) -> bool {
+        self.sections.iter().all(|report| report.mismatch.is_none())
+    }
+
+    /// Returns the sections that do not match.
+    pub fn mismatches(&self) -> impl Iterator<Item = &SectionReport> {
+        self.sections
+            .iter()
+            .filter(|report| report.mismatch.is_some())
+    }
+
+    fn push(&mut self, section: ZkeySection, mismatch: Option<String>) {
+        self.sections.push(SectionReport { section, mismatch });
+    }
+}
+
+impl fmt::Display for ZkeyVerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for report in &self.sections {
+            match &report.mismatch {
+                Some(mismatch) => writeln!(f, "{}: INVALID ({mismatch})", report.section)?,
+                None => writeln!(f, "{}: OK", report.section)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The constraint matrices of the circuit, including the constraints snarkjs adds for the public inputs.
+struct Circuit<'a, P: Pairing> {
+    r1cs: &'a R1CS<P>,
+    n_public: usize,
+    power: usize,
+}
+
+impl<P: Pairing> Circuit<'_, P> {
+    fn domain_size(&self) -> usize {
+        1 << self.power
+    }
+
+    /// Folds the columns of A, B and C weighted by `r`, i.e., computes `sum_s r_s * M[k][s]` for every constraint `k`.
+    fn fold(&self, r: &[P::ScalarField]) -> [Vec<P::ScalarField>; 3] {
+        let weight = |s: usize| r.get(s).copied().unwrap_or_default();
+        let mut folded = [
+            vec![P::ScalarField::zero(); self.domain_size()],
+            vec![P::ScalarField::zero(); self.domain_size()],
+            vec![P::ScalarField::zero(); self.domain_size()],
+        ];
+        for (k, (a, b, c)) in self.r1cs.constraints.iter().enumerate() {
+            for (matrix, row) in [a, b, c].into_iter().enumerate() {
+                folded[matrix][k] = row.iter().map(|(s, coeff)| weight(*s) * coeff).sum();
+            }
+        }
+        for s in 0..=self.n_public {
+            folded[0][self.r1cs.n_constraints + s] = weight(s);
+        }
+        folded
+    }
+}
+
+fn msm<G: VariableBaseMSM>(bases: &[G::MulBase], scalars: &[G::ScalarField]) -> G {
+    G::msm(bases, scalars).expect("bases and scalars have the same length")
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
+    /// Verifies that the [`Zkey`] was derived from the `r1cs` and the powers of tau `ptau`,
+    /// the same way as `snarkjs zkey verify` does.
+    ///
+    /// The queries of the zkey are recomputed from the Lagrange sections of `ptau` and compared
+    /// using random linear combinations drawn from `rng`. As the l_query and h_query are divided
+    /// by delta, they are compared with pairings. Additionally, the contribution chain is verified
+    /// (see [`Zkey::verify_contribution_chain`]). The hash of the circuit is not recomputed.
+    ///
+    /// Returns an error if `ptau` is not suitable for the circuit. Otherwise, the returned
+    /// [`ZkeyVerificationReport`] states which sections mismatch.
+    pub fn verify<R: Rng>(
+        &self,
+        r1cs: &R1CS<P>,
+        ptau: &PowersOfTau<P>,
+        rng: &mut R,
+    ) -> Result<ZkeyVerificationReport, ZkeyVerificationError> {
+        let n_public =
+            usize::try_from(r1cs.n_pub_out + r1cs.n_pub_in).expect("u32 fits into usize");
+        let n_vars = r1cs.num_variables;
+        let power = usize::try_from(
+            (r1cs.n_constraints + n_public + 1)
+                .next_power_of_two()
+                .ilog2(),
+        )
+        .expect("u32 fits into usize");
+        if !ptau.is_prepared() {
+            return Err(ZkeyVerificationError::PtauNotPrepared);
+        }
+        if power > ptau.power {
+            return Err(ZkeyVerificationError::PtauTooSmall(power, ptau.power));
+        }
+        let circuit = Circuit {
+            r1cs,
+            n_public,
+            power,
+        };
+        let lagrange = PowersOfTau::<P>::lagrange_range(power);
+        let tau_g1 = &ptau.lagrange_tau_g1[lagrange.clone()];
+        let tau_g2 = &ptau.lagrange_tau_g2[lagrange.clone()];
+        let alpha_tau_g1 = &ptau.lagrange_alpha_tau_g1[lagrange.clone()];
+        let beta_tau_g1 = &ptau.lagrange_beta_tau_g1[lagrange];
+        // the h_query consists of the odd Lagrange basis elements of the domain of twice the size
+        let tau_g1_h = ptau.lagrange_tau_g1[PowersOfTau::<P>::lagrange_range(power + 1)]
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .copied()
+            .collect::<Vec<_>>();
+
+        let mut report = ZkeyVerificationReport { sections: vec![] };
+
+        let mut header = vec![];
+        if self.n_public != n_public {
+            header.push(format!(
+                "expected {n_public} public inputs but got {}",
+                self.n_public
+            ));
+        }
+        if self.pow != power {
+            header.push(format!("expected power {power} but got {}", self.pow));
+        }
+        if self.num_constraints != r1cs.n_constraints {
+            header.push(format!(
+                "expected {} constraints but got {}",
+                r1cs.n_constraints, self.num_constraints
+            ));
+        }
+        if self.alpha_g1 != ptau.alpha_tau_g1[0] {
+            header.push("alpha_g1 does not match powers of tau".to_owned());
+        }
+        if self.beta_g1 != ptau.beta_tau_g1[0] {
+            header.push("beta_g1 does not match powers of tau".to_owned());
+        }
+        if self.beta_g2 != ptau.beta_g2 {
+            header.push("beta_g2 does not match powers of tau".to_owned());
+        }
+        if self.gamma_g2 != P::G2Affine::generator() {
+            header.push("gamma_g2 is not the generator".to_owned());
+        }
+        report.push(
+            ZkeySection::Header,
+            (!header.is_empty()).then(|| header.join("; ")),
+        );
+
+        report.push(
+            ZkeySection::Contributions,
+            self.verify_contribution_chain()
+                .err()
+                .map(|e| e.to_string()),
+        );
+
+        let r = (0..n_vars)
+            .map(|_| P::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+
+        let mut r_public = r.clone();
+        r_public.truncate(n_public + 1);
+        let [a, b, c] = circuit.fold(&r_public);
+        let expected = msm::<P::G1>(beta_tau_g1, &a)
+            + msm::<P::G1>(alpha_tau_g1, &b)
+            + msm::<P::G1>(tau_g1, &c);
+        report.push(
+            ZkeySection::Ic,
+            check_len(&self.ic, n_public + 1)
+                .or_else(|| (msm::<P::G1>(&self.ic, &r_public) != expected).then(rlc_mismatch)),
+        );
+
+        report.push(ZkeySection::Coefficients, self.check_coefficients(r1cs));
+
+        let [a, b, _] = circuit.fold(&r);
+        report.push(
+            ZkeySection::AQuery,
+            check_len(&self.a_query, n_vars).or_else(|| {
+                (msm::<P::G1>(&self.a_query, &r) != msm::<P::G1>(tau_g1, &a)).then(rlc_mismatch)
+            }),
+        );
+        report.push(
+            ZkeySection::BG1Query,
+            check_len(&self.b_g1_query, n_vars).or_else(|| {
+                (msm::<P::G1>(&self.b_g1_query, &r) != msm::<P::G1>(tau_g1, &b)).then(rlc_mismatch)
+            }),
+        );
+        report.push(
+            ZkeySection::BG2Query,
+            check_len(&self.b_g2_query, n_vars).or_else(|| {
+                (msm::<P::G2>(&self.b_g2_query, &r) != msm::<P::G2>(tau_g2, &b)).then(rlc_mismatch)
+            }),
+        );
+
+        let mut r_private = r;
+        for r in r_private.iter_mut().take(n_public + 1) {
+            *r = P::ScalarField::zero();
+        }
+        let [a, b, c] = circuit.fold(&r_private);
+        let expected = msm::<P::G1>(beta_tau_g1, &a)
+            + msm::<P::G1>(alpha_tau_g1, &b)
+            + msm::<P::G1>(tau_g1, &c);
+        report.push(
+            ZkeySection::LQuery,
+            check_len(&self.l_query, n_vars.saturating_sub(n_public + 1)).or_else(|| {
+                let actual = msm::<P::G1>(&self.l_query, &r_private[n_public + 1..]);
+                (!self.divided_by_delta(actual, expected)).then(rlc_mismatch)
+            }),
+        );
+
+        let r_h = (0..circuit.domain_size())
+            .map(|_| P::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        report.push(
+            ZkeySection::HQuery,
+            check_len(&self.h_query, circuit.domain_size()).or_else(|| {
+                let actual = msm::<P::G1>(&self.h_query, &r_h);
+                let expected = msm::<P::G1>(&tau_g1_h, &r_h);
+                (!self.divided_by_delta(actual, expected)).then(rlc_mismatch)
+            }),
+        );
+        Ok(report)
+    }
+
+    fn check_coefficients(&self, r1cs: &R1CS<P>) -> Option<String> {
+        if self.a_matrix.len() != r1cs.n_constraints || self.b_matrix.len() != r1cs.n_constraints {
+            return Some(format!(
+                "expected {} constraints but got {}",
+                r1cs.n_constraints,
+                self.a_matrix.len()
+            ));
+        }
+        let matches = |zkey_row: &[(P::ScalarField, usize)],
+                       r1cs_row: &[(usize, P::ScalarField)]| {
+            zkey_row.len() == r1cs_row.len()
+                && zkey_row.iter().zip(r1cs_row).all(
+                    |((zkey_coeff, zkey_signal), (r1cs_signal, r1cs_coeff))| {
+                        zkey_signal == r1cs_signal && zkey_coeff == r1cs_coeff
+                    },
+                )
+        };
+        r1cs.constraints
+            .iter()
+            .zip(self.a_matrix.iter().zip(self.b_matrix.iter()))
+            .position(|((a, b, _), (zkey_a, zkey_b))| !matches(zkey_a, a) || !matches(zkey_b, b))
+            .map(|constraint| format!("constraint {constraint} does not match the R1CS"))
+    }
+
+    /// Checks that `actual` is `expected` divided by delta.
+    fn divided_by_delta(&self, actual: P::G1, expected: P::G1) -> bool {
+        P::multi_pairing(
+            [actual, -expected],
+            [self.delta_g2, P::G2Affine::generator()],
+        )
+        .is_zero()
+    }
+}
+
+fn check_len<T>(points: &[T], expected: usize) -> Option<String> {
+    (points.len() != expected)
+        .then(|| format!("expected {expected} elements but got {}", points.len()))
+}
+
+fn rlc_mismatch() -> String {
+    "random linear combination does not match the R1CS and the powers of tau".to_owned()
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use std::fs::File;
+
+    use ark_bn254::{Bn254, Fr};
+    use ark_ec::CurveGroup;
+    use ark_ff::{Field, One};
+
+    use crate::{CheckElement, tests::groth16_bn254_kats};
+
+    use super::*;
+
+    fn mismatches(report: &ZkeyVerificationReport) -> Vec<ZkeySection> {
+        report.mismatches().map(|report| report.section).collect()
+    }
+
+    #[test]
+    fn can_verify_zkey() {
+        let r1cs = R1CS::<Bn254>::from_reader(
+            File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap(),
+        )
+        .unwrap();
+        let ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            3,
+            Fr::from(1337u64),
+            Fr::from(42u64),
+            Fr::from(4711u64),
+        );
+        let mut rng = ark_std::test_rng();
+
+        let zkey = Zkey::setup(&r1cs, &ptau).unwrap();
+        let report = zkey.verify(&r1cs, &ptau, &mut rng).unwrap();
+        assert!(report.is_valid(), "{report}");
+
+        // delta is checked with pairings, only the missing contribution is reported
+        let mut tampered = zkey.clone();
+        let delta = Fr::from(5u64);
+        let delta_inv = delta.inverse().unwrap();
+        tampered.delta_g1 = (tampered.delta_g1 * delta).into_affine();
+        tampered.delta_g2 = (tampered.delta_g2 * delta).into_affine();
+        for p in tampered
+            .l_query
+            .iter_mut()
+            .chain(tampered.h_query.iter_mut())
+        {
+            *p = (*p * delta_inv).into_affine();
+        }
+        let report = tampered.verify(&r1cs, &ptau, &mut rng).unwrap();
+        assert_eq!(mismatches(&report), vec![ZkeySection::Contributions]);
+
+        let mut tampered = zkey.clone();
+        tampered.l_query[0] = tampered.alpha_g1;
+        tampered.h_query[1] = tampered.alpha_g1;
+        tampered.b_g2_query.swap(2, 3);
+        let report = tampered.verify(&r1cs, &ptau, &mut rng).unwrap();
+        assert_eq!(
+            mismatches(&report),
+            vec![
+                ZkeySection::BG2Query,
+                ZkeySection::LQuery,
+                ZkeySection::HQuery
+            ]
+        );
+
+        let mut tampered = zkey.clone();
+        tampered.a_matrix[0][0].0 = Fr::one();
+        tampered.ic.pop();
+        tampered.beta_g1 = tampered.alpha_g1;
+        let report = tampered.verify(&r1cs, &ptau, &mut rng).unwrap();
+        assert_eq!(
+            mismatches(&report),
+            vec![
+                ZkeySection::Header,
+                ZkeySection::Ic,
+                ZkeySection::Coefficients
+            ]
+        );
+
+        // the kat was derived from a different powers of tau
+        let kat = Zkey::<Bn254>::from_reader(
+            File::open(groth16_bn254_kats().join("circuit.zkey")).unwrap(),
+            CheckElement::Yes,
+        )
+        .unwrap();
+        let report = kat.verify(&r1cs, &ptau, &mut rng).unwrap();
+        assert!(!report.is_valid());
+
+        let small = PowersOfTau::<Bn254>::from_toxic_waste(
+            1,
+            Fr::from(1337u64),
+            Fr::from(42u64),
+            Fr::from(4711u64),
+        );
+        assert!(matches!(
+            zkey.verify(&r1cs, &small, &mut rng),
+            Err(ZkeyVerificationError::PtauTooSmall(2, 1))
+        ));
+    }
+}
@@ -0,0 +1,469 @@
+//! This module defines the [`CircuitInputs`] struct that implements deserialization of the `input.json` files of Circom
+//! circuits via [`serde::Deserialize`] and [`CircuitInputs::from_reader`].
+//!
+//! Every input signal is either a scalar or an arbitrarily nested array of scalars. Scalars are JSON integers, decimal or
+//! `0x`-prefixed hex strings, or booleans. They are reduced modulo the field like snarkjs does, so negative values wrap
+//! around.
+//!
+//! [`CircuitInputs::validate`] and [`CircuitInputs::validate_with_symbols`] check the inputs against the interface of the
+//! circuit before the witness is generated.
+
+use std::{fmt, io::Read, marker::PhantomData};
+
+use ark_ff::PrimeField;
+use serde::{
+    Deserialize, Deserializer,
+    de::{self, MapAccess, SeqAccess, Visitor},
+};
+use thiserror::Error;
+
+#[cfg(feature = "r1cs")]
+use ark_ec::pairing::Pairing;
+
+#[cfg(feature = "r1cs")]
+use crate::R1CS;
+#[cfg(all(feature = "r1cs", feature = "symbols"))]
+use crate::Symbols;
+
+/// Error type describing errors during parsing and validating circuit inputs
+#[derive(Debug, Error)]
+pub enum CircuitInputsError {
+    /// The inputs are not valid JSON of the expected shape
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The total number of input elements does not match the circuit
+    #[error("inputs have {actual} elements, expected {expected}")]
+    InputCount {
+        /// Number of public and private inputs of the circuit
+        expected: usize,
+        /// Number of provided elements
+        actual: usize,
+    },
+    /// An input signal is not an input of the circuit
+    #[error("{0} is not an input of the circuit")]
+    UnknownSignal(String),
+    /// An input signal of the circuit was not provided
+    #[error("{0} is missing")]
+    MissingSignal(String),
+    /// An input signal has the wrong number of elements
+    #[error("{name} has {actual} elements, expected {expected}")]
+    SignalLength {
+        /// Fully qualified name of the signal
+        name: String,
+        /// Number of elements of the signal in the circuit
+        expected: usize,
+        /// Number of provided elements
+        actual: usize,
+    },
+}
+
+/// The value of an input signal, a scalar or an arbitrarily nested array of scalars.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputValue<F> {
+    /// A single field element
+    Scalar(F),
+    /// An array of values, e.g., `in[3][2]` is an array of three arrays of two scalars
+    Array(Vec<InputValue<F>>),
+}
+
+impl<F: Copy> InputValue<F> {
+    /// Returns the number of scalars of the value.
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Scalar(_) => 1,
+            Self::Array(values) => values.iter().map(Self::len).sum(),
+        }
+    }
+
+    /// Returns `true` if the value is an (possibly nested) array without scalars.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the scalars of the value in row-major order, the order in which Circom assigns the
+    /// signals of an array.
+    pub fn flatten(&self) -> Vec<F> {
+        let mut flat = Vec::with_capacity(self.len());
+        self.flatten_into(&mut flat);
+        flat
+    }
+
+    fn flatten_into(&self, flat: &mut Vec<F>) {
+        match self {
+            Self::Scalar(value) => flat.push(*value),
+            Self::Array(values) => values.iter().for_each(|v| v.flatten_into(flat)),
+        }
+    }
+}
+
+/// The inputs of a Circom circuit, as given in its `input.json`. The signals keep the order of
+/// the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitInputs<F> {
+    /// The names and values of the input signals
+    pub signals: Vec<(String, InputValue<F>)>,
+}
+
+impl<F> Default for CircuitInputs<F> {
+    fn default() -> Self {
+        Self { signals: vec![] }
+    }
+}
+
+impl<F: PrimeField> CircuitInputs<F> {
+    /// Deserializes [`CircuitInputs`] from a reader of an `input.json`.
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, CircuitInputsError> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Returns the value of the input signal with the given name.
+    pub fn get(&self, name: &str) -> Option<&InputValue<F>> {
+        self.signals
+            .iter()
+            .find(|(signal, _)| signal == name)
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the names and flattened values of all input signals, see [`InputValue::flatten`].
+    pub fn flatten(&self) -> impl Iterator<Item = (&str, Vec<F>)> {
+        self.signals
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.flatten()))
+    }
+
+    /// Checks that the inputs provide as many elements as the public and private inputs of the `r1cs`.
+    ///
+    /// See [`CircuitInputs::validate_with_symbols`] to check every signal individually.
+    #[cfg(feature = "r1cs")]
+    pub fn validate<P: Pairing<ScalarField = F>>(
+        &self,
+        r1cs: &R1CS<P>,
+    ) -> Result<(), CircuitInputsError> {
+        let expected = r1cs.n_pub_in as usize + r1cs.n_prv_in as usize;
+        let actual = self.signals.iter().map(|(_, value)| value.len()).sum();
+        if actual != expected {
+            return Err(CircuitInputsError::InputCount { expected, actual });
+        }
+        Ok(())
+    }
+
+    /// Checks that the inputs provide exactly the input signals of the main component, with the number of
+    /// elements given by the `.sym` file of the circuit.
+    #[cfg(all(feature = "r1cs", feature = "symbols"))]
+    pub fn validate_with_symbols<P: Pairing<ScalarField = F>>(
+        &self,
+        r1cs: &R1CS<P>,
+        symbols: &Symbols,
+    ) -> Result<(), CircuitInputsError> {
+        let interface = input_signals(r1cs, symbols);
+        for (name, _) in &self.signals {
+            let name = format!("main.{name}");
+            if !interface.iter().any(|(signal, _)| *signal == name) {
+                return Err(CircuitInputsError::UnknownSignal(name));
+            }
+        }
+        for (name, expected) in interface {
+            let value = name
+                .strip_prefix("main.")
+                .and_then(|key| self.get(key))
+                .ok_or_else(|| CircuitInputsError::MissingSignal(name.clone()))?;
+            if value.len() != expected {
+                return Err(CircuitInputsError::SignalLength {
+                    actual: value.len(),
+                    name,
+                    expected,
+                });
+            }
+        }
+        self.validate(r1cs)
+    }
+
+    /// Validates the inputs with [`CircuitInputs::validate_with_symbols`] and returns their values in the
+    /// order of the input signals of the circuit, i.e., the public inputs followed by the private inputs.
+    #[cfg(all(feature = "r1cs", feature = "symbols"))]
+    pub fn flatten_in_signal_order<P: Pairing<ScalarField = F>>(
+        &self,
+        r1cs: &R1CS<P>,
+        symbols: &Symbols,
+    ) -> Result<Vec<F>, CircuitInputsError> {
+        self.validate_with_symbols(r1cs, symbols)?;
+        let mut values = Vec::new();
+        for (name, _) in input_signals(r1cs, symbols) {
+            let key = name
+                .strip_prefix("main.")
+                .expect("inputs are signals of main");
+            values.extend(self.get(key).expect("inputs are validated").flatten());
+        }
+        Ok(values)
+    }
+}
+
+/// Returns the input signals of the main component and their number of elements in signal order.
+///
+/// Circom assigns the labels after the public outputs to the public and then the private inputs of the
+/// main component, the elements of an array have consecutive labels.
+#[cfg(all(feature = "r1cs", feature = "symbols"))]
+fn input_signals<P: Pairing>(r1cs: &R1CS<P>, symbols: &Symbols) -> Vec<(String, usize)> {
+    let first = r1cs.n_pub_out as usize + 1;
+    let labels = first..first + r1cs.n_pub_in as usize + r1cs.n_prv_in as usize;
+    // the counts come from the r1cs file, so walk the symbols instead of the label range
+    let mut inputs = symbols
+        .symbols
+        .iter()
+        .filter(|symbol| labels.contains(&symbol.label))
+        .collect::<Vec<_>>();
+    inputs.sort_by_key(|symbol| symbol.label);
+    let mut signals: Vec<(String, usize)> = Vec::new();
+    for symbol in inputs {
+        let name = symbol.name.split('[').next().unwrap_or_default();
+        match signals.iter_mut().find(|(signal, _)| signal == name) {
+            Some((_, len)) => *len += 1,
+            None => signals.push((name.to_owned(), 1)),
+        }
+    }
+    signals
+}
+
+/// Parses a decimal or `0x`-prefixed hex integer with an optional minus sign and reduces it modulo the field.
+fn parse_integer<F: PrimeField>(s: &str) -> Option<F> {
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(s) => (true, s),
+        None => (false, s),
+    };
+    let (radix, digits) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(digits) => (16, digits),
+        None => (10, s),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let base = F::from(radix);
+    let value = digits.chars().try_fold(F::zero(), |acc, c| {
+        Some(acc * base + F::from(c.to_digit(radix)?))
+    })?;
+    Some(if negative { -value } else { value })
+}
+
+struct InputValueVisitor<F>(PhantomData<F>);
+
+impl<'de, F: PrimeField> Visitor<'de> for InputValueVisitor<F> {
+    type Value = InputValue<F>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer, a decimal or hex string, a boolean or an array of those")
+    }
+
+    fn visit_bool<E: de::Error>(self, v: bool) -> Result<Self::Value, E> {
+        Ok(InputValue::Scalar(F::from(v)))
+    }
+
+    fn visit_u64<E: de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        Ok(InputValue::Scalar(F::from(v)))
+    }
+
+    fn visit_i64<E: de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(InputValue::Scalar(F::from(v)))
+    }
+
+    fn visit_f64<E: de::Error>(self, v: f64) -> Result<Self::Value, E> {
+        Err(E::custom(format!(
+            "{v} is not an integer, large values must be given as strings"
+        )))
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        parse_integer(v)
+            .map(InputValue::Scalar)
+            .ok_or_else(|| E::custom(format!("{v:?} is not a decimal or hex integer")))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+        Ok(InputValue::Array(values))
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for InputValue<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(InputValueVisitor(PhantomData))
+    }
+}
+
+struct CircuitInputsVisitor<F>(PhantomData<F>);
+
+impl<'de, F: PrimeField> Visitor<'de> for CircuitInputsVisitor<F> {
+    type Value = CircuitInputs<F>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map from signal names to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut signals: Vec<(String, InputValue<F>)> = Vec::new();
+        while let Some((name, value)) = map.next_entry::<String, _>()? {
+            if signals.iter().any(|(signal, _)| *signal == name) {
+                return Err(de::Error::custom(format!("duplicate signal {name}")));
+            }
+            signals.push((name, value));
+        }
+        Ok(CircuitInputs { signals })
+    }
+}
+
+impl<'de, F: PrimeField> Deserialize<'de> for CircuitInputs<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(CircuitInputsVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use ark_bn254::Fr;
+
+    use super::*;
+
+    #[test]
+    fn can_deser_circuit_inputs() {
+        let json = r#"{
+            "b": 11,
+            "a": "3",
+            "path": [["1", 2], ["-1", "21888242871839275222246405745257275088548364400416034343698204186575808495618"]],
+            "empty": [],
+            "flags": [true, false],
+            "hex": ["0x1f", "-0X1"]
+        }"#;
+        let inputs = CircuitInputs::<Fr>::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(
+            inputs
+                .signals
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "a", "path", "empty", "flags", "hex"]
+        );
+        assert_eq!(inputs.get("a"), Some(&InputValue::Scalar(Fr::from(3u64))));
+        let path = inputs.get("path").unwrap();
+        assert_eq!(path.len(), 4);
+        // -1 wraps around and the modulus plus one is reduced to one
+        assert_eq!(
+            path.flatten(),
+            vec![
+                Fr::from(1u64),
+                Fr::from(2u64),
+                -Fr::from(1u64),
+                Fr::from(1u64)
+            ]
+        );
+        assert!(inputs.get("empty").unwrap().is_empty());
+        assert_eq!(
+            inputs.get("flags").unwrap().flatten(),
+            vec![Fr::from(1u64), Fr::from(0u64)]
+        );
+        assert_eq!(
+            inputs.get("hex").unwrap().flatten(),
+            vec![Fr::from(31u64), -Fr::from(1u64)]
+        );
+        assert_eq!(inputs.get("c"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_circuit_inputs() {
+        for json in [
+            r#"{"a": 1.5}"#,
+            r#"{"a": "0x"}"#,
+            r#"{"a": "12a"}"#,
+            r#"{"a": "0x1g"}"#,
+            r#"{"a": "-"}"#,
+            r#"{"a": {"b": 1}}"#,
+            r#"{"a": 1, "a": 2}"#,
+            r#"[1, 2]"#,
+        ] {
+            assert!(
+                CircuitInputs::<Fr>::from_reader(json.as_bytes()).is_err(),
+                "{json}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "r1cs", feature = "symbols"))]
+    fn can_validate_circuit_inputs() {
+        use std::fs::File;
+
+        use crate::{R1CS, Symbols, tests::groth16_bn254_kats};
+
+        let r1cs = File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<ark_bn254::Bn254>::from_reader(r1cs).unwrap();
+        let symbols =
+            Symbols::from_reader("1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b\n".as_bytes()).unwrap();
+        let validate = |json: &str| {
+            let inputs = CircuitInputs::<Fr>::from_reader(json.as_bytes()).unwrap();
+            inputs
+                .validate(&r1cs)
+                .and_then(|_| inputs.flatten_in_signal_order(&r1cs, &symbols))
+        };
+
+        assert_eq!(
+            validate(r#"{"b": 11, "a": 3}"#).unwrap(),
+            vec![Fr::from(3u64), Fr::from(11u64)]
+        );
+        assert_eq!(
+            validate(r#"{"a": 3}"#).unwrap_err().to_string(),
+            "inputs have 1 elements, expected 2"
+        );
+        assert_eq!(
+            validate(r#"{"a": 3, "c": 11}"#).unwrap_err().to_string(),
+            "main.c is not an input of the circuit"
+        );
+        assert_eq!(
+            validate(r#"{"a": [3, 4], "d": []}"#)
+                .unwrap_err()
+                .to_string(),
+            "main.d is not an input of the circuit"
+        );
+        assert_eq!(
+            validate(r#"{"a": [3, 4], "b": []}"#)
+                .unwrap_err()
+                .to_string(),
+            "main.a has 2 elements, expected 1"
+        );
+
+        let symbols = Symbols::from_reader(
+            "1,1,0,main.c\n2,2,0,main.path[0]\n3,3,0,main.path[1]\n".as_bytes(),
+        )
+        .unwrap();
+        let inputs = CircuitInputs::<Fr>::from_reader(r#"{"path": [3, 11]}"#.as_bytes()).unwrap();
+        assert_eq!(
+            inputs.flatten_in_signal_order(&r1cs, &symbols).unwrap(),
+            vec![Fr::from(3u64), Fr::from(11u64)]
+        );
+        let inputs = CircuitInputs::<Fr>::from_reader(r#"{"path": [3]}"#.as_bytes()).unwrap();
+        assert_eq!(
+            inputs
+                .validate_with_symbols(&r1cs, &symbols)
+                .unwrap_err()
+                .to_string(),
+            "main.path has 1 elements, expected 2"
+        );
+
+        // the counts of the r1cs header are not bounded by the file size
+        let mut r1cs = r1cs;
+        r1cs.n_pub_in = u32::MAX;
+        r1cs.n_prv_in = u32::MAX;
+        assert_eq!(
+            inputs.validate(&r1cs).unwrap_err().to_string(),
+            format!("inputs have 1 elements, expected {}", 2 * u32::MAX as usize)
+        );
+        assert_eq!(
+            inputs
+                .validate_with_symbols(&r1cs, &symbols)
+                .unwrap_err()
+                .to_string(),
+            "main.path has 1 elements, expected 2"
+        );
+    }
+}
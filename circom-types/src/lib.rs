@@ -4,33 +4,88 @@
 //! This crate provides deserialization support for Circom's binary and JSON formats,
 //! including proofs, verification keys, witnesses, R1CS constraint systems, and zkeys
 //! for both Groth16 and PLONK proof systems.
-#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau"
+))]
 mod binfile;
+#[cfg(any(feature = "bn254", feature = "bls12-381"))]
+mod chacha;
+#[cfg(all(feature = "r1cs", feature = "witness"))]
+mod circuit;
 #[cfg(feature = "groth16")]
 pub mod groth16;
 #[cfg(feature = "plonk")]
 pub mod plonk;
 
+#[cfg(feature = "inputs")]
+mod inputs;
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau",
+    feature = "r1cs",
+    feature = "witness"
+))]
+mod options;
+#[cfg(feature = "ptau")]
+mod ptau;
 #[cfg(feature = "r1cs")]
 mod r1cs;
 pub mod traits;
 
+#[cfg(feature = "symbols")]
+mod symbols;
 #[cfg(feature = "witness")]
 mod witness;
+#[cfg(feature = "witness-gen")]
+mod witness_gen;
 
 #[cfg(feature = "r1cs")]
 pub use r1cs::R1CS;
 #[cfg(feature = "r1cs")]
 pub use r1cs::R1CSParserError;
+#[cfg(all(feature = "r1cs", feature = "witness"))]
+pub use r1cs::{ConstraintSignal, Unsatisfied, UnsatisfiedConstraint};
+#[cfg(feature = "r1cs")]
+pub use r1cs::{CustomGate, CustomGateApplication};
+
+#[cfg(all(feature = "r1cs", feature = "witness"))]
+pub use circuit::CircomCircuit;
+
+#[cfg(feature = "symbols")]
+pub use symbols::{Symbol, Symbols, SymbolsParserError};
 
 #[cfg(feature = "witness")]
 pub use witness::Witness;
 #[cfg(feature = "witness")]
 pub use witness::WitnessParserError;
 
-#[cfg(all(feature = "zkey", any(feature = "groth16", feature = "plonk")))]
+#[cfg(feature = "inputs")]
+pub use inputs::{CircuitInputs, CircuitInputsError, InputValue};
+
+#[cfg(feature = "witness-gen")]
+pub use witness_gen::{WitnessGenError, WitnessGraph};
+
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau"
+))]
 pub use binfile::ZkeyParserError;
 
+#[cfg(any(all(feature = "zkey", feature = "groth16"), feature = "ptau"))]
+pub use binfile::{BinFileHeader, BinFileSection};
+
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau",
+    feature = "r1cs",
+    feature = "witness"
+))]
+pub use options::{LimitExceeded, ParseOptions};
+
+#[cfg(feature = "ptau")]
+pub use ptau::{PowersOfTau, PowersOfTauView, PtauContribution, PtauPublicKey};
+
 pub use ark_serde_compat::CheckElement;
 
 // re-export ark_bn254
@@ -124,6 +179,20 @@ pub(crate) mod tests {
         let cargo_manifest = PathBuf::from(std::env!("CARGO_MANIFEST_DIR"));
         cargo_manifest.join("kats/plonk/bls12_381")
     }
+
+    /// Returns all truncations and single byte corruptions of `bytes`, to check that the readers
+    /// reject malformed files with an error instead of panicking.
+    pub(crate) fn malformed(bytes: &[u8]) -> impl Iterator<Item = Vec<u8>> + '_ {
+        let truncated = (0..bytes.len()).map(|len| bytes[..len].to_vec());
+        let corrupted = (0..bytes.len()).flat_map(move |i| {
+            [0x00, 0x80, 0xff].into_iter().map(move |byte| {
+                let mut corrupted = bytes.to_vec();
+                corrupted[i] = byte;
+                corrupted
+            })
+        });
+        truncated.chain(corrupted)
+    }
 }
 
 #[cfg(test)]
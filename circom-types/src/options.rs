@@ -0,0 +1,251 @@
+//! This module defines [`ParseOptions`], which control how the readers of this crate validate the parsed elements,
+//! where they run in parallel and which sizes they accept. Use them to parse untrusted files without spending more
+//! CPU and memory than intended.
+
+#[cfg(feature = "parallel")]
+use std::sync::Arc;
+
+use ark_serde_compat::CheckElement;
+use thiserror::Error;
+
+/// Error describing that a file exceeds the limits configured in [`ParseOptions`]
+#[derive(Debug, Error)]
+#[error("{what} is {actual}, but the limit is {limit}")]
+pub struct LimitExceeded {
+    /// What exceeded the limit, e.g., `size of section 2`
+    pub what: String,
+    /// The size announced by the file
+    pub actual: u64,
+    /// The configured limit
+    pub limit: u64,
+}
+
+/// Options for the readers, accepted by the `from_reader_with_options` constructors of the parsed types.
+///
+/// The default checks all elements, runs on the global rayon thread pool and has no size limits. Independent of the
+/// limits, the readers never allocate more than the file actually contains, so the limits only need to be set to reject
+/// files that are valid, but too large to handle.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Whether to check that the points of zkeys and powers of tau are on their curve and in the correct subgroup, see
+    /// [`CheckElement`]
+    pub check: CheckElement,
+    /// The thread pool to decode zkeys and powers of tau on, or `None` for the global rayon thread pool
+    #[cfg(feature = "parallel")]
+    pub thread_pool: Option<Arc<rayon::ThreadPool>>,
+    /// Maximum size of a section in bytes
+    pub max_section_size: u64,
+    /// Maximum number of elements of a collection, e.g., the constraints, wires, points or witness values
+    pub max_elements: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            check: CheckElement::Yes,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            max_section_size: u64::MAX,
+            max_elements: usize::MAX,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// Sets whether to check the points of zkeys and powers of tau, see [`CheckElement`].
+    pub fn with_check(mut self, check: CheckElement) -> Self {
+        self.check = check;
+        self
+    }
+
+    /// Sets the thread pool to decode zkeys and powers of tau on, instead of the global rayon thread pool.
+    #[cfg(feature = "parallel")]
+    pub fn with_thread_pool(mut self, thread_pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(thread_pool);
+        self
+    }
+
+    /// Sets the maximum size of a section in bytes.
+    pub fn with_max_section_size(mut self, max_section_size: u64) -> Self {
+        self.max_section_size = max_section_size;
+        self
+    }
+
+    /// Sets the maximum number of elements of a collection.
+    pub fn with_max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+
+    /// Checks that section `id` with `size` bytes does not exceed [`ParseOptions::max_section_size`].
+    pub(crate) fn check_section_size(&self, id: u32, size: u64) -> Result<(), LimitExceeded> {
+        if size > self.max_section_size {
+            return Err(LimitExceeded {
+                what: format!("size of section {id}"),
+                actual: size,
+                limit: self.max_section_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `count` elements of `what` do not exceed [`ParseOptions::max_elements`].
+    pub(crate) fn check_element_count(
+        &self,
+        what: &str,
+        count: usize,
+    ) -> Result<(), LimitExceeded> {
+        if count > self.max_elements {
+            return Err(LimitExceeded {
+                what: format!("number of {what}"),
+                actual: count as u64,
+                limit: self.max_elements as u64,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs `op` on the configured thread pool, such that all parallel work spawned by `op` stays on it.
+    #[cfg(any(
+        all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+        feature = "ptau"
+    ))]
+    pub(crate) fn install<R: Send>(&self, op: impl FnOnce() -> R + Send) -> R {
+        #[cfg(feature = "parallel")]
+        if let Some(thread_pool) = &self.thread_pool {
+            return thread_pool.install(op);
+        }
+        op()
+    }
+}
+
+/// A scope to decode independent sections concurrently, see [`scope`].
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau"
+))]
+pub(crate) struct Scope<'a, 'scope> {
+    #[cfg(feature = "parallel")]
+    inner: &'a rayon::Scope<'scope>,
+    #[cfg(not(feature = "parallel"))]
+    _marker: std::marker::PhantomData<(&'a (), &'scope ())>,
+}
+
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau"
+))]
+impl<'scope> Scope<'_, 'scope> {
+    /// Spawns `task` on the current rayon thread pool, or runs it immediately without the `parallel` feature.
+    pub(crate) fn spawn(&self, task: impl FnOnce() + Send + 'scope) {
+        #[cfg(feature = "parallel")]
+        self.inner.spawn(|_| task());
+        #[cfg(not(feature = "parallel"))]
+        task();
+    }
+}
+
+/// Creates a [`Scope`] and waits for all tasks spawned on it. Use [`ParseOptions::install`] to select the thread pool.
+#[cfg(any(
+    all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+    feature = "ptau"
+))]
+pub(crate) fn scope<'scope>(op: impl for<'a> FnOnce(&Scope<'a, 'scope>) + Send) {
+    #[cfg(feature = "parallel")]
+    rayon::scope(|s| op(&Scope { inner: s }));
+    #[cfg(not(feature = "parallel"))]
+    op(&Scope {
+        _marker: std::marker::PhantomData,
+    });
+}
+
+#[cfg(test)]
+#[cfg(all(
+    feature = "parallel",
+    any(
+        all(feature = "zkey", any(feature = "groth16", feature = "plonk")),
+        feature = "ptau"
+    )
+))]
+pub(crate) mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use rayon::prelude::*;
+
+    use super::ParseOptions;
+
+    fn single_thread_pool(name: &'static str) -> Arc<rayon::ThreadPool> {
+        let thread_pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .thread_name(move |_| name.to_owned())
+            .build()
+            .unwrap();
+        Arc::new(thread_pool)
+    }
+
+    /// Runs `decode` with [`ParseOptions`] whose single-threaded thread pool is blocked, and checks that `decode`
+    /// only finishes after the thread pool is released, i.e., that it runs on the thread pool and not on the global
+    /// one.
+    #[cfg(all(
+        feature = "bn254",
+        any(all(feature = "zkey", feature = "groth16"), feature = "ptau")
+    ))]
+    pub(crate) fn decode_on_blocked_thread_pool<R: Send>(
+        decode: impl FnOnce(&ParseOptions) -> R + Send,
+    ) -> R {
+        use std::sync::mpsc;
+        use std::time::Duration;
+
+        let thread_pool = single_thread_pool("blocked");
+        let options = ParseOptions::default().with_thread_pool(Arc::clone(&thread_pool));
+        let (release, blocked) = mpsc::channel::<()>();
+        thread_pool.spawn(move || {
+            let _ = blocked.recv();
+        });
+        std::thread::scope(|s| {
+            let (done, finished) = mpsc::channel();
+            let decoder = s.spawn(move || {
+                let result = decode(&options);
+                let _ = done.send(());
+                result
+            });
+            assert!(
+                finished.recv_timeout(Duration::from_millis(200)).is_err(),
+                "finished while the thread pool was blocked"
+            );
+            release.send(()).unwrap();
+            decoder.join().unwrap()
+        })
+    }
+
+    #[test]
+    fn spawns_tasks_on_thread_pool() {
+        let options = ParseOptions::default().with_thread_pool(single_thread_pool("scope-test"));
+        let threads = Mutex::new(Vec::new());
+        let record = || {
+            let thread = std::thread::current();
+            threads.lock().unwrap().push((
+                thread.name().map(str::to_owned),
+                rayon::current_thread_index(),
+            ));
+        };
+        options.install(|| {
+            super::scope(|s| {
+                for _ in 0..4 {
+                    s.spawn(|| {
+                        record();
+                        // the parallel iterators of the decoders stay on the thread pool as well
+                        (0..16).into_par_iter().for_each(|_| record());
+                    });
+                }
+            })
+        });
+        let threads = threads.into_inner().unwrap();
+        assert_eq!(threads.len(), 4 * 17);
+        assert!(
+            threads
+                .iter()
+                .all(|thread| *thread == (Some("scope-test".to_owned()), Some(0)))
+        );
+    }
+}
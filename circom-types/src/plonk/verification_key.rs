@@ -1,9 +1,13 @@
 //! This module defines the [`VerificationKey`] struct that implements de/serialization using [`serde`].
 
 use ark_ec::pairing::Pairing;
+#[cfg(feature = "zkey")]
+use ark_ff::PrimeField;
 
 use serde::{Deserialize, Serialize};
 
+#[cfg(feature = "zkey")]
+use crate::plonk::Zkey;
 use crate::traits::CircomArkworksPairingBridge;
 
 /// Represents a verification key in JSON format that was created by Circom. Supports de/serialization using [`serde`].
@@ -78,6 +82,49 @@ pub struct VerificationKey<P: Pairing + CircomArkworksPairingBridge> {
     w: P::ScalarField,
 }
 
+#[cfg(feature = "zkey")]
+impl<P: Pairing + CircomArkworksPairingBridge> From<&Zkey<P>> for VerificationKey<P> {
+    /// Extracts the verification key from a zkey, the equivalent of `snarkjs zkey export verificationkey`.
+    fn from(zkey: &Zkey<P>) -> Self {
+        let vk = &zkey.verifying_key;
+        Self {
+            protocol: "plonk".to_owned(),
+            curve: P::get_circom_name(),
+            n_public: zkey.n_public,
+            power: zkey.pow,
+            k1: vk.k1,
+            k2: vk.k2,
+            qm: vk.qm,
+            ql: vk.ql,
+            qr: vk.qr,
+            qo: vk.qo,
+            qc: vk.qc,
+            s1: vk.s1,
+            s2: vk.s2,
+            s3: vk.s3,
+            x2: vk.x_2,
+            w: root_of_unity::<P::ScalarField>(zkey.pow),
+        }
+    }
+}
+
+/// Computes the root of unity of order `2^power` the same way ffjavascript does.
+///
+/// ffjavascript derives the roots of unity from the smallest quadratic non-residue, which is
+/// not necessarily the generator used by arkworks (e.g., for BLS12-381).
+#[cfg(feature = "zkey")]
+fn root_of_unity<F: PrimeField>(power: usize) -> F {
+    let mut nqr = F::from(2u64);
+    while !nqr.legendre().is_qnr() {
+        nqr += F::one();
+    }
+    let mut w = nqr.pow(F::TRACE);
+    for _ in power..usize::try_from(F::TWO_ADICITY).expect("u32 fits into usize") {
+        w.square_in_place();
+    }
+    w
+}
+
 #[cfg(test)]
 #[cfg(feature = "bls12-381")]
 mod bls12_381_tests {
@@ -151,6 +198,8 @@ mod bls12_381_tests {
             )
             .unwrap()
         );
+        #[cfg(feature = "zkey")]
+        assert_eq!(vk.w, super::root_of_unity::<ark_bls12_381::Fr>(vk.power));
 
         let ser_vk = serde_json::to_string(&vk).unwrap();
         let der_vk = serde_json::from_str::<VerificationKey<Bls12_381>>(&ser_vk).unwrap();
@@ -229,6 +278,8 @@ mod bn254_tests {
             )
             .unwrap()
         );
+        #[cfg(feature = "zkey")]
+        assert_eq!(vk.w, super::root_of_unity::<ark_bn254::Fr>(vk.power));
 
         let ser_vk = serde_json::to_string(&vk).unwrap();
         let der_vk = serde_json::from_str::<VerificationKey<Bn254>>(&ser_vk).unwrap();
@@ -33,7 +33,9 @@ use ark_serialize::CanonicalDeserialize;
 use std::io::{Cursor, Read};
 
 use crate::{
+    ParseOptions,
     binfile::{BinFile, ZkeyParserError, ZkeyParserResult},
+    options::scope,
     traits::CircomArkworksPairingBridge,
 };
 
@@ -159,31 +161,61 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
     /// only with care.
     ///
     /// See [`CheckElement`].
-    pub fn from_reader<R: Read>(mut reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
-        let mut binfile = BinFile::<P>::new(&mut reader)?;
+    pub fn from_reader<R: Read>(reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default().with_check(check))
+    }
+
+    /// Deserializes a [`Zkey`] from a reader with the given [`ParseOptions`].
+    ///
+    /// The sections are decoded in parallel on the configured thread pool.
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        let mut binfile = BinFile::<P>::new(&mut reader, options)?;
 
         tracing::debug!("start transforming bin file into zkey...");
-        let header = PlonkHeader::<P>::read(&mut binfile.take_section(2))?;
+        let header = PlonkHeader::<P>::read(&mut binfile.take_section(2)?)?;
         let n_vars = header.n_vars;
         let n_additions = header.n_additions;
         let n_constraints = header.n_constraints;
         let n_public = header.n_public;
         let domain_size = header.domain_size;
+        options.check_element_count("variables", n_vars)?;
+        options.check_element_count("public inputs", n_public)?;
+        options.check_element_count("additions", n_additions)?;
+        options.check_element_count("constraints", n_constraints)?;
+        options.check_element_count("domain elements", domain_size)?;
         //the sigmas are in the same section - so we split it here in separate chunks
         let sigma_section_size = domain_size * header.n8r + domain_size * 4 * header.n8r;
 
-        let add_section = binfile.take_section(3);
-        let a_section = binfile.take_section(4);
-        let b_section = binfile.take_section(5);
-        let c_section = binfile.take_section(6);
-        let qm_section = binfile.take_section(7);
-        let ql_section = binfile.take_section(8);
-        let qr_section = binfile.take_section(9);
-        let q0_section = binfile.take_section(10);
-        let qc_section = binfile.take_section(11);
-        let sigma_sections = binfile.take_section_raw(12);
-        let l_section = binfile.take_section(13);
-        let t_section = binfile.take_section(14);
+        // the sizes of the sections are determined by the header, so checking them up front
+        // bounds everything allocated while decoding by the size of the file
+        let mut section = |id: u32, num: usize, size: usize| {
+            let section = binfile.take_section_raw(id)?;
+            if num.checked_mul(size).is_none_or(|len| section.len() < len) {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "section {id} is too short for {num} elements"
+                )));
+            }
+            Ok(section)
+        };
+        let add_section = Cursor::new(section(3, n_additions, 8 + 2 * header.n8r)?);
+        let a_section = Cursor::new(section(4, n_constraints, 4)?);
+        let b_section = Cursor::new(section(5, n_constraints, 4)?);
+        let c_section = Cursor::new(section(6, n_constraints, 4)?);
+        let qm_section = Cursor::new(section(7, 1, sigma_section_size)?);
+        let ql_section = Cursor::new(section(8, 1, sigma_section_size)?);
+        let qr_section = Cursor::new(section(9, 1, sigma_section_size)?);
+        let q0_section = Cursor::new(section(10, 1, sigma_section_size)?);
+        let qc_section = Cursor::new(section(11, 1, sigma_section_size)?);
+        let sigma_sections = section(12, 3, sigma_section_size)?;
+        let l_section = Cursor::new(section(13, n_public, sigma_section_size)?);
+        let t_section = Cursor::new(section(
+            14,
+            domain_size + 6,
+            P::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED,
+        )?);
         let sigma1_section = Cursor::new(&sigma_sections[..sigma_section_size]);
         let sigma2_section =
             Cursor::new(&sigma_sections[sigma_section_size..sigma_section_size * 2]);
@@ -204,21 +236,24 @@ impl<P: Pairing + CircomArkworksPairingBridge> Zkey<P> {
         let mut lagrange = None;
         let mut p_tau = None;
         tracing::debug!("parsing zkey sections...");
-        std::thread::scope(|s| {
-            s.spawn(|| additions = Some(Self::additions_indices(n_additions, add_section)));
-            s.spawn(|| map_a = Some(Self::id_map(n_constraints, a_section)));
-            s.spawn(|| map_b = Some(Self::id_map(n_constraints, b_section)));
-            s.spawn(|| map_c = Some(Self::id_map(n_constraints, c_section)));
-            s.spawn(|| qm = Some(Self::evaluations(domain_size, qm_section)));
-            s.spawn(|| ql = Some(Self::evaluations(domain_size, ql_section)));
-            s.spawn(|| qr = Some(Self::evaluations(domain_size, qr_section)));
-            s.spawn(|| q0 = Some(Self::evaluations(domain_size, q0_section)));
-            s.spawn(|| qc = Some(Self::evaluations(domain_size, qc_section)));
-            s.spawn(|| sigma1 = Some(Self::evaluations(domain_size, sigma1_section)));
-            s.spawn(|| sigma2 = Some(Self::evaluations(domain_size, sigma2_section)));
-            s.spawn(|| sigma3 = Some(Self::evaluations(domain_size, sigma3_section)));
-            s.spawn(|| lagrange = Some(Self::lagrange(n_public, domain_size, l_section)));
-            s.spawn(|| p_tau = Some(Self::taus(domain_size, t_section, check)));
+        let check = options.check;
+        options.install(|| {
+            scope(|s| {
+                s.spawn(|| additions = Some(Self::additions_indices(n_additions, add_section)));
+                s.spawn(|| map_a = Some(Self::id_map(n_constraints, a_section)));
+                s.spawn(|| map_b = Some(Self::id_map(n_constraints, b_section)));
+                s.spawn(|| map_c = Some(Self::id_map(n_constraints, c_section)));
+                s.spawn(|| qm = Some(Self::evaluations(domain_size, qm_section)));
+                s.spawn(|| ql = Some(Self::evaluations(domain_size, ql_section)));
+                s.spawn(|| qr = Some(Self::evaluations(domain_size, qr_section)));
+                s.spawn(|| q0 = Some(Self::evaluations(domain_size, q0_section)));
+                s.spawn(|| qc = Some(Self::evaluations(domain_size, qc_section)));
+                s.spawn(|| sigma1 = Some(Self::evaluations(domain_size, sigma1_section)));
+                s.spawn(|| sigma2 = Some(Self::evaluations(domain_size, sigma2_section)));
+                s.spawn(|| sigma3 = Some(Self::evaluations(domain_size, sigma3_section)));
+                s.spawn(|| lagrange = Some(Self::lagrange(n_public, domain_size, l_section)));
+                s.spawn(|| p_tau = Some(Self::taus(domain_size, t_section, check)));
+            })
         });
         tracing::debug!("we are done with parsing sections!");
         Ok(Self {
@@ -394,7 +429,7 @@ impl<P: Pairing + CircomArkworksPairingBridge> PlonkHeader<P> {
         let n_constraints = u32::deserialize_uncompressed(&mut reader)?;
         tracing::debug!("n_vars: {n_vars}; n_public: {n_public}, domain_size: {domain_size}");
         let verifying_key = VerifyingKey::new(&mut reader)?;
-        if domain_size & (domain_size - 1) == 0 && domain_size > 0 {
+        if domain_size.is_power_of_two() {
             tracing::debug!("read header done!");
             Ok(Self {
                 n8r,
@@ -413,3 +448,95 @@ impl<P: Pairing + CircomArkworksPairingBridge> PlonkHeader<P> {
         }
     }
 }
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use ark_bn254::Bn254;
+    use ark_ff::BigInteger;
+
+    use super::*;
+
+    // the sections of a zkey for a domain of size one without constraints, all points are the
+    // point at infinity
+    fn empty_zkey() -> Vec<(u32, Vec<u8>)> {
+        let mut header = Vec::new();
+        for modulus in [
+            ark_bn254::Fq::MODULUS.to_bytes_le(),
+            ark_bn254::Fr::MODULUS.to_bytes_le(),
+        ] {
+            header.extend((modulus.len() as u32).to_le_bytes());
+            header.extend(modulus);
+        }
+        // n_vars, n_public, domain_size, n_additions, n_constraints
+        for value in [1u32, 0, 1, 0, 0] {
+            header.extend(value.to_le_bytes());
+        }
+        // k1, k2, eight points in G1 and x_2 in G2
+        header.extend([0u8; 2 * 32 + 8 * 64 + 128]);
+        let polynomial = vec![0u8; 5 * 32];
+        let mut sections = vec![(1, 2u32.to_le_bytes().to_vec()), (2, header)];
+        sections.extend((3..=6).map(|id| (id, vec![])));
+        sections.extend((7..=11).map(|id| (id, polynomial.clone())));
+        sections.push((12, polynomial.repeat(3)));
+        sections.push((13, vec![]));
+        sections.push((14, vec![0u8; 7 * 64]));
+        sections
+    }
+
+    fn to_bytes(sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = b"zkey".to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (id, section) in sections {
+            bytes.extend(id.to_le_bytes());
+            bytes.extend((section.len() as u64).to_le_bytes());
+            bytes.extend(section);
+        }
+        bytes
+    }
+
+    fn corrupted_bin_file(sections: &[(u32, Vec<u8>)]) -> String {
+        match Zkey::<Bn254>::from_reader(to_bytes(sections).as_slice(), CheckElement::Yes) {
+            Err(ZkeyParserError::CorruptedBinFile(msg)) => msg,
+            Err(err) => panic!("unexpected error {err}"),
+            Ok(_) => panic!("malformed zkey was accepted"),
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_bn254_zkey() {
+        let sections = empty_zkey();
+        let bytes = to_bytes(&sections);
+        let zkey = Zkey::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes).unwrap();
+        assert_eq!(zkey.domain_size, 1);
+        assert_eq!(zkey.p_tau.len(), 7);
+        for malformed in crate::tests::malformed(&bytes) {
+            // must not panic
+            let _ = Zkey::<Bn254>::from_reader(malformed.as_slice(), CheckElement::No);
+        }
+
+        let mut missing = sections.clone();
+        missing.retain(|(id, _)| *id != 12);
+        assert_eq!(corrupted_bin_file(&missing), "missing section 12");
+        let mut short = sections.clone();
+        short[11].1.truncate(2 * 5 * 32);
+        assert_eq!(
+            corrupted_bin_file(&short),
+            "section 12 is too short for 3 elements"
+        );
+        let mut additions = sections.clone();
+        additions[1].1[84..88].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            corrupted_bin_file(&additions),
+            format!("section 3 is too short for {} elements", u32::MAX)
+        );
+        assert!(matches!(
+            Zkey::<Bn254>::from_reader_with_options(
+                bytes.as_slice(),
+                &ParseOptions::default().with_max_elements(0)
+            ),
+            Err(ZkeyParserError::LimitExceeded(_))
+        ));
+    }
+}
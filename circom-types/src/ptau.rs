@@ -0,0 +1,877 @@
+//! This module defines the [`PowersOfTau`] struct that implements deserialization of the `.ptau` files
+//! produced by the powers-of-tau ceremony of snarkjs via [`PowersOfTau::from_reader`] and serialization
+//! via [`PowersOfTau::write_to`]. [`PowersOfTauView`] decodes the sections on demand instead.
+#[cfg(feature = "mmap")]
+use std::path::Path;
+use std::{
+    io::{Read, Write},
+    ops::Range,
+};
+
+use ark_ec::pairing::Pairing;
+use ark_ff::PrimeField;
+use ark_serde_compat::CheckElement;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{
+    ParseOptions,
+    binfile::{
+        BinFileView, BinFileWriter, ContributionParams, ZkeyParserError, ZkeyParserResult,
+        read_array,
+    },
+    options::scope,
+    traits::CircomArkworksPairingBridge,
+};
+
+const PTAU_HEADER: &str = "ptau";
+const PTAU_VERSION: u32 = 1;
+const PTAU_NUM_SECTIONS: u32 = 7;
+const PTAU_NUM_SECTIONS_PREPARED: u32 = 11;
+
+/// Represents a powers-of-tau file in the format defined by snarkjs (`.ptau`).
+///
+/// Files prepared for phase 2 (`snarkjs powersoftau prepare phase2`) additionally contain the
+/// powers of tau in Lagrange basis for all domains up to the size of the file, which are needed to
+/// derive a Groth16 zkey. The Lagrange sections are empty for files that are not prepared.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PowersOfTau<P: Pairing> {
+    /// The power of the file, i.e., the file supports circuits with up to `2^power` constraints
+    pub power: usize,
+    /// The power of the ceremony the file was produced by
+    pub ceremony_power: usize,
+    /// `tau^i` in G1 for `i` in `0..2^(power+1)-1`
+    pub tau_g1: Vec<P::G1Affine>,
+    /// `tau^i` in G2 for `i` in `0..2^power`
+    pub tau_g2: Vec<P::G2Affine>,
+    /// `alpha * tau^i` in G1 for `i` in `0..2^power`
+    pub alpha_tau_g1: Vec<P::G1Affine>,
+    /// `beta * tau^i` in G1 for `i` in `0..2^power`
+    pub beta_tau_g1: Vec<P::G1Affine>,
+    /// `beta` in G2
+    pub beta_g2: P::G2Affine,
+    /// The contributions to the ceremony in the order they were applied
+    pub contributions: Vec<PtauContribution<P>>,
+    /// The Lagrange basis evaluated at tau in G1 for the domains of size `2^p` for `p` in `0..=power+1`,
+    /// see [`PowersOfTau::lagrange_range`]
+    pub lagrange_tau_g1: Vec<P::G1Affine>,
+    /// The Lagrange basis evaluated at tau in G2 for the domains of size `2^p` for `p` in `0..=power`
+    pub lagrange_tau_g2: Vec<P::G2Affine>,
+    /// The Lagrange basis evaluated at tau multiplied by alpha in G1 for the domains of size `2^p` for `p` in `0..=power`
+    pub lagrange_alpha_tau_g1: Vec<P::G1Affine>,
+    /// The Lagrange basis evaluated at tau multiplied by beta in G1 for the domains of size `2^p` for `p` in `0..=power`
+    pub lagrange_beta_tau_g1: Vec<P::G1Affine>,
+}
+
+/// A single contribution to the powers-of-tau ceremony.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PtauContribution<P: Pairing> {
+    /// `tau` in G1 after applying this contribution
+    pub tau_g1: P::G1Affine,
+    /// `tau` in G2 after applying this contribution
+    pub tau_g2: P::G2Affine,
+    /// `alpha` in G1 after applying this contribution
+    pub alpha_g1: P::G1Affine,
+    /// `beta` in G1 after applying this contribution
+    pub beta_g1: P::G1Affine,
+    /// `beta` in G2 after applying this contribution
+    pub beta_g2: P::G2Affine,
+    /// The public key of the contribution
+    pub public_key: PtauPublicKey<P>,
+    /// The internal state of the hash of the response before the contribution
+    pub partial_hash: [u8; 216],
+    /// The challenge for the next contribution
+    pub next_challenge: [u8; 64],
+    /// `0` for a regular contribution and `1` for a beacon
+    pub contribution_type: u32,
+    /// the optional name of the contributor
+    pub name: Option<String>,
+    /// the number of iterations of the beacon as power of two, if this is a beacon
+    pub num_iterations_exp: Option<u8>,
+    /// the hash of the beacon, if this is a beacon
+    pub beacon_hash: Option<Vec<u8>>,
+}
+
+/// The public key of a powers-of-tau contribution, committing to the secrets tau, alpha and beta.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PtauPublicKey<P: Pairing> {
+    /// the random point `s` for tau
+    pub tau_g1_s: P::G1Affine,
+    /// `s` multiplied by tau
+    pub tau_g1_sx: P::G1Affine,
+    /// the random point `s` for alpha
+    pub alpha_g1_s: P::G1Affine,
+    /// `s` multiplied by alpha
+    pub alpha_g1_sx: P::G1Affine,
+    /// the random point `s` for beta
+    pub beta_g1_s: P::G1Affine,
+    /// `s` multiplied by beta
+    pub beta_g1_sx: P::G1Affine,
+    /// `sp` multiplied by tau
+    pub tau_g2_spx: P::G2Affine,
+    /// `sp` multiplied by alpha
+    pub alpha_g2_spx: P::G2Affine,
+    /// `sp` multiplied by beta
+    pub beta_g2_spx: P::G2Affine,
+}
+
+impl<P: Pairing> PowersOfTau<P> {
+    /// Returns the range of the Lagrange basis of the domain of size `2^power` within the Lagrange sections.
+    pub fn lagrange_range(power: usize) -> Range<usize> {
+        (1 << power) - 1..(1 << (power + 1)) - 1
+    }
+
+    /// Returns `true` if the file is prepared for phase 2, i.e., contains the Lagrange sections.
+    pub fn is_prepared(&self) -> bool {
+        !self.lagrange_tau_g1.is_empty()
+    }
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> PowersOfTau<P> {
+    /// Deserializes a [`PowersOfTau`] from a reader.
+    ///
+    /// You may use the second parameter to specify whether
+    /// the deserialization should check if the elements are on
+    /// their respective curve.
+    ///
+    /// `No` indicates to skip those checks, which is by orders of magnitude
+    /// faster, but could potentially result in undefined behaviour. Use
+    /// only with care.
+    ///
+    /// See [`CheckElement`].
+    pub fn from_reader<R: Read>(reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default().with_check(check))
+    }
+
+    /// Deserializes a [`PowersOfTau`] from a reader with the given [`ParseOptions`].
+    ///
+    /// The sections are decoded in parallel on the configured thread pool.
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        tracing::debug!("reading ptau...");
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        PowersOfTauView::with_options(&bytes, options)?.decode()
+    }
+
+    /// Deserializes a [`PowersOfTau`] from the file at `path` by memory-mapping it.
+    ///
+    /// Use [`PowersOfTauView`] directly to only decode the required sections.
+    ///
+    /// See [`PowersOfTau::from_reader`] for the semantics of `check`.
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap(path: impl AsRef<Path>, check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::from_mmap_with_options(path, &ParseOptions::default().with_check(check))
+    }
+
+    /// Deserializes a [`PowersOfTau`] from the file at `path` by memory-mapping it, with the
+    /// given [`ParseOptions`].
+    #[cfg(feature = "mmap")]
+    pub fn from_mmap_with_options(
+        path: impl AsRef<Path>,
+        options: &ParseOptions,
+    ) -> ZkeyParserResult<Self> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: the map only lives for the duration of this function. Modifying the
+        // file concurrently is undefined behaviour, as with every memory-mapped file.
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        PowersOfTauView::<P>::with_options(&mmap, options)?.decode()
+    }
+
+    /// Serializes the [`PowersOfTau`] into a writer in the format produced by snarkjs.
+    ///
+    /// Writing a [`PowersOfTau`] obtained from [`PowersOfTau::from_reader`] reproduces the original file.
+    pub fn write_to<W: Write>(&self, writer: W) -> ZkeyParserResult<()> {
+        tracing::debug!("writing ptau...");
+        let num_sections = if self.is_prepared() {
+            PTAU_NUM_SECTIONS_PREPARED
+        } else {
+            PTAU_NUM_SECTIONS
+        };
+        let mut binfile = BinFileWriter::new(writer, PTAU_HEADER, PTAU_VERSION, num_sections)?;
+
+        let mut section = Vec::new();
+        P::BaseField::MODULUS_BIT_SIZE
+            .div_ceil(8)
+            .serialize_uncompressed(&mut section)?;
+        <P::BaseField as PrimeField>::MODULUS.serialize_uncompressed(&mut section)?;
+        usize_to_u32(self.power)?.serialize_uncompressed(&mut section)?;
+        usize_to_u32(self.ceremony_power)?.serialize_uncompressed(&mut section)?;
+        binfile.write_section(1, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.tau_g1, &mut section)?;
+        binfile.write_section(2, &section)?;
+
+        section.clear();
+        P::g2_vec_to_writer(&self.tau_g2, &mut section)?;
+        binfile.write_section(3, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.alpha_tau_g1, &mut section)?;
+        binfile.write_section(4, &section)?;
+
+        section.clear();
+        P::g1_vec_to_writer(&self.beta_tau_g1, &mut section)?;
+        binfile.write_section(5, &section)?;
+
+        section.clear();
+        P::g2_to_writer(&self.beta_g2, &mut section)?;
+        binfile.write_section(6, &section)?;
+
+        section.clear();
+        section.write_u32::<LittleEndian>(usize_to_u32(self.contributions.len())?)?;
+        for contribution in &self.contributions {
+            contribution.write_to(&mut section)?;
+        }
+        binfile.write_section(7, &section)?;
+
+        if self.is_prepared() {
+            section.clear();
+            P::g1_vec_to_writer(&self.lagrange_tau_g1, &mut section)?;
+            binfile.write_section(12, &section)?;
+
+            section.clear();
+            P::g2_vec_to_writer(&self.lagrange_tau_g2, &mut section)?;
+            binfile.write_section(13, &section)?;
+
+            section.clear();
+            P::g1_vec_to_writer(&self.lagrange_alpha_tau_g1, &mut section)?;
+            binfile.write_section(14, &section)?;
+
+            section.clear();
+            P::g1_vec_to_writer(&self.lagrange_beta_tau_g1, &mut section)?;
+            binfile.write_section(15, &section)?;
+        }
+        binfile.finish()?;
+        tracing::debug!("ptau writing done!");
+        Ok(())
+    }
+}
+
+impl<P: Pairing + CircomArkworksPairingBridge> PtauContribution<P> {
+    fn from_reader<R: Read>(mut reader: R, check: CheckElement) -> ZkeyParserResult<Self> {
+        let tau_g1 = P::g1_from_reader(&mut reader, check)?;
+        let tau_g2 = P::g2_from_reader(&mut reader, check)?;
+        let alpha_g1 = P::g1_from_reader(&mut reader, check)?;
+        let beta_g1 = P::g1_from_reader(&mut reader, check)?;
+        let beta_g2 = P::g2_from_reader(&mut reader, check)?;
+        let public_key = PtauPublicKey {
+            tau_g1_s: P::g1_from_reader(&mut reader, check)?,
+            tau_g1_sx: P::g1_from_reader(&mut reader, check)?,
+            alpha_g1_s: P::g1_from_reader(&mut reader, check)?,
+            alpha_g1_sx: P::g1_from_reader(&mut reader, check)?,
+            beta_g1_s: P::g1_from_reader(&mut reader, check)?,
+            beta_g1_sx: P::g1_from_reader(&mut reader, check)?,
+            tau_g2_spx: P::g2_from_reader(&mut reader, check)?,
+            alpha_g2_spx: P::g2_from_reader(&mut reader, check)?,
+            beta_g2_spx: P::g2_from_reader(&mut reader, check)?,
+        };
+        let partial_hash = read_array(&mut reader)?;
+        let next_challenge = read_array(&mut reader)?;
+        let contribution_type = reader.read_u32::<LittleEndian>()?;
+        let ContributionParams {
+            name,
+            num_iterations_exp,
+            beacon_hash,
+        } = ContributionParams::read(&mut reader)?;
+        Ok(Self {
+            tau_g1,
+            tau_g2,
+            alpha_g1,
+            beta_g1,
+            beta_g2,
+            public_key,
+            partial_hash,
+            next_challenge,
+            contribution_type,
+            name,
+            num_iterations_exp,
+            beacon_hash,
+        })
+    }
+
+    fn write_to<W: Write>(&self, mut writer: W) -> ZkeyParserResult<()> {
+        P::g1_to_writer(&self.tau_g1, &mut writer)?;
+        P::g2_to_writer(&self.tau_g2, &mut writer)?;
+        P::g1_to_writer(&self.alpha_g1, &mut writer)?;
+        P::g1_to_writer(&self.beta_g1, &mut writer)?;
+        P::g2_to_writer(&self.beta_g2, &mut writer)?;
+        let key = &self.public_key;
+        for p in [
+            &key.tau_g1_s,
+            &key.tau_g1_sx,
+            &key.alpha_g1_s,
+            &key.alpha_g1_sx,
+            &key.beta_g1_s,
+            &key.beta_g1_sx,
+        ] {
+            P::g1_to_writer(p, &mut writer)?;
+        }
+        for p in [&key.tau_g2_spx, &key.alpha_g2_spx, &key.beta_g2_spx] {
+            P::g2_to_writer(p, &mut writer)?;
+        }
+        writer.write_all(&self.partial_hash)?;
+        writer.write_all(&self.next_challenge)?;
+        writer.write_u32::<LittleEndian>(self.contribution_type)?;
+        ContributionParams {
+            name: self.name.clone(),
+            num_iterations_exp: self.num_iterations_exp,
+            beacon_hash: self.beacon_hash.clone(),
+        }
+        .write(writer)
+    }
+}
+
+/// A lazily decoded view on a powers-of-tau file in the format defined by snarkjs.
+///
+/// Creating the view only decodes the header and checks the sizes of the sections. All other
+/// sections are borrowed from the underlying bytes (e.g., a memory-mapped file) and decoded on
+/// demand. This is useful for large ceremony files, of which usually only the Lagrange basis of a
+/// single domain is needed, see [`PowersOfTauView::lagrange_tau_g1`].
+///
+/// Use [`PowersOfTauView::decode`] to decode all sections into a [`PowersOfTau`].
+#[derive(Clone, Debug)]
+pub struct PowersOfTauView<'a, P: Pairing> {
+    power: usize,
+    ceremony_power: usize,
+    sections: BinFileView<'a>,
+    options: ParseOptions,
+    phantom_data: std::marker::PhantomData<P>,
+}
+
+impl<'a, P: Pairing + CircomArkworksPairingBridge> PowersOfTauView<'a, P> {
+    /// Creates a [`PowersOfTauView`] on the powers of tau contained in `bytes`.
+    ///
+    /// See [`PowersOfTau::from_reader`] for the semantics of `check`, which applies to
+    /// all elements decoded through this view.
+    pub fn new(bytes: &'a [u8], check: CheckElement) -> ZkeyParserResult<Self> {
+        Self::with_options(bytes, &ParseOptions::default().with_check(check))
+    }
+
+    /// Creates a [`PowersOfTauView`] on the powers of tau contained in `bytes` with the given
+    /// [`ParseOptions`], which apply to all elements decoded through this view.
+    pub fn with_options(bytes: &'a [u8], options: &ParseOptions) -> ZkeyParserResult<Self> {
+        let (ftype, _, sections) = BinFileView::with_header(bytes)?;
+        if ftype != PTAU_HEADER {
+            return Err(ZkeyParserError::CorruptedBinFile(format!(
+                "expected file type \"{PTAU_HEADER}\" but got \"{ftype}\""
+            )));
+        }
+        for (id, section) in sections.iter() {
+            options.check_section_size(id, section.len() as u64)?;
+        }
+        let (power, ceremony_power) = read_header::<P>(sections.section(1))?;
+        options.check_element_count("domain elements", 1 << power)?;
+        let view = Self {
+            power,
+            ceremony_power,
+            sections,
+            options: options.clone(),
+            phantom_data: std::marker::PhantomData,
+        };
+        view.check_section_sizes()?;
+        Ok(view)
+    }
+
+    fn check_section_sizes(&self) -> ZkeyParserResult<()> {
+        let g1 = P::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED;
+        let g2 = P::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED;
+        let num_powers = 1 << self.power;
+        let mut expected = vec![
+            (2, 2 * num_powers - 1, g1),
+            (3, num_powers, g2),
+            (4, num_powers, g1),
+            (5, num_powers, g1),
+            (6, 1, g2),
+        ];
+        if self.is_prepared() {
+            expected.extend([
+                (12, 4 * num_powers - 1, g1),
+                (13, 2 * num_powers - 1, g2),
+                (14, 2 * num_powers - 1, g1),
+                (15, 2 * num_powers - 1, g1),
+            ]);
+        }
+        for (id, num, size) in expected {
+            let len = self.sections.section(id).len();
+            if num * size != len {
+                return Err(ZkeyParserError::CorruptedBinFile(format!(
+                    "expected {num} elements in section {id} but section has {len} bytes"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The power of the file, i.e., the file supports circuits with up to `2^power` constraints
+    pub fn power(&self) -> usize {
+        self.power
+    }
+
+    /// The power of the ceremony the file was produced by
+    pub fn ceremony_power(&self) -> usize {
+        self.ceremony_power
+    }
+
+    /// Returns `true` if the file is prepared for phase 2, i.e., contains the Lagrange sections.
+    pub fn is_prepared(&self) -> bool {
+        !self.sections.section(12).is_empty()
+    }
+
+    /// Decodes all sections into a [`PowersOfTau`], in parallel on the thread pool of the [`ParseOptions`].
+    pub fn decode(&self) -> ZkeyParserResult<PowersOfTau<P>> {
+        let mut tau_g1 = None;
+        let mut tau_g2 = None;
+        let mut alpha_tau_g1 = None;
+        let mut beta_tau_g1 = None;
+        let mut lagrange_tau_g1 = None;
+        let mut lagrange_tau_g2 = None;
+        let mut lagrange_alpha_tau_g1 = None;
+        let mut lagrange_beta_tau_g1 = None;
+
+        tracing::debug!("parsing ptau sections...");
+        self.options.install(|| {
+            scope(|s| {
+                s.spawn(|| tau_g1 = Some(self.tau_g1()));
+                s.spawn(|| tau_g2 = Some(self.tau_g2()));
+                s.spawn(|| alpha_tau_g1 = Some(self.alpha_tau_g1()));
+                s.spawn(|| beta_tau_g1 = Some(self.beta_tau_g1()));
+                s.spawn(|| lagrange_tau_g1 = Some(self.g1_elements(12, ..)));
+                s.spawn(|| lagrange_tau_g2 = Some(self.g2_elements(13, ..)));
+                s.spawn(|| lagrange_alpha_tau_g1 = Some(self.g1_elements(14, ..)));
+                s.spawn(|| lagrange_beta_tau_g1 = Some(self.g1_elements(15, ..)));
+            })
+        });
+        tracing::debug!("ptau parsing done!");
+        Ok(PowersOfTau {
+            power: self.power,
+            ceremony_power: self.ceremony_power,
+            tau_g1: tau_g1.unwrap()?,
+            tau_g2: tau_g2.unwrap()?,
+            alpha_tau_g1: alpha_tau_g1.unwrap()?,
+            beta_tau_g1: beta_tau_g1.unwrap()?,
+            beta_g2: self.beta_g2()?,
+            contributions: self.contributions()?,
+            lagrange_tau_g1: lagrange_tau_g1.unwrap()?,
+            lagrange_tau_g2: lagrange_tau_g2.unwrap()?,
+            lagrange_alpha_tau_g1: lagrange_alpha_tau_g1.unwrap()?,
+            lagrange_beta_tau_g1: lagrange_beta_tau_g1.unwrap()?,
+        })
+    }
+
+    /// Decodes the powers of tau in G1
+    pub fn tau_g1(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_elements(2, ..)
+    }
+
+    /// Decodes the powers of tau in G2
+    pub fn tau_g2(&self) -> ZkeyParserResult<Vec<P::G2Affine>> {
+        self.g2_elements(3, ..)
+    }
+
+    /// Decodes the powers of tau multiplied by alpha in G1
+    pub fn alpha_tau_g1(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_elements(4, ..)
+    }
+
+    /// Decodes the powers of tau multiplied by beta in G1
+    pub fn beta_tau_g1(&self) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.g1_elements(5, ..)
+    }
+
+    /// Decodes beta in G2
+    pub fn beta_g2(&self) -> ZkeyParserResult<P::G2Affine> {
+        Ok(P::g2_from_reader(
+            self.sections.section(6),
+            self.options.check,
+        )?)
+    }
+
+    /// Decodes the contributions to the ceremony
+    pub fn contributions(&self) -> ZkeyParserResult<Vec<PtauContribution<P>>> {
+        let mut section = self.sections.section(7);
+        let num_contributions = section.read_u32::<LittleEndian>()?;
+        self.options
+            .check_element_count("contributions", num_contributions as usize)?;
+        (0..num_contributions)
+            .map(|_| PtauContribution::from_reader(&mut section, self.options.check))
+            .collect()
+    }
+
+    /// Decodes the Lagrange basis of the domain of size `2^power` evaluated at tau in G1.
+    ///
+    /// Returns an error if the file is not prepared or `power` exceeds `self.power() + 1`.
+    pub fn lagrange_tau_g1(&self, power: usize) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.check_lagrange_power(power, self.power + 1)?;
+        self.g1_elements(12, PowersOfTau::<P>::lagrange_range(power))
+    }
+
+    /// Decodes the Lagrange basis of the domain of size `2^power` evaluated at tau in G2.
+    ///
+    /// Returns an error if the file is not prepared or `power` exceeds `self.power()`.
+    pub fn lagrange_tau_g2(&self, power: usize) -> ZkeyParserResult<Vec<P::G2Affine>> {
+        self.check_lagrange_power(power, self.power)?;
+        self.g2_elements(13, PowersOfTau::<P>::lagrange_range(power))
+    }
+
+    /// Decodes the Lagrange basis of the domain of size `2^power` evaluated at tau multiplied by alpha in G1.
+    ///
+    /// Returns an error if the file is not prepared or `power` exceeds `self.power()`.
+    pub fn lagrange_alpha_tau_g1(&self, power: usize) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.check_lagrange_power(power, self.power)?;
+        self.g1_elements(14, PowersOfTau::<P>::lagrange_range(power))
+    }
+
+    /// Decodes the Lagrange basis of the domain of size `2^power` evaluated at tau multiplied by beta in G1.
+    ///
+    /// Returns an error if the file is not prepared or `power` exceeds `self.power()`.
+    pub fn lagrange_beta_tau_g1(&self, power: usize) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        self.check_lagrange_power(power, self.power)?;
+        self.g1_elements(15, PowersOfTau::<P>::lagrange_range(power))
+    }
+
+    fn check_lagrange_power(&self, power: usize, max_power: usize) -> ZkeyParserResult<()> {
+        if !self.is_prepared() {
+            return Err(ZkeyParserError::CorruptedBinFile(
+                "powers of tau is not prepared for phase 2".to_owned(),
+            ));
+        }
+        if power > max_power {
+            return Err(ZkeyParserError::CorruptedBinFile(format!(
+                "no Lagrange basis for power {power}, maximum is {max_power}"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Decodes the elements in `range` of a G1 section, whose size was checked on creation.
+    fn g1_elements(
+        &self,
+        id: u32,
+        range: impl std::ops::RangeBounds<usize>,
+    ) -> ZkeyParserResult<Vec<P::G1Affine>> {
+        let section = self.sections.section(id);
+        let bytes = element_range(section, range, P::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED);
+        Ok(P::g1_vec_from_bytes(bytes, self.options.check)?)
+    }
+
+    /// Decodes the elements in `range` of a G2 section, whose size was checked on creation.
+    fn g2_elements(
+        &self,
+        id: u32,
+        range: impl std::ops::RangeBounds<usize>,
+    ) -> ZkeyParserResult<Vec<P::G2Affine>> {
+        let section = self.sections.section(id);
+        let bytes = element_range(section, range, P::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED);
+        Ok(P::g2_vec_from_bytes(bytes, self.options.check)?)
+    }
+}
+
+fn element_range(
+    section: &[u8],
+    range: impl std::ops::RangeBounds<usize>,
+    element_size: usize,
+) -> &[u8] {
+    use std::ops::Bound;
+    let start = match range.start_bound() {
+        Bound::Included(start) => start * element_size,
+        Bound::Excluded(start) => (start + 1) * element_size,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => (end + 1) * element_size,
+        Bound::Excluded(end) => end * element_size,
+        Bound::Unbounded => section.len(),
+    };
+    &section[start..end]
+}
+
+fn usize_to_u32(x: usize) -> ZkeyParserResult<u32> {
+    u32::try_from(x)
+        .map_err(|_| ZkeyParserError::CorruptedBinFile(format!("{x} does not fit into u32")))
+}
+
+fn read_header<P: Pairing>(mut section: &[u8]) -> ZkeyParserResult<(usize, usize)> {
+    let n8q = u32::deserialize_uncompressed(&mut section)?;
+    let expected_n8q = P::BaseField::MODULUS_BIT_SIZE.div_ceil(8);
+    if n8q != expected_n8q {
+        return Err(ZkeyParserError::UnexpectedByteSize(expected_n8q, n8q));
+    }
+    let q = <P::BaseField as PrimeField>::BigInt::deserialize_uncompressed(&mut section)?;
+    if q != <P::BaseField as PrimeField>::MODULUS {
+        return Err(ZkeyParserError::InvalidPrimeInHeader);
+    }
+    let power = u32::deserialize_uncompressed(&mut section)?;
+    let ceremony_power = u32::deserialize_uncompressed(&mut section)?;
+    tracing::debug!("ptau power: {power}; ceremony power: {ceremony_power}");
+    // the byte size of the Lagrange section in G1 must fit into usize
+    if power > usize::BITS - 12 {
+        return Err(ZkeyParserError::CorruptedBinFile(format!(
+            "power {power} too large"
+        )));
+    }
+    Ok((
+        usize::try_from(power).expect("u32 fits into usize"),
+        usize::try_from(ceremony_power).expect("u32 fits into usize"),
+    ))
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+impl<P: Pairing> PowersOfTau<P> {
+    /// Creates a prepared [`PowersOfTau`] from the toxic waste.
+    pub(crate) fn from_toxic_waste(
+        power: usize,
+        tau: P::ScalarField,
+        alpha: P::ScalarField,
+        beta: P::ScalarField,
+    ) -> Self {
+        use ark_ec::{AffineRepr, CurveGroup};
+        use ark_ff::Field;
+        use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+
+        let g1 = P::G1Affine::generator();
+        let g2 = P::G2Affine::generator();
+        let powers = |n: usize| {
+            std::iter::successors(Some(P::ScalarField::ONE), |x| Some(*x * tau))
+                .take(n)
+                .collect::<Vec<_>>()
+        };
+        let g1_times = |scalars: &[P::ScalarField], factor: P::ScalarField| {
+            P::G1::normalize_batch(
+                &scalars
+                    .iter()
+                    .map(|s| g1 * (*s * factor))
+                    .collect::<Vec<_>>(),
+            )
+        };
+        let g2_times = |scalars: &[P::ScalarField]| {
+            P::G2::normalize_batch(&scalars.iter().map(|s| g2 * s).collect::<Vec<_>>())
+        };
+        let lagrange = |max_power: usize| {
+            (0..=max_power)
+                .flat_map(|p| {
+                    Radix2EvaluationDomain::<P::ScalarField>::new(1 << p)
+                        .unwrap()
+                        .evaluate_all_lagrange_coefficients(tau)
+                })
+                .collect::<Vec<_>>()
+        };
+        let num_powers = 1 << power;
+        let lagrange_g1 = lagrange(power + 1);
+        let lagrange_g2 = lagrange(power);
+        Self {
+            power,
+            ceremony_power: power,
+            tau_g1: g1_times(&powers(2 * num_powers - 1), P::ScalarField::ONE),
+            tau_g2: g2_times(&powers(num_powers)),
+            alpha_tau_g1: g1_times(&powers(num_powers), alpha),
+            beta_tau_g1: g1_times(&powers(num_powers), beta),
+            beta_g2: (g2 * beta).into_affine(),
+            contributions: vec![],
+            lagrange_tau_g1: g1_times(&lagrange_g1, P::ScalarField::ONE),
+            lagrange_tau_g2: g2_times(&lagrange_g2),
+            lagrange_alpha_tau_g1: g1_times(&lagrange_g2, alpha),
+            lagrange_beta_tau_g1: g1_times(&lagrange_g2, beta),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "bn254")]
+mod bn254_tests {
+    use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+
+    use super::*;
+
+    #[test]
+    fn can_write_and_read_ptau() {
+        let ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            2,
+            Fr::from(42u64),
+            Fr::from(7u64),
+            Fr::from(11u64),
+        );
+        assert_eq!(ptau.tau_g1.len(), 7);
+        assert_eq!(ptau.lagrange_tau_g1.len(), 15);
+        assert_eq!(ptau.lagrange_tau_g2.len(), 7);
+        assert_eq!(PowersOfTau::<Bn254>::lagrange_range(2), 3..7);
+
+        let mut bytes = Vec::new();
+        ptau.write_to(&mut bytes).unwrap();
+        let read = PowersOfTau::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes).unwrap();
+        assert_eq!(read, ptau);
+
+        let mut rewritten = Vec::new();
+        read.write_to(&mut rewritten).unwrap();
+        assert_eq!(rewritten, bytes);
+
+        // not prepared for phase 2
+        let unprepared = PowersOfTau {
+            lagrange_tau_g1: vec![],
+            lagrange_tau_g2: vec![],
+            lagrange_alpha_tau_g1: vec![],
+            lagrange_beta_tau_g1: vec![],
+            ..ptau
+        };
+        let mut bytes = Vec::new();
+        unprepared.write_to(&mut bytes).unwrap();
+        let read = PowersOfTau::<Bn254>::from_reader(bytes.as_slice(), CheckElement::Yes).unwrap();
+        assert!(!read.is_prepared());
+        assert_eq!(read, unprepared);
+
+        assert!(
+            PowersOfTau::<Bn254>::from_reader(&bytes[..bytes.len() - 1], CheckElement::Yes)
+                .is_err()
+        );
+        let view = PowersOfTauView::<Bn254>::new(&bytes, CheckElement::Yes).unwrap();
+        assert!(view.lagrange_tau_g1(1).is_err());
+    }
+
+    #[test]
+    fn can_view_ptau() {
+        let mut ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            3,
+            Fr::from(42u64),
+            Fr::from(7u64),
+            Fr::from(11u64),
+        );
+        let g1 = |x: u64| (G1Affine::generator() * Fr::from(x)).into_affine();
+        let g2 = |x: u64| (G2Affine::generator() * Fr::from(x)).into_affine();
+        let contribution = PtauContribution {
+            tau_g1: ptau.tau_g1[1],
+            tau_g2: ptau.tau_g2[1],
+            alpha_g1: ptau.alpha_tau_g1[0],
+            beta_g1: ptau.beta_tau_g1[0],
+            beta_g2: ptau.beta_g2,
+            public_key: PtauPublicKey {
+                tau_g1_s: g1(1),
+                tau_g1_sx: g1(2),
+                alpha_g1_s: g1(3),
+                alpha_g1_sx: g1(4),
+                beta_g1_s: g1(5),
+                beta_g1_sx: g1(6),
+                tau_g2_spx: g2(7),
+                alpha_g2_spx: g2(8),
+                beta_g2_spx: g2(9),
+            },
+            partial_hash: [1; 216],
+            next_challenge: [2; 64],
+            contribution_type: 0,
+            name: Some("first".to_owned()),
+            num_iterations_exp: None,
+            beacon_hash: None,
+        };
+        let beacon = PtauContribution {
+            contribution_type: 1,
+            name: None,
+            num_iterations_exp: Some(10),
+            beacon_hash: Some(vec![3; 32]),
+            ..contribution.clone()
+        };
+        ptau.contributions = vec![contribution, beacon];
+
+        let mut bytes = Vec::new();
+        ptau.write_to(&mut bytes).unwrap();
+        let view = PowersOfTauView::<Bn254>::new(&bytes, CheckElement::Yes).unwrap();
+        assert_eq!(view.power(), 3);
+        assert_eq!(view.ceremony_power(), 3);
+        assert!(view.is_prepared());
+        assert_eq!(view.contributions().unwrap(), ptau.contributions);
+        assert_eq!(view.decode().unwrap(), ptau);
+
+        for power in 0..=3 {
+            let range = PowersOfTau::<Bn254>::lagrange_range(power);
+            assert_eq!(
+                view.lagrange_tau_g1(power).unwrap(),
+                ptau.lagrange_tau_g1[range.clone()]
+            );
+            assert_eq!(
+                view.lagrange_tau_g2(power).unwrap(),
+                ptau.lagrange_tau_g2[range.clone()]
+            );
+            assert_eq!(
+                view.lagrange_alpha_tau_g1(power).unwrap(),
+                ptau.lagrange_alpha_tau_g1[range.clone()]
+            );
+            assert_eq!(
+                view.lagrange_beta_tau_g1(power).unwrap(),
+                ptau.lagrange_beta_tau_g1[range]
+            );
+        }
+        let range = PowersOfTau::<Bn254>::lagrange_range(4);
+        assert_eq!(
+            view.lagrange_tau_g1(4).unwrap(),
+            ptau.lagrange_tau_g1[range]
+        );
+        assert!(view.lagrange_tau_g1(5).is_err());
+        assert!(view.lagrange_tau_g2(4).is_err());
+
+        let mut rewritten = Vec::new();
+        view.decode().unwrap().write_to(&mut rewritten).unwrap();
+        assert_eq!(rewritten, bytes);
+    }
+
+    #[test]
+    fn can_read_ptau_with_options() {
+        let ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            2,
+            Fr::from(42u64),
+            Fr::from(7u64),
+            Fr::from(11u64),
+        );
+        let mut bytes = Vec::new();
+        ptau.write_to(&mut bytes).unwrap();
+
+        // the largest section holds the 15 Lagrange coefficients in G1
+        let options = ParseOptions::default()
+            .with_max_elements(4)
+            .with_max_section_size(15 * 64);
+        let read = PowersOfTau::<Bn254>::from_reader_with_options(bytes.as_slice(), &options);
+        assert_eq!(read.unwrap(), ptau);
+        for options in [
+            ParseOptions::default().with_max_elements(3),
+            ParseOptions::default().with_max_section_size(15 * 64 - 1),
+        ] {
+            assert!(matches!(
+                PowersOfTauView::<Bn254>::with_options(&bytes, &options),
+                Err(ZkeyParserError::LimitExceeded(_))
+            ));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn can_decode_ptau_on_thread_pool() {
+        use crate::options::tests::decode_on_blocked_thread_pool;
+
+        let ptau = PowersOfTau::<Bn254>::from_toxic_waste(
+            2,
+            Fr::from(42u64),
+            Fr::from(7u64),
+            Fr::from(11u64),
+        );
+        let mut bytes = Vec::new();
+        ptau.write_to(&mut bytes).unwrap();
+
+        let read = decode_on_blocked_thread_pool(|options| {
+            PowersOfTau::<Bn254>::from_reader_with_options(bytes.as_slice(), options)
+        });
+        assert_eq!(read.unwrap(), ptau);
+
+        #[cfg(feature = "mmap")]
+        {
+            let path = std::env::temp_dir().join(format!("ptau-{}.ptau", std::process::id()));
+            std::fs::write(&path, &bytes).unwrap();
+            let read = decode_on_blocked_thread_pool(|options| {
+                PowersOfTau::<Bn254>::from_mmap_with_options(&path, options)
+            });
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(read.unwrap(), ptau);
+        }
+    }
+}
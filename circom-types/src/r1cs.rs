@@ -27,17 +27,25 @@
 //! This module provides the [`R1CS`] type which implements [`R1CS::from_reader`] for parsing the R1CS file format used by Circom.
 //! Inspired by <https://github.com/arkworks-rs/circom-compat/blob/170b10fc9ed182b5f72ecf379033dda023d0bf07/src/circom/r1cs_reader.rs>
 use ark_ff::PrimeField;
+#[cfg(feature = "witness")]
+use ark_ff::{One, Zero};
 use byteorder::{LittleEndian, ReadBytesExt};
 use std::io::{Error, ErrorKind};
 use thiserror::Error;
 
 use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::ConstraintMatrices;
 use ark_serialize::{CanonicalDeserialize, SerializationError};
-use ark_std::io::{Read, Seek, SeekFrom};
+use ark_std::io::{Read, Seek, SeekFrom, Take};
 
 use std::collections::HashMap;
 
+#[cfg(feature = "symbols")]
+use crate::Symbols;
+#[cfg(feature = "witness")]
+use crate::Witness;
 use crate::{
+    LimitExceeded, ParseOptions,
     reader_utils::{self, InvalidHeaderError},
     traits::CircomArkworksPairingBridge,
 };
@@ -68,6 +76,30 @@ pub enum R1CSParserError {
     /// Error describing that the ScalarField from curve does not match in witness file
     #[error("ScalarField from curve does not match in witness file")]
     WrongScalarField,
+    /// Error describing that a custom gate application references a gate that is not in the custom gates list
+    #[error("Custom gate application references gate {0}, but there are only {1} custom gates")]
+    UnknownCustomGate(usize, usize),
+    /// Error describing that the file exceeds the limits configured in [`ParseOptions`]
+    #[error(transparent)]
+    LimitExceeded(#[from] LimitExceeded),
+}
+
+/// A custom gate template used by the circuit, as declared with `pragma custom_templates`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomGate<F> {
+    /// Name of the template
+    pub name: String,
+    /// Parameters the template is instantiated with
+    pub parameters: Vec<F>,
+}
+
+/// An application of a [`CustomGate`] to signals of the circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CustomGateApplication {
+    /// Index of the applied gate in [`R1CS::custom_gates`]
+    pub gate: usize,
+    /// Wires the gate is applied to
+    pub signals: Vec<usize>,
 }
 
 /// Struct representing a R1CS file produced by Circom that implements [`R1CS::from_reader`] for parsing the R1CS file format used by Circom.
@@ -93,11 +125,23 @@ pub struct R1CS<P: Pairing> {
     pub n_labels: u64,
     /// Number of constraints
     pub n_constraints: usize,
+    /// Custom gates used by the circuit (section 4), empty if the circuit uses no custom templates
+    pub custom_gates: Vec<CustomGate<P::ScalarField>>,
+    /// Applications of the custom gates (section 5), empty if the circuit uses no custom templates
+    pub custom_gate_applications: Vec<CustomGateApplication>,
 }
 
 impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
     /// Parses an [`R1CS`] file from a reader and returns [`R1CSParserError`] on failure
-    pub fn from_reader<R: Read + Seek>(mut reader: R) -> Result<Self> {
+    pub fn from_reader<R: Read + Seek>(reader: R) -> Result<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Parses an [`R1CS`] file from a reader and rejects files exceeding the limits of the given [`ParseOptions`]
+    pub fn from_reader_with_options<R: Read + Seek>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self> {
         reader_utils::read_header(&mut reader, R1CS_HEADER)?;
         let version = reader.read_u32::<LittleEndian>()?;
         if version != MAX_VERSION {
@@ -105,7 +149,10 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
         }
         let num_sections = reader.read_u32::<LittleEndian>()?;
 
-        // todo: handle sec_size correctly
+        let sections_start = reader.stream_position()?;
+        let file_size = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(sections_start))?;
+
         // section type -> file offset
         let mut sec_offsets = HashMap::<u32, u64>::new();
         let mut sec_sizes = HashMap::<u32, u64>::new();
@@ -114,15 +161,27 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
         for _ in 0..num_sections {
             let sec_type = reader.read_u32::<LittleEndian>()?;
             let sec_size = reader.read_u64::<LittleEndian>()?;
+            options.check_section_size(sec_type, sec_size)?;
             let offset = reader.stream_position()?;
+            let end = offset
+                .checked_add(sec_size)
+                .filter(|end| *end <= file_size)
+                .ok_or_else(|| {
+                    Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Section {sec_type} exceeds file size"),
+                    )
+                })?;
             sec_offsets.insert(sec_type, offset);
             sec_sizes.insert(sec_type, sec_size);
-            reader.seek(SeekFrom::Current(sec_size as i64))?;
+            reader.seek(SeekFrom::Start(end))?;
         }
 
         let header_type = 1;
         let constraint_type = 2;
         let wire2label_type = 3;
+        let custom_gates_list_type = 4;
+        let custom_gates_application_type = 5;
 
         let header_offset = sec_offsets.get(&header_type).ok_or_else(|| {
             Error::new(
@@ -164,6 +223,18 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
         let n_labels = reader.read_u64::<LittleEndian>()?;
         let n_constraints =
             usize::try_from(reader.read_u32::<LittleEndian>()?).expect("u32 fits into usize");
+        options.check_element_count("wires", num_variables)?;
+        options.check_element_count("constraints", n_constraints)?;
+        let num_inputs = 1 + n_pub_in as usize + n_pub_out as usize;
+        if num_inputs + n_prv_in as usize > num_variables {
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "{num_inputs} public and {n_prv_in} private signals, but only {num_variables} wires"
+                ),
+            ))?;
+        }
+        let num_aux = num_variables - num_inputs;
 
         let constraint_offset = sec_offsets.get(&constraint_type).ok_or_else(|| {
             Error::new(
@@ -174,7 +245,14 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
 
         reader.seek(SeekFrom::Start(*constraint_offset?))?;
 
-        let constraints = read_constraints::<&mut R, P>(&mut reader, n_constraints, field_size)?;
+        let constraint_size = sec_sizes[&constraint_type];
+        let constraints = read_constraints::<_, P>(
+            reader.by_ref().take(constraint_size),
+            n_constraints,
+            field_size,
+            num_variables,
+            options,
+        )?;
 
         let wire2label_offset = sec_offsets.get(&wire2label_type).ok_or_else(|| {
             Error::new(
@@ -194,8 +272,28 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
 
         let wire_mapping = read_map(&mut reader, *wire2label_size, num_variables)?;
 
-        let num_inputs = (1 + n_pub_in + n_pub_out) as usize;
-        let num_aux = num_variables - num_inputs;
+        // both custom gate sections are only present if the circuit uses custom templates
+        let custom_gates = match sec_offsets.get(&custom_gates_list_type) {
+            Some(offset) => {
+                reader.seek(SeekFrom::Start(*offset))?;
+                let size = sec_sizes[&custom_gates_list_type];
+                read_custom_gates::<_, P>(reader.by_ref().take(size), field_size, options)?
+            }
+            None => vec![],
+        };
+        let custom_gate_applications = match sec_offsets.get(&custom_gates_application_type) {
+            Some(offset) => {
+                reader.seek(SeekFrom::Start(*offset))?;
+                let size = sec_sizes[&custom_gates_application_type];
+                read_custom_gate_applications(
+                    reader.by_ref().take(size),
+                    custom_gates.len(),
+                    options,
+                )?
+            }
+            None => vec![],
+        };
+
         Ok(R1CS {
             num_aux,
             num_inputs,
@@ -207,42 +305,385 @@ impl<P: Pairing + CircomArkworksPairingBridge> R1CS<P> {
             n_prv_in,
             n_labels,
             n_constraints,
+            custom_gates,
+            custom_gate_applications,
         })
     }
 }
 
+/// A signal referenced by an [`UnsatisfiedConstraint`], together with its value in the witness.
+#[cfg(feature = "witness")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConstraintSignal<F> {
+    /// Index of the wire in the witness
+    pub wire: usize,
+    /// Label of the wire, see [`R1CS::wire_mapping`]
+    pub label: usize,
+    /// Name of the signal, if provided via [`Unsatisfied::with_signal_names`]
+    pub name: Option<String>,
+    /// Value of the wire in the witness
+    pub value: F,
+}
+
+/// A constraint `<A, w> * <B, w> = <C, w>` that does not hold for a witness `w`.
+#[cfg(feature = "witness")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint<F> {
+    /// Index of the constraint in [`R1CS::constraints`]
+    pub index: usize,
+    /// The evaluation `<A, w>`
+    pub a: F,
+    /// The evaluation `<B, w>`
+    pub b: F,
+    /// The evaluation `<C, w>`
+    pub c: F,
+    /// The signals referenced by the constraint, sorted by wire
+    pub signals: Vec<ConstraintSignal<F>>,
+}
+
+/// Error type returned by [`R1CS::check_witness`] describing why a witness does not satisfy the constraints
+#[cfg(feature = "witness")]
+#[derive(Clone, Debug, PartialEq, Eq, Error)]
+pub enum Unsatisfied<F: PrimeField> {
+    /// The witness does not have a value for every wire
+    #[error("Expected a witness with {expected} values, but got {actual}")]
+    WitnessLength {
+        /// Number of wires of the R1CS
+        expected: usize,
+        /// Number of values of the witness
+        actual: usize,
+    },
+    /// The value of the constant wire 0 is not one
+    #[error("Wire 0 must be one, but is {0}")]
+    InvalidConstant(F),
+    /// Constraints that do not hold for the witness, in ascending order
+    #[error("{}", display_unsatisfied(.0))]
+    Constraints(Vec<UnsatisfiedConstraint<F>>),
+}
+
+#[cfg(feature = "witness")]
+impl<F: PrimeField> Unsatisfied<F> {
+    /// Attaches signal names to the signals of the failing constraints. `names` maps the label of
+    /// a signal to its name, e.g., as given in the `.sym` file produced by Circom.
+    pub fn with_signal_names<S: Into<String>>(
+        mut self,
+        names: impl Fn(usize) -> Option<S>,
+    ) -> Self {
+        if let Self::Constraints(constraints) = &mut self {
+            for signal in constraints.iter_mut().flat_map(|c| c.signals.iter_mut()) {
+                signal.name = names(signal.label).map(Into::into);
+            }
+        }
+        self
+    }
+
+    /// Attaches the signal names of `symbols` to the signals of the failing constraints, see
+    /// [`Unsatisfied::with_signal_names`].
+    #[cfg(feature = "symbols")]
+    pub fn with_symbols(self, symbols: &Symbols) -> Self {
+        self.with_signal_names(|label| symbols.by_label(label).map(|s| s.name.clone()))
+    }
+}
+
+#[cfg(feature = "witness")]
+fn display_unsatisfied<F: PrimeField>(constraints: &[UnsatisfiedConstraint<F>]) -> String {
+    let mut msg = format!("unsatisfied constraints: {}", constraints.len());
+    for UnsatisfiedConstraint {
+        index,
+        a,
+        b,
+        c,
+        signals,
+    } in constraints
+    {
+        msg.push_str(&format!(
+            "\nconstraint {index}: <A, w> * <B, w> != <C, w> with <A, w> = {a}, <B, w> = {b}, <C, w> = {c}"
+        ));
+        for signal in signals {
+            let name = signal.name.as_deref().unwrap_or("unknown");
+            msg.push_str(&format!(
+                "\n  wire {} (label {}, {name}) = {}",
+                signal.wire, signal.label, signal.value
+            ));
+        }
+    }
+    msg
+}
+
+#[cfg(feature = "witness")]
+impl<P: Pairing> R1CS<P> {
+    /// Checks that `witness` satisfies every constraint `<A, w> * <B, w> = <C, w>` of the R1CS.
+    ///
+    /// Returns [`Unsatisfied::Constraints`] with the evaluations and the referenced signals of all
+    /// failing constraints. Use [`Unsatisfied::with_signal_names`] to attach signal names.
+    pub fn check_witness(
+        &self,
+        witness: &Witness<P::ScalarField>,
+    ) -> std::result::Result<(), Unsatisfied<P::ScalarField>> {
+        let w = &witness.values;
+        if w.len() != self.num_variables {
+            return Err(Unsatisfied::WitnessLength {
+                expected: self.num_variables,
+                actual: w.len(),
+            });
+        }
+        if !w[0].is_one() {
+            return Err(Unsatisfied::InvalidConstant(w[0]));
+        }
+        // constraints with out of range wires cannot be satisfied, their wires evaluate to zero
+        let eval = |vec: &ConstraintVec<P>| -> P::ScalarField {
+            vec.iter()
+                .map(|(wire, coeff)| w.get(*wire).map_or(P::ScalarField::zero(), |x| *x * coeff))
+                .sum()
+        };
+        let failing = self
+            .constraints
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (a, b, c))| {
+                let (a_w, b_w, c_w) = (eval(a), eval(b), eval(c));
+                if a_w * b_w == c_w {
+                    return None;
+                }
+                let mut wires = a
+                    .iter()
+                    .chain(b)
+                    .chain(c)
+                    .map(|(wire, _)| *wire)
+                    .collect::<Vec<_>>();
+                wires.sort_unstable();
+                wires.dedup();
+                let signals = wires
+                    .into_iter()
+                    .map(|wire| ConstraintSignal {
+                        wire,
+                        label: self.wire_mapping.get(wire).copied().unwrap_or(wire),
+                        name: None,
+                        value: w.get(wire).copied().unwrap_or_default(),
+                    })
+                    .collect();
+                Some(UnsatisfiedConstraint {
+                    index,
+                    a: a_w,
+                    b: b_w,
+                    c: c_w,
+                    signals,
+                })
+            })
+            .collect::<Vec<_>>();
+        if failing.is_empty() {
+            Ok(())
+        } else {
+            Err(Unsatisfied::Constraints(failing))
+        }
+    }
+}
+
+#[cfg(feature = "symbols")]
+impl<P: Pairing> R1CS<P> {
+    /// Formats the constraint with the given index as `(A) * (B) = (C)`, naming the wires with
+    /// the signal names of `symbols`. Wires without a name are printed as `w_i`.
+    ///
+    /// Returns `None` if there is no constraint with the given index.
+    pub fn display_constraint(&self, index: usize, symbols: &Symbols) -> Option<String> {
+        let (a, b, c) = self.constraints.get(index)?;
+        let lc = |vec: &ConstraintVec<P>| -> String {
+            if vec.is_empty() {
+                return "0".to_owned();
+            }
+            vec.iter()
+                .map(|(wire, coeff)| {
+                    let name = match (wire, symbols.name_of_wire(*wire)) {
+                        (0, _) => "1".to_owned(),
+                        (_, Some(name)) => name.to_owned(),
+                        (_, None) => format!("w_{wire}"),
+                    };
+                    // print coefficients in the upper half of the field as negative numbers
+                    let neg = -*coeff;
+                    if neg.into_bigint() < coeff.into_bigint() {
+                        format!("-{neg} * {name}")
+                    } else {
+                        format!("{coeff} * {name}")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" + ")
+        };
+        Some(format!("({}) * ({}) = ({})", lc(a), lc(b), lc(c)))
+    }
+}
+
+impl<P: Pairing> From<&R1CS<P>> for ConstraintMatrices<P::ScalarField> {
+    /// Converts the constraints into arkworks [`ConstraintMatrices`].
+    ///
+    /// Circom orders the wires like arkworks orders its variables, i.e., the constant one, the public
+    /// outputs and inputs followed by the private wires, so wire `i` becomes column `i`. Like arkworks, the
+    /// matrices do not contain the constraints for the public inputs added by the Groth16 setup.
+    fn from(r1cs: &R1CS<P>) -> Self {
+        let matrix = |select: fn(&Constraints<P>) -> &ConstraintVec<P>| {
+            r1cs.constraints
+                .iter()
+                .map(|constraint| {
+                    select(constraint)
+                        .iter()
+                        .map(|(wire, coeff)| (*coeff, *wire))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let num_non_zero = |m: &Vec<Vec<(P::ScalarField, usize)>>| m.iter().map(Vec::len).sum();
+        let a = matrix(|(a, _, _)| a);
+        let b = matrix(|(_, b, _)| b);
+        let c = matrix(|(_, _, c)| c);
+        ConstraintMatrices {
+            num_instance_variables: r1cs.num_inputs,
+            num_witness_variables: r1cs.num_aux,
+            num_constraints: r1cs.constraints.len(),
+            a_num_non_zero: num_non_zero(&a),
+            b_num_non_zero: num_non_zero(&b),
+            c_num_non_zero: num_non_zero(&c),
+            a,
+            b,
+            c,
+        }
+    }
+}
+
 fn read_constraint_vec<R: Read, P: Pairing>(
-    mut reader: R,
+    reader: &mut Take<R>,
     field_size: usize,
+    num_variables: usize,
 ) -> Result<ConstraintVec<P>> {
     let n_vec = reader.read_u32::<LittleEndian>()? as usize;
+    // every entry is a wire index and a coefficient
+    if n_vec as u64 * (4 + field_size as u64) > reader.limit() {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "Constraint section too short",
+        ))?;
+    }
     let mut vec = Vec::with_capacity(n_vec);
     for _ in 0..n_vec {
+        let wire = reader.read_u32::<LittleEndian>()? as usize;
+        if wire >= num_variables {
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Constraint references wire {wire}, but there are only {num_variables} wires"
+                ),
+            ))?;
+        }
         vec.push((
-            reader.read_u32::<LittleEndian>()? as usize,
-            reader_utils::prime_field_from_reader(&mut reader, field_size)?,
+            wire,
+            reader_utils::prime_field_from_reader(&mut *reader, field_size)?,
         ));
     }
     Ok(vec)
 }
 
 fn read_constraints<R: Read, P: Pairing>(
-    mut reader: R,
+    mut reader: Take<R>,
     n_constraints: usize,
     field_size: usize,
+    num_variables: usize,
+    options: &ParseOptions,
 ) -> Result<Vec<Constraints<P>>> {
-    // todo check section size
+    // every constraint consists of three vectors with at least their length
+    if n_constraints as u64 * 12 > reader.limit() {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            "Constraint section too short",
+        ))?;
+    }
     let mut vec = Vec::with_capacity(n_constraints);
     for _ in 0..n_constraints {
-        vec.push((
-            read_constraint_vec::<_, P>(&mut reader, field_size)?,
-            read_constraint_vec::<_, P>(&mut reader, field_size)?,
-            read_constraint_vec::<_, P>(&mut reader, field_size)?,
-        ));
+        let constraint = (
+            read_constraint_vec::<_, P>(&mut reader, field_size, num_variables)?,
+            read_constraint_vec::<_, P>(&mut reader, field_size, num_variables)?,
+            read_constraint_vec::<_, P>(&mut reader, field_size, num_variables)?,
+        );
+        for vec in [&constraint.0, &constraint.1, &constraint.2] {
+            options.check_element_count("constraint entries", vec.len())?;
+        }
+        vec.push(constraint);
     }
     Ok(vec)
 }
 
+fn read_custom_gates<R: Read, P: Pairing>(
+    mut reader: Take<R>,
+    field_size: usize,
+    options: &ParseOptions,
+) -> Result<Vec<CustomGate<P::ScalarField>>> {
+    let n_gates = reader.read_u32::<LittleEndian>()? as usize;
+    options.check_element_count("custom gates", n_gates)?;
+    let mut gates = Vec::new();
+    for _ in 0..n_gates {
+        // the name is null-terminated
+        let mut name = Vec::new();
+        loop {
+            match reader.read_u8()? {
+                0 => break,
+                byte => name.push(byte),
+            }
+        }
+        let name = String::from_utf8(name).map_err(|_| {
+            Error::new(
+                ErrorKind::InvalidData,
+                "Custom gate name is not valid UTF-8",
+            )
+        })?;
+        let n_parameters = reader.read_u32::<LittleEndian>()? as usize;
+        options.check_element_count("custom gate parameters", n_parameters)?;
+        let parameters = (0..n_parameters)
+            .map(|_| reader_utils::prime_field_from_reader(&mut reader, field_size))
+            .collect::<std::io::Result<_>>()?;
+        gates.push(CustomGate { name, parameters });
+    }
+    check_section_consumed(&reader, "Custom gates list")?;
+    Ok(gates)
+}
+
+fn read_custom_gate_applications<R: Read>(
+    mut reader: Take<R>,
+    n_gates: usize,
+    options: &ParseOptions,
+) -> Result<Vec<CustomGateApplication>> {
+    let n_applications = reader.read_u32::<LittleEndian>()? as usize;
+    options.check_element_count("custom gate applications", n_applications)?;
+    let mut applications = Vec::new();
+    for _ in 0..n_applications {
+        let gate = reader.read_u32::<LittleEndian>()? as usize;
+        if gate >= n_gates {
+            return Err(R1CSParserError::UnknownCustomGate(gate, n_gates));
+        }
+        let n_signals = reader.read_u32::<LittleEndian>()? as usize;
+        options.check_element_count("custom gate signals", n_signals)?;
+        let signals = (0..n_signals)
+            .map(|_| {
+                let signal = reader.read_u64::<LittleEndian>()?;
+                usize::try_from(signal).map_err(|_| {
+                    Error::new(ErrorKind::InvalidData, "Signal does not fit into usize")
+                })
+            })
+            .collect::<std::io::Result<_>>()?;
+        applications.push(CustomGateApplication { gate, signals });
+    }
+    check_section_consumed(&reader, "Custom gates application")?;
+    Ok(applications)
+}
+
+/// Rejects a section with bytes after its content, reading past its end already fails on the [`Take`].
+fn check_section_consumed<R>(reader: &Take<R>, section: &str) -> Result<()> {
+    if reader.limit() != 0 {
+        Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("{section} section has {} trailing bytes", reader.limit()),
+        ))?;
+    }
+    Ok(())
+}
+
 fn read_map<R: Read>(mut reader: R, size: u64, n_wires: usize) -> Result<Vec<u64>> {
     if size != u64::try_from(n_wires).expect("usize fits into u64") * 8 {
         Err(std::io::Error::new(
@@ -254,7 +695,7 @@ fn read_map<R: Read>(mut reader: R, size: u64, n_wires: usize) -> Result<Vec<u64
     for _ in 0..n_wires {
         vec.push(reader.read_u64::<LittleEndian>()?);
     }
-    if vec[0] != 0 {
+    if vec.first() != Some(&0) {
         Err(std::io::Error::new(
             ErrorKind::InvalidData,
             "Wire 0 should always be mapped to 0",
@@ -337,4 +778,220 @@ mod bn254_tests {
         );
         assert_eq!(r1cs.wire_mapping, vec![0, 1, 2, 3]);
     }
+
+    #[test]
+    #[cfg(feature = "witness")]
+    fn test_bn254_check_witness() {
+        let groth16_bn254_kats = groth16_bn254_kats();
+        let r1cs_file = File::open(groth16_bn254_kats.join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<Bn254>::from_reader(r1cs_file).unwrap();
+        let witness_file = File::open(groth16_bn254_kats.join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bn254::Fr>::from_reader(witness_file).unwrap();
+        r1cs.check_witness(&witness).unwrap();
+
+        let mut invalid = witness.clone();
+        invalid.values[3] += ark_bn254::Fr::from(1u64);
+        let err = r1cs.check_witness(&invalid).unwrap_err();
+        let Unsatisfied::Constraints(failing) = &err else {
+            panic!("expected failing constraints, got {err}");
+        };
+        assert_eq!(failing.len(), 1);
+        assert_eq!(failing[0].index, 0);
+        assert_ne!(failing[0].a * failing[0].b, failing[0].c);
+        assert_eq!(
+            failing[0]
+                .signals
+                .iter()
+                .map(|s| s.wire)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(failing[0].signals[2].value, invalid.values[3]);
+
+        let err = err.with_signal_names(|label| Some(format!("main.s{label}")));
+        let Unsatisfied::Constraints(failing) = &err else {
+            unreachable!()
+        };
+        assert_eq!(failing[0].signals[0].name.as_deref(), Some("main.s1"));
+        assert!(err.to_string().contains("constraint 0"));
+
+        #[cfg(feature = "symbols")]
+        {
+            let symbols = crate::Symbols::from_reader(
+                "1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b\n".as_bytes(),
+            )
+            .unwrap();
+            let Unsatisfied::Constraints(failing) = err.with_symbols(&symbols) else {
+                unreachable!()
+            };
+            assert_eq!(failing[0].signals[2].name.as_deref(), Some("main.b"));
+        }
+
+        invalid.values.pop();
+        assert_eq!(
+            r1cs.check_witness(&invalid),
+            Err(Unsatisfied::WitnessLength {
+                expected: 4,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "symbols")]
+    fn test_bn254_display_constraint() {
+        let r1cs_file = File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<Bn254>::from_reader(r1cs_file).unwrap();
+        let symbols =
+            crate::Symbols::from_reader("1,1,0,main.c\n2,2,0,main.a\n".as_bytes()).unwrap();
+        assert_eq!(
+            r1cs.display_constraint(0, &symbols).unwrap(),
+            "(-1 * main.a) * (1 * w_3) = (-1 * main.c)"
+        );
+        assert_eq!(r1cs.display_constraint(1, &symbols), None);
+    }
+
+    #[test]
+    fn test_bn254_malformed() {
+        let bytes = std::fs::read(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        for malformed in crate::tests::malformed(&bytes) {
+            // must not panic
+            let _ = R1CS::<Bn254>::from_reader(std::io::Cursor::new(malformed));
+        }
+
+        // a section exceeding the file
+        let mut too_long = bytes.clone();
+        too_long[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(too_long)),
+            Err(R1CSParserError::IoError(_))
+        ));
+        // more input signals than wires
+        let mut too_many_inputs = bytes.clone();
+        too_many_inputs[204..208].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(too_many_inputs)),
+            Err(R1CSParserError::IoError(_))
+        ));
+        // a constraint referencing a wire that does not exist
+        let r1cs = R1CS::<Bn254>::from_reader(std::io::Cursor::new(&bytes)).unwrap();
+        let mut invalid_wire = bytes.clone();
+        let position = invalid_wire
+            .windows(8)
+            .position(|w| w == [1, 0, 0, 0, 2, 0, 0, 0])
+            .unwrap();
+        invalid_wire[position + 4..position + 8]
+            .copy_from_slice(&(r1cs.num_variables as u32).to_le_bytes());
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(invalid_wire)),
+            Err(R1CSParserError::IoError(_))
+        ));
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader_with_options(
+                std::io::Cursor::new(&bytes),
+                &ParseOptions::default().with_max_elements(3)
+            ),
+            Err(R1CSParserError::LimitExceeded(_))
+        ));
+    }
+
+    fn append_section(r1cs: &mut Vec<u8>, id: u32, content: &[u8]) {
+        r1cs.extend(id.to_le_bytes());
+        r1cs.extend((content.len() as u64).to_le_bytes());
+        r1cs.extend(content);
+        let num_sections = u32::from_le_bytes(r1cs[8..12].try_into().unwrap());
+        r1cs[8..12].copy_from_slice(&(num_sections + 1).to_le_bytes());
+    }
+
+    #[test]
+    fn test_bn254_custom_gates() {
+        let mut r1cs_bytes = std::fs::read(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<Bn254>::from_reader(std::io::Cursor::new(&r1cs_bytes)).unwrap();
+        assert!(r1cs.custom_gates.is_empty());
+        assert!(r1cs.custom_gate_applications.is_empty());
+
+        let mut gates = 2u32.to_le_bytes().to_vec();
+        gates.extend(b"CMul\0");
+        gates.extend(0u32.to_le_bytes());
+        gates.extend(b"Poseidon\0");
+        gates.extend(1u32.to_le_bytes());
+        let mut parameter = [0u8; 32];
+        parameter[0] = 7;
+        gates.extend(parameter);
+        let without_gates = r1cs_bytes.clone();
+        append_section(&mut r1cs_bytes, 4, &gates);
+        let mut applications = 1u32.to_le_bytes().to_vec();
+        applications.extend(1u32.to_le_bytes());
+        applications.extend(2u32.to_le_bytes());
+        applications.extend(3u64.to_le_bytes());
+        applications.extend(1u64.to_le_bytes());
+        let mut valid = r1cs_bytes.clone();
+        append_section(&mut valid, 5, &applications);
+
+        let r1cs = R1CS::<Bn254>::from_reader(std::io::Cursor::new(&valid)).unwrap();
+        assert_eq!(
+            r1cs.custom_gates,
+            vec![
+                CustomGate {
+                    name: "CMul".to_owned(),
+                    parameters: vec![]
+                },
+                CustomGate {
+                    name: "Poseidon".to_owned(),
+                    parameters: vec![ark_bn254::Fr::from(7u64)]
+                }
+            ]
+        );
+        assert_eq!(
+            r1cs.custom_gate_applications,
+            vec![CustomGateApplication {
+                gate: 1,
+                signals: vec![3, 1]
+            }]
+        );
+
+        // a custom gates section missing the last parameter must not read into the next section
+        let mut truncated = without_gates.clone();
+        append_section(&mut truncated, 4, &gates[..gates.len() - 32]);
+        append_section(&mut truncated, 5, &applications);
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(&truncated)),
+            Err(R1CSParserError::IoError(_))
+        ));
+        let mut trailing = r1cs_bytes.clone();
+        append_section(&mut trailing, 5, &[applications.as_slice(), &[0]].concat());
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(&trailing)),
+            Err(R1CSParserError::IoError(_))
+        ));
+
+        applications[4..8].copy_from_slice(&2u32.to_le_bytes());
+        append_section(&mut r1cs_bytes, 5, &applications);
+        assert!(matches!(
+            R1CS::<Bn254>::from_reader(std::io::Cursor::new(&r1cs_bytes)),
+            Err(R1CSParserError::UnknownCustomGate(2, 2))
+        ));
+    }
+
+    #[test]
+    fn test_bn254_constraint_matrices() {
+        let r1cs_file = File::open(groth16_bn254_kats().join("circuit.r1cs")).unwrap();
+        let r1cs = R1CS::<Bn254>::from_reader(r1cs_file).unwrap();
+        let matrices = ConstraintMatrices::from(&r1cs);
+        let minus_one = -ark_bn254::Fr::from(1u64);
+        assert_eq!(matrices.num_instance_variables, 2);
+        assert_eq!(matrices.num_witness_variables, 2);
+        assert_eq!(matrices.num_constraints, 1);
+        assert_eq!(matrices.a, vec![vec![(minus_one, 2)]]);
+        assert_eq!(matrices.b, vec![vec![(ark_bn254::Fr::from(1u64), 3)]]);
+        assert_eq!(matrices.c, vec![vec![(minus_one, 1)]]);
+        assert_eq!(
+            (
+                matrices.a_num_non_zero,
+                matrices.b_num_non_zero,
+                matrices.c_num_non_zero
+            ),
+            (1, 1, 1)
+        );
+    }
 }
@@ -0,0 +1,184 @@
+//! This module defines the [`Symbols`] struct that implements parsing of the `.sym` files produced by `circom --sym` via [`Symbols::from_reader`].
+//!
+//! Every line of a `.sym` file describes one signal as `label,wire,component,name`, where `wire` is
+//! `-1` if the signal was removed by the optimizer of Circom.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read};
+
+use thiserror::Error;
+
+type Result<T> = std::result::Result<T, SymbolsParserError>;
+
+/// Error type describing errors during parsing `.sym` files
+#[derive(Debug, Error)]
+pub enum SymbolsParserError {
+    /// Error during IO operations (reading/opening file, etc.)
+    #[error(transparent)]
+    IoError(#[from] io::Error),
+    /// Error describing that a line is not of the form `label,wire,component,name`
+    #[error("Invalid symbol in line {0}: {1}")]
+    InvalidLine(usize, String),
+    /// Error describing that a signal name occurs more than once
+    #[error("Duplicate signal name {0}")]
+    DuplicateName(String),
+}
+
+/// A signal of a Circom circuit, as described by a line of a `.sym` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    /// Label of the signal, see [`R1CS::wire_mapping`](crate::R1CS::wire_mapping)
+    pub label: usize,
+    /// Index of the wire of the signal in the R1CS and the witness, or `None` if the signal was
+    /// removed during optimization
+    pub wire: Option<usize>,
+    /// Index of the component the signal belongs to
+    pub component: usize,
+    /// Fully qualified name of the signal, e.g., `main.merkle.path[3]`
+    pub name: String,
+}
+
+/// Represents the symbols of a Circom circuit, mapping signal names to wires and labels.
+/// Implements [`Symbols::from_reader`] to parse the `.sym` file produced by `circom --sym`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Symbols {
+    /// The symbols in the order of the file
+    pub symbols: Vec<Symbol>,
+    by_name: HashMap<String, usize>,
+    by_label: HashMap<usize, usize>,
+    by_wire: HashMap<usize, Vec<usize>>,
+}
+
+impl Symbols {
+    /// Parses a `.sym` file from a reader and returns [`SymbolsParserError`] on failure
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        let mut symbols = Self::default();
+        for (line_number, line) in BufReader::new(reader).lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || SymbolsParserError::InvalidLine(line_number + 1, line.clone());
+            let mut fields = line.splitn(4, ',');
+            let (Some(label), Some(wire), Some(component), Some(name)) =
+                (fields.next(), fields.next(), fields.next(), fields.next())
+            else {
+                return Err(invalid());
+            };
+            let wire = match wire.parse::<i64>().map_err(|_| invalid())? {
+                -1 => None,
+                wire => Some(usize::try_from(wire).map_err(|_| invalid())?),
+            };
+            symbols.push(Symbol {
+                label: label.parse().map_err(|_| invalid())?,
+                wire,
+                component: component.parse().map_err(|_| invalid())?,
+                name: name.to_owned(),
+            })?;
+        }
+        Ok(symbols)
+    }
+
+    fn push(&mut self, symbol: Symbol) -> Result<()> {
+        let index = self.symbols.len();
+        if self.by_name.insert(symbol.name.clone(), index).is_some() {
+            return Err(SymbolsParserError::DuplicateName(symbol.name));
+        }
+        self.by_label.insert(symbol.label, index);
+        if let Some(wire) = symbol.wire {
+            self.by_wire.entry(wire).or_default().push(index);
+        }
+        self.symbols.push(symbol);
+        Ok(())
+    }
+
+    /// Returns the number of symbols.
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns `true` if there are no symbols.
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Returns the symbol of the signal with the given fully qualified name.
+    pub fn get(&self, name: &str) -> Option<&Symbol> {
+        self.by_name.get(name).map(|i| &self.symbols[*i])
+    }
+
+    /// Returns the wire of the signal with the given fully qualified name, or `None` if there is no
+    /// such signal or it was removed during optimization.
+    pub fn wire(&self, name: &str) -> Option<usize> {
+        self.get(name).and_then(|symbol| symbol.wire)
+    }
+
+    /// Returns the symbol with the given label.
+    pub fn by_label(&self, label: usize) -> Option<&Symbol> {
+        self.by_label.get(&label).map(|i| &self.symbols[*i])
+    }
+
+    /// Returns all symbols of the given wire. Circom merges signals that are constrained to be
+    /// equal, so a wire may have several names.
+    pub fn by_wire(&self, wire: usize) -> impl Iterator<Item = &Symbol> {
+        self.by_wire
+            .get(&wire)
+            .into_iter()
+            .flatten()
+            .map(|i| &self.symbols[*i])
+    }
+
+    /// Returns the first name of the given wire, see [`Symbols::by_wire`].
+    pub fn name_of_wire(&self, wire: usize) -> Option<&str> {
+        self.by_wire(wire).next().map(|symbol| symbol.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYM: &str =
+        "1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b\n4,3,1,main.sub.in\n5,-1,1,main.sub.tmp\n";
+
+    #[test]
+    fn can_parse_symbols() {
+        let symbols = Symbols::from_reader(SYM.as_bytes()).unwrap();
+        assert_eq!(symbols.len(), 5);
+        assert_eq!(
+            symbols.get("main.a"),
+            Some(&Symbol {
+                label: 2,
+                wire: Some(2),
+                component: 0,
+                name: "main.a".to_owned(),
+            })
+        );
+        assert_eq!(symbols.wire("main.c"), Some(1));
+        assert_eq!(symbols.wire("main.sub.tmp"), None);
+        assert_eq!(symbols.wire("main.d"), None);
+        assert_eq!(symbols.by_label(5).unwrap().name, "main.sub.tmp");
+        assert_eq!(
+            symbols.by_wire(3).map(|s| s.label).collect::<Vec<_>>(),
+            vec![3, 4]
+        );
+        assert_eq!(symbols.name_of_wire(3), Some("main.b"));
+        assert_eq!(symbols.name_of_wire(0), None);
+    }
+
+    #[test]
+    fn rejects_invalid_symbols() {
+        assert!(matches!(
+            Symbols::from_reader("1,1,main.c\n".as_bytes()),
+            Err(SymbolsParserError::InvalidLine(1, _))
+        ));
+        assert!(matches!(
+            Symbols::from_reader("1,1,0,main.c\n2,-2,0,main.a\n".as_bytes()),
+            Err(SymbolsParserError::InvalidLine(2, _))
+        ));
+        assert!(matches!(
+            Symbols::from_reader("1,1,0,main.c\n2,2,0,main.c\n".as_bytes()),
+            Err(SymbolsParserError::DuplicateName(_))
+        ));
+    }
+}
@@ -1,6 +1,6 @@
 //! Traits for converting Circom file formats to arkworks types.
 
-use std::io::Read;
+use std::io::{Read, Write};
 
 use ark_ec::pairing::Pairing;
 use ark_serde_compat::{CanonicalJsonSerialize, CheckElement};
@@ -13,17 +13,46 @@ macro_rules! impl_serde_for_curve {
     ($mod_name: ident, $config: ident, $curve: ident, $name: expr, $field_size: expr, $scalar_field_size: expr, $circom_name: expr) => {
         mod $mod_name {
 
-            use std::io::Read;
+            use std::io::{Read, Write};
 
-            use ark_ec::AffineRepr;
-            use ark_ff::{PrimeField, Zero};
+            use ark_ec::{AffineRepr, short_weierstrass::SWCurveConfig};
+            use ark_ff::{Field, PrimeField, Zero};
             use ark_serde_compat::CheckElement;
 
-            use ark_serialize::{CanonicalDeserialize, SerializationError};
-            use $curve::{Fq2, $config};
+            use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+            use $curve::{Fq, Fq2, g2, $config};
+
+            use crate::chacha::ChaCha;
 
             use super::*;
 
+            // ffjavascript samples the internal (montgomery) representation of the element
+            fn fq_from_rng(rng: &mut ChaCha) -> Fq {
+                let mut bigint = <Fq as PrimeField>::BigInt::zero();
+                loop {
+                    for limb in bigint.0.iter_mut() {
+                        *limb = rng.next_u64();
+                    }
+                    let excess_bits = 64 * bigint.0.len() - Fq::MODULUS_BIT_SIZE as usize;
+                    *bigint.0.last_mut().expect("at least one limb") &= u64::MAX >> excess_bits;
+                    if bigint < Fq::MODULUS {
+                        return Fq::new_unchecked(bigint);
+                    }
+                }
+            }
+
+            fn fq_is_negative(f: &Fq) -> bool {
+                f.into_bigint() > Fq::MODULUS_MINUS_ONE_DIV_TWO
+            }
+
+            fn fq2_is_negative(f: &Fq2) -> bool {
+                if f.c1.is_zero() {
+                    fq_is_negative(&f.c0)
+                } else {
+                    fq_is_negative(&f.c1)
+                }
+            }
+
             impl CircomArkworksPairingBridge for $config {
                 const G1_SERIALIZED_BYTE_SIZE_COMPRESSED: usize = $field_size;
                 const G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED: usize = $field_size * 2;
@@ -121,6 +150,10 @@ macro_rules! impl_serde_for_curve {
                         <Self::ScalarField as PrimeField>::BigInt::deserialize_uncompressed(
                             buf.as_slice(),
                         )?;
+                    // arithmetic on unreduced elements panics
+                    if bigint >= Self::ScalarField::MODULUS {
+                        return Err(SerializationError::InvalidData);
+                    }
                     Ok(Self::ScalarField::new_unchecked(bigint))
                 }
 
@@ -130,6 +163,9 @@ macro_rules! impl_serde_for_curve {
                     let bigint = <Self::BaseField as PrimeField>::BigInt::deserialize_uncompressed(
                         buf.as_slice(),
                     )?;
+                    if bigint >= Self::BaseField::MODULUS {
+                        return Err(SerializationError::InvalidData);
+                    }
                     Ok(Self::BaseField::new_unchecked(bigint))
                 }
 
@@ -140,6 +176,82 @@ macro_rules! impl_serde_for_curve {
                         Self::fr_from_montgomery_reader(reader)?.into_bigint(),
                     ))
                 }
+
+                //Circom expects its field elements in montgomery form
+                //therefore we write the internal representation as is
+                fn g1_to_writer(p: &Self::G1Affine, mut writer: impl Write) -> SerResult<()> {
+                    match p.xy() {
+                        Some((x, y)) => {
+                            Self::fq_to_montgomery_writer(&x, &mut writer)?;
+                            Self::fq_to_montgomery_writer(&y, &mut writer)?;
+                        }
+                        None => {
+                            writer.write_all(&[0u8; Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED])?
+                        }
+                    }
+                    Ok(())
+                }
+
+                fn g2_to_writer(p: &Self::G2Affine, mut writer: impl Write) -> SerResult<()> {
+                    match p.xy() {
+                        Some((x, y)) => {
+                            Self::fq_to_montgomery_writer(&x.c0, &mut writer)?;
+                            Self::fq_to_montgomery_writer(&x.c1, &mut writer)?;
+                            Self::fq_to_montgomery_writer(&y.c0, &mut writer)?;
+                            Self::fq_to_montgomery_writer(&y.c1, &mut writer)?;
+                        }
+                        None => {
+                            writer.write_all(&[0u8; Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED])?
+                        }
+                    }
+                    Ok(())
+                }
+
+                fn fr_to_montgomery_writer(
+                    f: &Self::ScalarField,
+                    writer: impl Write,
+                ) -> SerResult<()> {
+                    f.0.serialize_uncompressed(writer)
+                }
+
+                fn fq_to_montgomery_writer(
+                    f: &Self::BaseField,
+                    writer: impl Write,
+                ) -> SerResult<()> {
+                    f.0.serialize_uncompressed(writer)
+                }
+
+                fn hash_to_g2(hash: &[u8]) -> Self::G2Affine {
+                    let mut rng = ChaCha::from_hash(hash);
+                    loop {
+                        let c0 = fq_from_rng(&mut rng);
+                        let c1 = fq_from_rng(&mut rng);
+                        let x = Fq2::new(c0, c1);
+                        let greatest = rng.next_bool();
+                        let x3b = x.square() * x + g2::Config::COEFF_B;
+                        let Some(mut y) = x3b.sqrt() else {
+                            continue;
+                        };
+                        if x.is_zero() {
+                            continue;
+                        }
+                        if greatest ^ fq2_is_negative(&y) {
+                            y = -y;
+                        }
+                        return Self::G2Affine::new_unchecked(x, y).mul_by_cofactor();
+                    }
+                }
+
+                fn fr_to_writer_for_groth16_zkey(
+                    f: &Self::ScalarField,
+                    writer: impl Write,
+                ) -> SerResult<()> {
+                    // the inverse of fr_from_reader_for_groth16_zkey: lift the montgomery
+                    // representation into the field once more, so that reading performs two reductions
+                    let lifted = Self::ScalarField::from_bigint(f.0)
+                        .ok_or(SerializationError::InvalidData)?;
+                    Self::fr_to_montgomery_writer(&lifted, writer)
+                }
             }
         }
     };
@@ -184,43 +296,66 @@ pub trait CircomArkworksPairingBridge: Pairing + CanonicalJsonSerialize {
     ) -> SerResult<Vec<Self::G1Affine>> {
         let mut buf = vec![0u8; Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED * num];
         reader.read_exact(&mut buf)?;
+        Self::g1_vec_from_bytes(&buf, check)
+    }
+    /// Deserializes vec of G2 from reader where the elements are already in montgomery form (no montgomery reduction performed)
+    /// The default implementation runs multithreaded using rayon
+    fn g2_vec_from_reader(
+        mut reader: impl Read,
+        num: usize,
+        check: CheckElement,
+    ) -> SerResult<Vec<Self::G2Affine>> {
+        let mut buf = vec![0u8; Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED * num];
+        reader.read_exact(&mut buf)?;
+        Self::g2_vec_from_bytes(&buf, check)
+    }
+    /// Deserializes vec of G1 from bytes where the elements are already in montgomery form (no montgomery reduction performed).
+    /// The length of `bytes` must be a multiple of the size of an element.
+    /// The default implementation runs multithreaded using rayon
+    fn g1_vec_from_bytes(bytes: &[u8], check: CheckElement) -> SerResult<Vec<Self::G1Affine>> {
+        if !bytes
+            .len()
+            .is_multiple_of(Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
+        {
+            return Err(SerializationError::InvalidData);
+        }
         #[cfg(feature = "parallel")]
         use rayon::prelude::*;
 
         #[cfg(feature = "parallel")]
-        let ret_val = buf
+        let ret_val = bytes
             .par_chunks_exact(Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
             .map(|chunk| Self::g1_from_bytes(chunk, check))
             .collect::<Result<Vec<_>, SerializationError>>();
 
         #[cfg(not(feature = "parallel"))]
-        let ret_val = buf
+        let ret_val = bytes
             .chunks_exact(Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
             .map(|chunk| Self::g1_from_bytes(chunk, check))
             .collect::<Result<Vec<_>, SerializationError>>();
         ret_val
     }
-    /// Deserializes vec of G2 from reader where the elements are already in montgomery form (no montgomery reduction performed)
+    /// Deserializes vec of G2 from bytes where the elements are already in montgomery form (no montgomery reduction performed).
+    /// The length of `bytes` must be a multiple of the size of an element.
     /// The default implementation runs multithreaded using rayon
-    fn g2_vec_from_reader(
-        mut reader: impl Read,
-        num: usize,
-        check: CheckElement,
-    ) -> SerResult<Vec<Self::G2Affine>> {
-        let mut buf = vec![0u8; Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED * num];
-        reader.read_exact(&mut buf)?;
-
+    fn g2_vec_from_bytes(bytes: &[u8], check: CheckElement) -> SerResult<Vec<Self::G2Affine>> {
+        if !bytes
+            .len()
+            .is_multiple_of(Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
+        {
+            return Err(SerializationError::InvalidData);
+        }
         #[cfg(feature = "parallel")]
         use rayon::prelude::*;
 
         #[cfg(feature = "parallel")]
-        let ret_val = buf
+        let ret_val = bytes
             .par_chunks_exact(Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
             .map(|chunk| Self::g2_from_bytes(chunk, check))
             .collect::<Result<Vec<_>, SerializationError>>();
 
         #[cfg(not(feature = "parallel"))]
-        let ret_val = buf
+        let ret_val = bytes
             .chunks_exact(Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED)
             .map(|chunk| Self::g2_from_bytes(chunk, check))
             .collect::<Result<Vec<_>, SerializationError>>();
@@ -235,6 +370,42 @@ pub trait CircomArkworksPairingBridge: Pairing + CanonicalJsonSerialize {
 
     /// Deserializes an element of [`Pairing::BaseField`] where the element is already in montgomery form (no montgomery reduction performed).
     fn fq_from_montgomery_reader(reader: impl Read) -> SerResult<Self::BaseField>;
+
+    /// Serializes element of G1 into writer in montgomery form. The point at infinity is written as all zeroes. Inverse of [`Self::g1_from_reader`]
+    fn g1_to_writer(p: &Self::G1Affine, writer: impl Write) -> SerResult<()>;
+    /// Serializes element of G2 into writer in montgomery form. The point at infinity is written as all zeroes. Inverse of [`Self::g2_from_reader`]
+    fn g2_to_writer(p: &Self::G2Affine, writer: impl Write) -> SerResult<()>;
+    /// Serializes vec of G1 into writer in montgomery form. Inverse of [`Self::g1_vec_from_reader`]
+    fn g1_vec_to_writer(points: &[Self::G1Affine], mut writer: impl Write) -> SerResult<()> {
+        let mut buf = Vec::with_capacity(Self::G1_SERIALIZED_BYTE_SIZE_UNCOMPRESSED * points.len());
+        for p in points {
+            Self::g1_to_writer(p, &mut buf)?;
+        }
+        writer.write_all(&buf)?;
+        Ok(())
+    }
+    /// Serializes vec of G2 into writer in montgomery form. Inverse of [`Self::g2_vec_from_reader`]
+    fn g2_vec_to_writer(points: &[Self::G2Affine], mut writer: impl Write) -> SerResult<()> {
+        let mut buf = Vec::with_capacity(Self::G2_SERIALIZED_BYTE_SIZE_UNCOMPRESSED * points.len());
+        for p in points {
+            Self::g2_to_writer(p, &mut buf)?;
+        }
+        writer.write_all(&buf)?;
+        Ok(())
+    }
+
+    /// Serializes an element of [`Pairing::ScalarField`] in montgomery form. Inverse of [`Self::fr_from_montgomery_reader`].
+    fn fr_to_montgomery_writer(f: &Self::ScalarField, writer: impl Write) -> SerResult<()>;
+
+    /// Serializes an element of [`Pairing::ScalarField`] in the encoding of the coefficient section of Circom's Zkey. Inverse of [`Self::fr_from_reader_for_groth16_zkey`].
+    fn fr_to_writer_for_groth16_zkey(f: &Self::ScalarField, writer: impl Write) -> SerResult<()>;
+
+    /// Serializes an element of [`Pairing::BaseField`] in montgomery form. Inverse of [`Self::fq_from_montgomery_reader`].
+    fn fq_to_montgomery_writer(f: &Self::BaseField, writer: impl Write) -> SerResult<()>;
+
+    /// Deterministically maps a hash to an element of G2, in the same way as snarkjs derives the
+    /// G2 part of the public key of a contribution (`hashToG2`).
+    fn hash_to_g2(hash: &[u8]) -> Self::G2Affine;
 }
 
 #[cfg(feature = "bn254")]
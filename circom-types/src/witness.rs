@@ -1,14 +1,20 @@
 //! This module defines the [`Witness`] struct that implements deserialization of Circom witness files via [`Witness::from_reader`].
 
-use std::io;
+use std::io::{self, Write};
 
 use ark_serialize::{Read, SerializationError};
-use byteorder::{LittleEndian, ReadBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use ark_ff::{BigInteger, PrimeField};
 
-use crate::reader_utils::{self, InvalidHeaderError};
+#[cfg(feature = "symbols")]
+use crate::Symbols;
+use crate::{
+    LimitExceeded, ParseOptions,
+    reader_utils::{self, InvalidHeaderError},
+};
 
 type Result<T> = std::result::Result<T, WitnessParserError>;
 const WITNESS_HEADER: &str = "wtns";
@@ -36,18 +42,39 @@ pub enum WitnessParserError {
     /// Error during reading Circom file header
     #[error(transparent)]
     WrongHeader(#[from] InvalidHeaderError),
+    /// Error describing that the size of a section does not match its content
+    #[error("Section {0} has {1} bytes, expected {2}")]
+    InvalidSectionSize(u32, u64, u64),
+    /// Error describing that the file exceeds the limits configured in [`ParseOptions`]
+    #[error(transparent)]
+    LimitExceeded(#[from] LimitExceeded),
 }
 
-/// Represents a witness in the format defined by Circom. Implements [`Witness::from_reader`] to deserialize a witness from a reader.
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Represents a witness in the format defined by Circom. Implements [`Witness::from_reader`] to deserialize a witness from a reader
+/// and [`Witness::write_to`] to serialize it.
+///
+/// Implements [`serde::Deserialize`] and [`serde::Serialize`] for the JSON format of `snarkjs wtns export json`, i.e., an array of
+/// decimal strings.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(transparent, bound = "F: PrimeField")]
 pub struct Witness<F> {
     /// The values of the witness.
+    #[serde(serialize_with = "ark_serde_compat::serialize_f_seq")]
+    #[serde(deserialize_with = "ark_serde_compat::deserialize_f_seq")]
     pub values: Vec<F>,
 }
 
 impl<F: PrimeField> Witness<F> {
     /// Deserializes a [`Witness`] from a reader.
-    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self> {
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self> {
+        Self::from_reader_with_options(reader, &ParseOptions::default())
+    }
+
+    /// Deserializes a [`Witness`] from a reader and rejects files exceeding the limits of the given [`ParseOptions`].
+    pub fn from_reader_with_options<R: Read>(
+        mut reader: R,
+        options: &ParseOptions,
+    ) -> Result<Self> {
         tracing::trace!("trying to read witness");
         reader_utils::read_header(&mut reader, WITNESS_HEADER)?;
         let version = reader.read_u32::<LittleEndian>()?;
@@ -65,22 +92,37 @@ impl<F: PrimeField> Witness<F> {
             ));
         }
         //this is the section id and length
-        //don't know if we need them, maybe at least log them later
-        let _ = reader.read_u32::<LittleEndian>()?;
-        let _ = reader.read_u64::<LittleEndian>()?;
+        let header_id = reader.read_u32::<LittleEndian>()?;
+        let header_size = reader.read_u64::<LittleEndian>()?;
+        options.check_section_size(header_id, header_size)?;
         let n8 = reader.read_u32::<LittleEndian>()?;
         let n8 = usize::try_from(n8).expect("u32 fits into usize");
+        let modulus = F::MODULUS.to_bytes_le();
+        // compare the size first to not allocate an arbitrary amount of memory
+        if n8 != modulus.len() {
+            tracing::trace!("wrong scalar field");
+            return Err(WitnessParserError::WrongScalarField);
+        }
         let mut buf = vec![0; n8];
         reader.read_exact(buf.as_mut_slice())?;
-        if F::MODULUS.to_bytes_le() != buf {
+        if modulus != buf {
             tracing::trace!("wrong scalar field");
             return Err(WitnessParserError::WrongScalarField);
         }
         let n_witness = reader.read_u32::<LittleEndian>()?;
+        options.check_element_count("witness values", n_witness as usize)?;
         //this is the section id and length
-        //don't know if we need them, maybe at least log them later
-        let _ = reader.read_u32::<LittleEndian>()?;
-        let _ = reader.read_u64::<LittleEndian>()?;
+        let values_id = reader.read_u32::<LittleEndian>()?;
+        let values_size = reader.read_u64::<LittleEndian>()?;
+        options.check_section_size(values_id, values_size)?;
+        let expected_size = u64::from(n_witness) * n8 as u64;
+        if values_size != expected_size {
+            return Err(WitnessParserError::InvalidSectionSize(
+                values_id,
+                values_size,
+                expected_size,
+            ));
+        }
         Ok(Self {
             values: (0..n_witness)
                 .map(|_| {
@@ -90,6 +132,68 @@ impl<F: PrimeField> Witness<F> {
                 .collect::<Result<Vec<F>>>()?,
         })
     }
+
+    /// Serializes the [`Witness`] into a writer in the version 2 format produced by Circom.
+    ///
+    /// Writing a [`Witness`] obtained from [`Witness::from_reader`] reproduces the original file.
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        tracing::trace!("writing witness with {} values", self.values.len());
+        let modulus = F::MODULUS.to_bytes_le();
+        let n8 = modulus.len();
+        let n_witness = u32::try_from(self.values.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Too many witness values"))?;
+        writer.write_all(WITNESS_HEADER.as_bytes())?;
+        writer.write_u32::<LittleEndian>(MAX_VERSION)?;
+        writer.write_u32::<LittleEndian>(N_SECTIONS)?;
+
+        writer.write_u32::<LittleEndian>(1)?;
+        writer.write_u64::<LittleEndian>((4 + n8 + 4) as u64)?;
+        writer.write_u32::<LittleEndian>(n8 as u32)?;
+        writer.write_all(&modulus)?;
+        writer.write_u32::<LittleEndian>(n_witness)?;
+
+        writer.write_u32::<LittleEndian>(2)?;
+        writer.write_u64::<LittleEndian>((n8 * self.values.len()) as u64)?;
+        for value in &self.values {
+            let mut bytes = value.into_bigint().to_bytes_le();
+            bytes.resize(n8, 0);
+            writer.write_all(&bytes)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns the public signals of the witness, i.e., the `n_public` values following the constant one,
+    /// in the order of the `public.json` produced by snarkjs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the witness has less than `n_public + 1` values.
+    #[cfg(all(feature = "groth16", feature = "public-input"))]
+    pub fn public_inputs(&self, n_public: usize) -> crate::groth16::PublicInput<F> {
+        crate::groth16::PublicInput(self.values[1..=n_public].to_vec())
+    }
+}
+
+#[cfg(feature = "symbols")]
+impl<F> Witness<F> {
+    /// Returns the value of the signal with the given fully qualified name, or `None` if the
+    /// signal does not exist or was removed during optimization.
+    pub fn get_by_name(&self, symbols: &Symbols, name: &str) -> Option<&F> {
+        symbols.wire(name).and_then(|wire| self.values.get(wire))
+    }
+
+    /// Returns the names and values of all signals that are part of the witness, in the order of
+    /// the `.sym` file.
+    pub fn named_values<'a>(
+        &'a self,
+        symbols: &'a Symbols,
+    ) -> impl Iterator<Item = (&'a str, &'a F)> {
+        symbols.symbols.iter().filter_map(|symbol| {
+            let value = self.values.get(symbol.wire?)?;
+            Some((symbol.name.as_str(), value))
+        })
+    }
 }
 
 #[cfg(test)]
@@ -97,9 +201,9 @@ impl<F: PrimeField> Witness<F> {
 mod bn254_tests {
     use std::fs::File;
 
-    use crate::tests::groth16_bn254_kats;
+    use crate::{ParseOptions, tests::groth16_bn254_kats};
 
-    use super::Witness;
+    use super::{Witness, WitnessParserError};
 
     #[test]
     fn can_deser_witness_bn254() {
@@ -118,6 +222,91 @@ mod bn254_tests {
             }
         );
     }
+
+    #[test]
+    fn rejects_malformed_witness_bn254() {
+        let bytes = std::fs::read(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        for malformed in crate::tests::malformed(&bytes) {
+            // must not panic
+            let _ = Witness::<ark_bn254::Fr>::from_reader(malformed.as_slice());
+        }
+
+        // a field size that would allocate 4GiB
+        let mut huge = bytes.clone();
+        huge[24..28].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            Witness::<ark_bn254::Fr>::from_reader(huge.as_slice()),
+            Err(WitnessParserError::WrongScalarField)
+        ));
+        // more values than the section contains
+        let mut too_many = bytes.clone();
+        too_many[60..64].copy_from_slice(&5u32.to_le_bytes());
+        assert!(matches!(
+            Witness::<ark_bn254::Fr>::from_reader(too_many.as_slice()),
+            Err(WitnessParserError::InvalidSectionSize(2, 128, 160))
+        ));
+        assert!(matches!(
+            Witness::<ark_bn254::Fr>::from_reader_with_options(
+                bytes.as_slice(),
+                &ParseOptions::default().with_max_elements(3)
+            ),
+            Err(WitnessParserError::LimitExceeded(_))
+        ));
+    }
+
+    #[test]
+    fn can_write_witness_bn254() {
+        let should = std::fs::read(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bn254::Fr>::from_reader(should.as_slice()).unwrap();
+        let mut is = Vec::new();
+        witness.write_to(&mut is).unwrap();
+        assert_eq!(is, should);
+    }
+
+    #[test]
+    fn can_serde_witness_bn254() {
+        let witness = File::open(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bn254::Fr>::from_reader(witness).unwrap();
+        let json = serde_json::to_string(&witness).unwrap();
+        assert_eq!(json, r#"["1","33","3","11"]"#);
+        assert_eq!(
+            serde_json::from_str::<Witness<ark_bn254::Fr>>(&json).unwrap(),
+            witness
+        );
+    }
+
+    #[test]
+    #[cfg(all(feature = "groth16", feature = "public-input"))]
+    fn can_extract_public_inputs() {
+        let witness = File::open(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bn254::Fr>::from_reader(witness).unwrap();
+        assert_eq!(
+            witness.public_inputs(1).into_inner(),
+            vec![ark_bn254::Fr::from(33)]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "symbols")]
+    fn can_look_up_witness_by_name() {
+        let witness = File::open(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bn254::Fr>::from_reader(witness).unwrap();
+        let symbols =
+            crate::Symbols::from_reader("1,1,0,main.c\n2,2,0,main.a\n3,-1,0,main.tmp\n".as_bytes())
+                .unwrap();
+        assert_eq!(
+            witness.get_by_name(&symbols, "main.a"),
+            Some(&ark_bn254::Fr::from(3))
+        );
+        assert_eq!(witness.get_by_name(&symbols, "main.tmp"), None);
+        assert_eq!(
+            witness.named_values(&symbols).collect::<Vec<_>>(),
+            vec![
+                ("main.c", &ark_bn254::Fr::from(33)),
+                ("main.a", &ark_bn254::Fr::from(3))
+            ]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +318,15 @@ mod bls12_381_tests {
 
     use super::Witness;
 
+    #[test]
+    fn can_write_witness_bls12381() {
+        let should = std::fs::read(groth16_bls12_381_kats().join("witness.wtns")).unwrap();
+        let witness = Witness::<ark_bls12_381::Fr>::from_reader(should.as_slice()).unwrap();
+        let mut is = Vec::new();
+        witness.write_to(&mut is).unwrap();
+        assert_eq!(is, should);
+    }
+
     #[test]
     fn can_deser_witness_bls12381() {
         let groth16_bls12_381_kats = groth16_bls12_381_kats();
@@ -0,0 +1,281 @@
+//! This module defines [`WitnessGraph`], which computes [`Witness`]es natively from the execution graphs of
+//! [circom-witness-rs](https://github.com/philsippl/circom-witness-rs), replacing the WASM witness calculator of Circom.
+//!
+//! The graph of a circuit is created once with the `build-witness` feature of circom-witness-rs. Witness generation is
+//! only supported on BN254, as circom-witness-rs evaluates the graph over its scalar field.
+
+use std::{collections::HashMap, io::Read};
+
+use ark_bn254::Fr;
+use ark_ff::{BigInt, PrimeField};
+use circom_witness_rs::{BlackBoxFunction, Graph, graph};
+use ruint::aliases::U256;
+use thiserror::Error;
+
+use crate::{CircuitInputs, Symbols, Witness};
+
+/// Error type describing errors during witness generation
+#[derive(Debug, Error)]
+pub enum WitnessGenError {
+    /// Error during IO operations (reading/opening file, etc.)
+    #[error(transparent)]
+    IoError(#[from] std::io::Error),
+    /// The graph file cannot be deserialized
+    #[error("Invalid witness graph: {0}")]
+    InvalidGraph(String),
+    /// An input signal is not an input of the circuit
+    #[error("Unknown input signal {0}")]
+    UnknownInput(String),
+    /// An input signal has the wrong number of elements
+    #[error("{name} has {actual} elements, expected {expected}")]
+    InputLength {
+        /// Name of the input signal
+        name: String,
+        /// Number of elements of the signal in the circuit
+        expected: usize,
+        /// Number of provided elements
+        actual: usize,
+    },
+    /// An input signal of the circuit was not provided. Named by the hash of its name, unless the
+    /// graph knows the names of its inputs (see [`WitnessGraph::with_symbols`])
+    #[error("Missing input signal {0}")]
+    MissingInput(String),
+    /// The evaluation of the graph failed, e.g., because of a missing black box function
+    #[error("Witness generation failed: {0}")]
+    Evaluation(String),
+}
+
+/// The execution graph of a Circom circuit, which computes the [`Witness`] for [`CircuitInputs`].
+#[derive(Clone)]
+pub struct WitnessGraph {
+    graph: Graph,
+    black_box_functions: HashMap<String, BlackBoxFunction>,
+    input_names: HashMap<u64, String>,
+}
+
+impl std::fmt::Debug for WitnessGraph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WitnessGraph")
+            .field("nodes", &self.graph.nodes.len())
+            .field("signals", &self.graph.signals.len())
+            .field("inputs", &self.graph.input_mapping.len())
+            .field(
+                "black_box_functions",
+                &self.black_box_functions.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+// The hash circom-witness-rs uses to identify input signals by name
+fn fnv1a(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+impl WitnessGraph {
+    /// Deserializes a [`WitnessGraph`] from the bytes of a graph file produced by circom-witness-rs.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, WitnessGenError> {
+        let graph = circom_witness_rs::init_graph(bytes)
+            .map_err(|err| WitnessGenError::InvalidGraph(err.to_string()))?;
+        Ok(Self::from(graph))
+    }
+
+    /// Deserializes a [`WitnessGraph`] from a reader of a graph file produced by circom-witness-rs.
+    pub fn from_reader<R: Read>(mut reader: R) -> Result<Self, WitnessGenError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Registers a black box function, which evaluates the Circom function `name` (prefixed with `bbf`) natively.
+    pub fn with_black_box_function(mut self, name: impl Into<String>, f: BlackBoxFunction) -> Self {
+        self.black_box_functions.insert(name.into(), f);
+        self
+    }
+
+    /// Names the input signals after the symbols of the circuit produced by `circom --sym`.
+    ///
+    /// The graph only identifies its inputs by the hash of their name, the names are used to report
+    /// missing inputs.
+    pub fn with_symbols(mut self, symbols: &Symbols) -> Self {
+        for symbol in &symbols.symbols {
+            // the inputs are signals of the main component, e.g., `main.in[2]`
+            let Some(name) = symbol.name.strip_prefix("main.") else {
+                continue;
+            };
+            let name = name.split('[').next().unwrap_or_default();
+            let hash = fnv1a(name);
+            if !name.contains('.') && self.graph.input_mapping.iter().any(|i| i.hash == hash) {
+                self.input_names.insert(hash, name.to_owned());
+            }
+        }
+        self
+    }
+
+    /// Computes the [`Witness`] for the given inputs.
+    ///
+    /// Returns an error if an input signal is unknown, has the wrong number of elements or is
+    /// missing, before evaluating the graph.
+    pub fn calculate_witness(
+        &self,
+        inputs: &CircuitInputs<Fr>,
+    ) -> Result<Witness<Fr>, WitnessGenError> {
+        let mut buffer =
+            circom_witness_rs::get_inputs_buffer(circom_witness_rs::get_inputs_size(&self.graph));
+        let mut provided = vec![false; self.graph.input_mapping.len()];
+        for (name, values) in inputs.flatten() {
+            let hash = fnv1a(name);
+            let index = self
+                .graph
+                .input_mapping
+                .iter()
+                .position(|info| info.hash == hash)
+                .ok_or_else(|| WitnessGenError::UnknownInput(name.to_owned()))?;
+            let info = &self.graph.input_mapping[index];
+            let expected = info.signalsize as usize;
+            if values.len() != expected {
+                return Err(WitnessGenError::InputLength {
+                    name: name.to_owned(),
+                    expected,
+                    actual: values.len(),
+                });
+            }
+            let start = info.signalid as usize;
+            let slots = start
+                .checked_add(expected)
+                .and_then(|end| buffer.get_mut(start..end))
+                .ok_or_else(|| {
+                    WitnessGenError::InvalidGraph(format!("input {name} is out of bounds"))
+                })?;
+            for (slot, value) in slots.iter_mut().zip(values) {
+                *slot = U256::from_limbs(value.into_bigint().0);
+            }
+            provided[index] = true;
+        }
+        if let Some(index) = provided.iter().position(|provided| !provided) {
+            let hash = self.graph.input_mapping[index].hash;
+            let name = match self.input_names.get(&hash) {
+                Some(name) => name.clone(),
+                None => format!("with hash {hash:#018x}"),
+            };
+            return Err(WitnessGenError::MissingInput(name));
+        }
+
+        let values = graph::evaluate(
+            &self.graph.nodes,
+            &buffer,
+            &self.graph.signals,
+            Some(&self.black_box_functions),
+        )
+        .map_err(|err| WitnessGenError::Evaluation(err.to_string()))?;
+        let values = values
+            .into_iter()
+            .map(|value| {
+                Fr::from_bigint(BigInt(value.into_limbs())).ok_or_else(|| {
+                    WitnessGenError::Evaluation("value is not in the scalar field".to_owned())
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Witness { values })
+    }
+}
+
+impl From<Graph> for WitnessGraph {
+    fn from(graph: Graph) -> Self {
+        Self {
+            graph,
+            black_box_functions: HashMap::new(),
+            input_names: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use circom_witness_rs::{
+        HashSignalInfo,
+        graph::{Node, Operation},
+    };
+
+    use super::*;
+    use crate::tests::groth16_bn254_kats;
+
+    // the graph of the kat circuit, which computes c = a * b
+    fn mult2() -> WitnessGraph {
+        let input = |name: &str, signalid| HashSignalInfo {
+            hash: fnv1a(name),
+            signalid,
+            signalsize: 1,
+        };
+        WitnessGraph::from(Graph {
+            nodes: vec![
+                Node::Input(0),
+                Node::Input(1),
+                Node::Input(2),
+                Node::Op(Operation::Mul, 1, 2),
+            ],
+            signals: vec![0, 3, 1, 2],
+            input_mapping: vec![input("a", 1), input("b", 2)],
+        })
+    }
+
+    #[test]
+    fn can_calculate_witness() {
+        let inputs = CircuitInputs::from_reader(r#"{"a": "3", "b": 11}"#.as_bytes()).unwrap();
+        let witness = mult2().calculate_witness(&inputs).unwrap();
+        let should = File::open(groth16_bn254_kats().join("witness.wtns")).unwrap();
+        assert_eq!(witness, Witness::from_reader(should).unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_inputs() {
+        let graph = mult2();
+        let calculate = |json: &str| {
+            graph.calculate_witness(&CircuitInputs::from_reader(json.as_bytes()).unwrap())
+        };
+        assert!(matches!(
+            calculate(r#"{"a": 3, "b": 11, "c": 1}"#),
+            Err(WitnessGenError::UnknownInput(name)) if name == "c"
+        ));
+        assert!(matches!(
+            calculate(r#"{"a": [3, 4], "b": 11}"#),
+            Err(WitnessGenError::InputLength {
+                expected: 1,
+                actual: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            calculate(r#"{"a": 3}"#),
+            Err(WitnessGenError::MissingInput(name)) if name == format!("with hash {:#018x}", fnv1a("b"))
+        ));
+        let symbols =
+            Symbols::from_reader("1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b\n".as_bytes()).unwrap();
+        let named = mult2().with_symbols(&symbols);
+        let err = named
+            .calculate_witness(&CircuitInputs::from_reader(r#"{"b": 11}"#.as_bytes()).unwrap())
+            .unwrap_err();
+        assert!(matches!(&err, WitnessGenError::MissingInput(name) if name == "a"));
+        assert_eq!(err.to_string(), "Missing input signal a");
+        assert!(matches!(
+            WitnessGraph::from_bytes(&[0xff; 3]),
+            Err(WitnessGenError::InvalidGraph(_))
+        ));
+
+        // inputs outside of the input buffer, up to the end of the address space
+        for signalid in [3, u64::MAX] {
+            let mut graph = mult2();
+            graph.graph.input_mapping[1].signalid = signalid;
+            assert!(matches!(
+                graph.calculate_witness(
+                    &CircuitInputs::from_reader(r#"{"a": 3, "b": 11}"#.as_bytes()).unwrap()
+                ),
+                Err(WitnessGenError::InvalidGraph(_))
+            ));
+        }
+    }
+}
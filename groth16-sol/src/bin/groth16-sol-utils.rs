@@ -1,15 +1,18 @@
 use std::fmt;
-use std::io::BufReader;
+use std::io::{BufReader, Write};
 use std::str::FromStr;
 use std::{fs::File, path::PathBuf, process::ExitCode};
 
-use ark_bn254::Bn254;
+use ark_bn254::{Bn254, Fr};
 use ark_ff::Zero;
-use circom_types::groth16::{Proof, PublicInput, VerificationKey};
+use ark_groth16::VerifyingKey;
+use ark_serialize::Compress;
+use circom_types::CheckElement;
+use circom_types::groth16::{Proof, PublicInput, VerificationKey, Zkey};
 use clap::{Args, Parser, Subcommand};
 use eyre::Context;
 use taceo_groth16_sol::askama::Template;
-use taceo_groth16_sol::{SolidityVerifierConfig, SolidityVerifierContext};
+use taceo_groth16_sol::{Finding, Severity, SolidityVerifierConfig, SolidityVerifierContext};
 
 #[derive(Copy, Clone, Debug, Default)]
 enum Format {
@@ -17,6 +20,7 @@ enum Format {
     Circom,
     Bellman,
     Gnark,
+    Zokrates,
 }
 
 impl fmt::Display for Format {
@@ -25,6 +29,7 @@ impl fmt::Display for Format {
             Self::Circom => write!(f, "circom"),
             Self::Bellman => write!(f, "bellman"),
             Self::Gnark => write!(f, "gnark"),
+            Self::Zokrates => write!(f, "zokrates"),
         }
     }
 }
@@ -37,7 +42,49 @@ impl FromStr for Format {
             "circom" => Ok(Self::Circom),
             "bellman" => Ok(Self::Bellman),
             "gnark" => Ok(Self::Gnark),
-            _ => eyre::bail!("Format must be either circom, bellman or gnark"),
+            "zokrates" => Ok(Self::Zokrates),
+            _ => eyre::bail!("Format must be either circom, bellman, gnark or zokrates"),
+        }
+    }
+}
+
+/// The format of a verification key, which may also be derived from a Circom zkey.
+#[derive(Copy, Clone, Debug)]
+enum VkFormat {
+    Key(Format),
+    Zkey,
+}
+
+impl Default for VkFormat {
+    fn default() -> Self {
+        Self::Key(Format::default())
+    }
+}
+
+impl From<Format> for VkFormat {
+    fn from(format: Format) -> Self {
+        Self::Key(format)
+    }
+}
+
+impl fmt::Display for VkFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key(format) => format.fmt(f),
+            Self::Zkey => write!(f, "zkey"),
+        }
+    }
+}
+
+impl FromStr for VkFormat {
+    type Err = eyre::Report;
+
+    fn from_str(s: &str) -> eyre::Result<Self> {
+        match s {
+            "zkey" => Ok(Self::Zkey),
+            _ => s.parse().map(Self::Key).map_err(|_| {
+                eyre::eyre!("Vk format must be either circom, bellman, gnark, zokrates or zkey")
+            }),
         }
     }
 }
@@ -54,6 +101,10 @@ struct Config {
 enum SubCommand {
     GenerateCall(GenerateCallConfig),
     ExtractVerifier(ExtractVerifierConfig),
+    Convert(ConvertConfig),
+    Unwrap(UnwrapConfig),
+    Verify(VerifyConfig),
+    LintVk(LintVkConfig),
 }
 
 #[derive(Debug, Default, Args)]
@@ -61,9 +112,9 @@ struct GenerateCallConfig {
     /// Path to Circom proof.
     #[clap(long)]
     pub proof: PathBuf,
-    /// Path to Circom public inputs.
+    /// Path to Circom public inputs. Optional for ZoKrates proofs, which contain their inputs.
     #[clap(long)]
-    pub public: PathBuf,
+    pub public: Option<PathBuf>,
     /// Location of the output file. Write to stdout if omitted.
     #[clap(short, long)]
     pub output: Option<PathBuf>,
@@ -72,9 +123,9 @@ struct GenerateCallConfig {
     pub format: Format,
 }
 
-#[derive(Debug, Default, Args)]
+#[derive(Debug, Args)]
 struct ExtractVerifierConfig {
-    /// Path to Circom verification key.
+    /// Path to the verification key, or the Circom zkey with `--format zkey`.
     #[clap(short, long)]
     pub vk: PathBuf,
     /// Output of the Solidity file. Write to stdout if omitted.
@@ -84,19 +135,176 @@ struct ExtractVerifierConfig {
     #[clap(long, default_value = "^0.8.0")]
     pub pragma_version: String,
     /// Vk format.
+    #[clap(short, long, default_value_t = VkFormat::default())]
+    pub format: VkFormat,
+    /// Render the verifier even if the verification key has critical findings (see `lint-vk`).
+    #[clap(long)]
+    pub allow_insecure: bool,
+}
+
+/// Checks a verification key for conditions that break the soundness of the verifier, e.g.,
+/// keys of development setups. Exits with failure if a critical issue is found.
+#[derive(Debug, Args)]
+struct LintVkConfig {
+    /// Path to the verification key, or the Circom zkey with `--format zkey`.
+    #[clap(short, long)]
+    pub vk: PathBuf,
+    /// Vk format.
+    #[clap(short, long, default_value_t = VkFormat::default())]
+    pub format: VkFormat,
+}
+
+/// Converts a verification key or a proof between the supported formats.
+#[derive(Debug, Args)]
+struct ConvertConfig {
+    /// Path to the verification key to convert.
+    #[clap(long, conflicts_with_all = ["proof", "zkey"], required_unless_present_any = ["proof", "zkey"])]
+    pub vk: Option<PathBuf>,
+    /// Path to a Circom zkey to convert its verification key.
+    #[clap(long, conflicts_with = "proof")]
+    pub zkey: Option<PathBuf>,
+    /// Path to the proof to convert.
+    #[clap(long)]
+    pub proof: Option<PathBuf>,
+    /// Format of the input.
+    #[clap(long, required_unless_present = "zkey")]
+    pub from: Option<Format>,
+    /// Format of the output.
+    #[clap(long, default_value_t = Format::Circom)]
+    pub to: Format,
+    /// Location of the output file. Write to stdout if omitted.
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
+    /// Write compressed points (gnark only).
+    #[clap(long)]
+    pub compressed: bool,
+    /// Path to Circom public inputs of the proof, written into ZoKrates proofs.
+    #[clap(long, requires = "proof")]
+    pub public: Option<PathBuf>,
+}
+
+/// Verifies a proof with the same checks as the generated Solidity verifier and reports which
+/// stage failed.
+#[derive(Debug, Args)]
+struct VerifyConfig {
+    /// Path to the verification key, or the Circom zkey with `--vk-format zkey`.
+    #[clap(long)]
+    pub vk: PathBuf,
+    /// Path to the proof.
+    #[clap(long)]
+    pub proof: PathBuf,
+    /// Path to Circom public inputs. Optional for ZoKrates proofs, which contain their inputs.
+    #[clap(long)]
+    pub public: Option<PathBuf>,
+    /// Proof format.
     #[clap(short, long, default_value_t = Format::Circom)]
     pub format: Format,
+    /// Vk format. Defaults to the proof format.
+    #[clap(long)]
+    pub vk_format: Option<VkFormat>,
 }
 
-fn generate_call(config: GenerateCallConfig) -> eyre::Result<ExitCode> {
-    let GenerateCallConfig {
-        proof,
-        public,
-        output,
-        format,
-    } = config;
+#[derive(Copy, Clone, Debug, clap::ValueEnum)]
+enum Zkvm {
+    Sp1,
+    Risc0,
+}
+
+/// Unwraps the Groth16 proof of a zkVM into a Circom proof and its public inputs, which can be
+/// passed to `generate-call`.
+#[derive(Debug, Args)]
+struct UnwrapConfig {
+    /// The zkVM that produced the proof.
+    #[clap(long, value_enum)]
+    pub zkvm: Zkvm,
+    /// Path to the proof bytes (SP1) or seal (RISC Zero), either binary or hex encoded.
+    #[clap(long)]
+    pub proof: PathBuf,
+    /// The expected selector of the verifier as hex.
+    #[clap(long)]
+    pub selector: String,
+    /// The verifying key of the SP1 program as hex.
+    #[clap(long, required_if_eq("zkvm", "sp1"))]
+    pub program_vkey: Option<String>,
+    /// Path to the public values committed by the SP1 program.
+    #[clap(long, required_if_eq("zkvm", "sp1"))]
+    pub public_values: Option<PathBuf>,
+    /// The control root of the RISC Zero verifier as hex.
+    #[clap(long, required_if_eq("zkvm", "risc0"))]
+    pub control_root: Option<String>,
+    /// The BN254 control id of the RISC Zero verifier as hex.
+    #[clap(long, required_if_eq("zkvm", "risc0"))]
+    pub bn254_control_id: Option<String>,
+    /// The digest of the RISC Zero receipt claim as hex.
+    #[clap(long, conflicts_with_all = ["image_id", "journal"])]
+    pub claim_digest: Option<String>,
+    /// The image id of the RISC Zero program as hex, used with `--journal` to compute the claim digest.
+    #[clap(long, requires = "journal")]
+    pub image_id: Option<String>,
+    /// Path to the journal of the RISC Zero program.
+    #[clap(long, requires = "image_id")]
+    pub journal: Option<PathBuf>,
+    /// Location of the Circom proof.
+    #[clap(long)]
+    pub proof_out: PathBuf,
+    /// Location of the Circom public inputs.
+    #[clap(long)]
+    pub public_out: PathBuf,
+}
+
+fn read_vk(path: PathBuf, format: VkFormat) -> eyre::Result<VerifyingKey<Bn254>> {
+    Ok(read_vk_with_n_public(path, format)?.0)
+}
+
+/// Reads a verification key, together with its number of public inputs if the format contains it.
+fn read_vk_with_n_public(
+    path: PathBuf,
+    format: VkFormat,
+) -> eyre::Result<(VerifyingKey<Bn254>, Option<usize>)> {
+    let vk_file = BufReader::new(File::open(path).context("while opening input file")?);
+    let format = match format {
+        VkFormat::Key(format) => format,
+        VkFormat::Zkey => {
+            let zkey = Zkey::<Bn254>::from_reader(vk_file, CheckElement::Yes)
+                .context("while parsing circom zkey")?;
+            return Ok((VerificationKey::from(&zkey).into(), Some(zkey.n_public)));
+        }
+    };
+    let vk = match format {
+        Format::Circom => {
+            let vk = VerificationKey::<Bn254>::from_reader(vk_file)
+                .context("while parsing circom verification-key")?;
+            return Ok((vk.clone().into(), Some(vk.n_public)));
+        }
+        Format::Bellman => taceo_groth16_sol::read_bellman_vk(vk_file)
+            .context("while parsing bellman verification-key")?,
+        Format::Gnark => taceo_groth16_sol::read_gnark_vk(vk_file)
+            .context("while parsing gnark verification-key")?,
+        Format::Zokrates => taceo_groth16_sol::read_zokrates_vk(vk_file)
+            .context("while parsing zokrates verification-key")?,
+    };
+    Ok((vk, None))
+}
+
+/// Reads a verification key and checks it with [`taceo_groth16_sol::lint_verifying_key`].
+fn lint_vk_file(
+    path: PathBuf,
+    format: VkFormat,
+) -> eyre::Result<(VerifyingKey<Bn254>, Vec<Finding>)> {
+    let (vk, n_public) = read_vk_with_n_public(path, format)?;
+    let mut findings = taceo_groth16_sol::lint_verifying_key(&vk);
+    findings.extend(
+        n_public.and_then(|n_public| taceo_groth16_sol::lint_public_input_count(&vk, n_public)),
+    );
+    Ok((vk, findings))
+}
 
-    let proof_file = BufReader::new(File::open(proof).context("while opening input file")?);
+/// Reads a proof, together with its public inputs if the format contains them.
+fn read_proof(
+    path: PathBuf,
+    format: Format,
+) -> eyre::Result<(ark_groth16::Proof<Bn254>, Option<Vec<Fr>>)> {
+    let proof_file = BufReader::new(File::open(path).context("while opening input file")?);
     let proof = match format {
         Format::Circom => {
             let proof: Proof<Bn254> = serde_json::from_reader(proof_file)
@@ -107,12 +315,39 @@ fn generate_call(config: GenerateCallConfig) -> eyre::Result<ExitCode> {
             .context("while parsing bellman groth16 proof")?,
         Format::Gnark => taceo_groth16_sol::read_gnark_proof(proof_file)
             .context("while parsing gnark groth16 proof")?,
+        Format::Zokrates => {
+            let (proof, inputs) = taceo_groth16_sol::read_zokrates_proof(proof_file)
+                .context("while parsing zokrates groth16 proof")?;
+            return Ok((proof, Some(inputs)));
+        }
     };
+    Ok((proof, None))
+}
 
-    let public_input: PublicInput<ark_bn254::Fr> = serde_json::from_reader(File::open(public)?)?;
+fn read_public_input(path: PathBuf) -> eyre::Result<Vec<Fr>> {
+    let public_input: PublicInput<Fr> =
+        serde_json::from_reader(File::open(path).context("while opening public input file")?)
+            .context("while parsing public input")?;
+    Ok(public_input.0)
+}
+
+fn generate_call(config: GenerateCallConfig) -> eyre::Result<ExitCode> {
+    let GenerateCallConfig {
+        proof,
+        public,
+        output,
+        format,
+    } = config;
+
+    let (proof, inputs) = read_proof(proof, format)?;
+
+    let public_input = match (public, inputs) {
+        (Some(public), _) => read_public_input(public)?,
+        (None, Some(inputs)) => inputs,
+        (None, None) => eyre::bail!("--public is required for {format} proofs"),
+    };
 
     let pub_ins = public_input
-        .0
         .into_iter()
         .map(|x| {
             if x.is_zero() {
@@ -144,18 +379,22 @@ fn extract_verifier(config: ExtractVerifierConfig) -> eyre::Result<ExitCode> {
         output,
         pragma_version,
         format,
+        allow_insecure,
     } = config;
 
-    let vk_file = BufReader::new(File::open(vk).context("while opening input file")?);
-    let vk = match format {
-        Format::Circom => VerificationKey::<Bn254>::from_reader(vk_file)
-            .context("while parsing circom verification-key")?
-            .into(),
-        Format::Bellman => taceo_groth16_sol::read_bellman_vk(vk_file)
-            .context("while parsing bellman verification-key")?,
-        Format::Gnark => taceo_groth16_sol::read_gnark_vk(vk_file)
-            .context("while parsing gnark verification-key")?,
-    };
+    let (vk, findings) = lint_vk_file(vk, format)?;
+    let critical = findings
+        .iter()
+        .filter(|finding| finding.severity() == Severity::Critical)
+        .collect::<Vec<_>>();
+    for finding in &critical {
+        eprintln!("{}: {finding}", finding.severity());
+    }
+    if !critical.is_empty() && !allow_insecure {
+        eyre::bail!(
+            "refusing to render a verifier for an insecure verification key, pass --allow-insecure to render it anyway"
+        );
+    }
 
     let contract = SolidityVerifierContext {
         little_endian: false,
@@ -171,10 +410,197 @@ fn extract_verifier(config: ExtractVerifierConfig) -> eyre::Result<ExitCode> {
     Ok(ExitCode::SUCCESS)
 }
 
+fn convert(config: ConvertConfig) -> eyre::Result<ExitCode> {
+    let ConvertConfig {
+        vk,
+        zkey,
+        proof,
+        from,
+        to,
+        output,
+        compressed,
+        public,
+    } = config;
+    let compress = if compressed {
+        Compress::Yes
+    } else {
+        Compress::No
+    };
+
+    let mut converted = Vec::new();
+    let vk = match (vk, zkey) {
+        (Some(vk), _) => Some((vk, VkFormat::from(from.unwrap_or_default()))),
+        (None, Some(zkey)) => Some((zkey, VkFormat::Zkey)),
+        (None, None) => None,
+    };
+    match (vk, proof) {
+        (Some((vk, format)), _) => {
+            let vk = read_vk(vk, format)?;
+            match to {
+                Format::Circom => {
+                    serde_json::to_writer_pretty(&mut converted, &VerificationKey::from_ark(vk))?
+                }
+                Format::Bellman => taceo_groth16_sol::write_bellman_vk(&vk, &mut converted)?,
+                Format::Gnark => taceo_groth16_sol::write_gnark_vk(&vk, &mut converted, compress)?,
+                Format::Zokrates => taceo_groth16_sol::write_zokrates_vk(&vk, &mut converted)?,
+            }
+        }
+        (_, Some(proof)) => {
+            let (proof, inputs) = read_proof(proof, from.expect("required by clap"))?;
+            match to {
+                Format::Circom => {
+                    serde_json::to_writer_pretty(&mut converted, &Proof::from(proof))?
+                }
+                Format::Bellman => taceo_groth16_sol::write_bellman_proof(&proof, &mut converted)?,
+                Format::Gnark => {
+                    taceo_groth16_sol::write_gnark_proof(&proof, &mut converted, compress)?
+                }
+                Format::Zokrates => {
+                    let inputs = match (public, inputs) {
+                        (Some(public), _) => read_public_input(public)?,
+                        (None, Some(inputs)) => inputs,
+                        (None, None) => eyre::bail!("--public is required for zokrates proofs"),
+                    };
+                    taceo_groth16_sol::write_zokrates_proof(&proof, &inputs, &mut converted)?
+                }
+            }
+        }
+        (_, None) => eyre::bail!("either --vk, --zkey or --proof is required"),
+    }
+    if let Some(output) = output {
+        std::fs::write(output, converted).context("while writing output")?;
+    } else {
+        std::io::stdout()
+            .write_all(&converted)
+            .context("while writing output")?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn parse_hex<const N: usize>(hex: &str) -> eyre::Result<[u8; N]> {
+    let bytes = alloy_primitives::hex::decode(hex.trim()).context("while decoding hex")?;
+    bytes
+        .try_into()
+        .map_err(|bytes: Vec<u8>| eyre::eyre!("expected {N} bytes, but got {}", bytes.len()))
+}
+
+fn unwrap(config: UnwrapConfig) -> eyre::Result<ExitCode> {
+    let UnwrapConfig {
+        zkvm,
+        proof,
+        selector,
+        program_vkey,
+        public_values,
+        control_root,
+        bn254_control_id,
+        claim_digest,
+        image_id,
+        journal,
+        proof_out,
+        public_out,
+    } = config;
+
+    let proof = std::fs::read(proof).context("while reading proof")?;
+    // accept hex encoded proofs as printed by the zkVM SDKs
+    let proof = match std::str::from_utf8(&proof) {
+        Ok(hex) => alloy_primitives::hex::decode(hex.trim()).unwrap_or(proof),
+        Err(_) => proof,
+    };
+    let selector = parse_hex(&selector)?;
+    let wrapped = match zkvm {
+        Zkvm::Sp1 => {
+            let program_vkey = parse_hex(&program_vkey.expect("required by clap"))?;
+            let public_values = std::fs::read(public_values.expect("required by clap"))
+                .context("while reading public values")?;
+            taceo_groth16_sol::read_sp1_proof(&proof, selector, program_vkey, &public_values)?
+        }
+        Zkvm::Risc0 => {
+            let control_root = parse_hex(&control_root.expect("required by clap"))?;
+            let bn254_control_id = parse_hex(&bn254_control_id.expect("required by clap"))?;
+            let claim_digest = match (claim_digest, image_id, journal) {
+                (Some(claim_digest), _, _) => parse_hex(&claim_digest)?,
+                (None, Some(image_id), Some(journal)) => {
+                    let journal = std::fs::read(journal).context("while reading journal")?;
+                    taceo_groth16_sol::risc0_claim_digest(parse_hex(&image_id)?, &journal)
+                }
+                _ => eyre::bail!("either --claim-digest or --image-id and --journal is required"),
+            };
+            taceo_groth16_sol::read_risc0_seal(
+                &proof,
+                selector,
+                control_root,
+                bn254_control_id,
+                claim_digest,
+            )?
+        }
+    };
+
+    let proof_file = File::create(proof_out).context("while creating proof file")?;
+    serde_json::to_writer_pretty(proof_file, &Proof::from(wrapped.proof))?;
+    let public_file = File::create(public_out).context("while creating public input file")?;
+    serde_json::to_writer_pretty(public_file, &PublicInput(wrapped.public_inputs))?;
+    Ok(ExitCode::SUCCESS)
+}
+
+fn verify(config: VerifyConfig) -> eyre::Result<ExitCode> {
+    let VerifyConfig {
+        vk,
+        proof,
+        public,
+        format,
+        vk_format,
+    } = config;
+
+    let vk = read_vk(vk, vk_format.unwrap_or(format.into()))?;
+    let (proof, inputs) = read_proof(proof, format)?;
+    let public_input = match (public, inputs) {
+        (Some(public), _) => read_public_input(public)?,
+        (None, Some(inputs)) => inputs,
+        (None, None) => eyre::bail!("--public is required for {format} proofs"),
+    };
+
+    let public_input = public_input.into_iter().map(Into::into).collect::<Vec<_>>();
+    let proof = taceo_groth16_sol::prepare_uncompressed_proof(&proof);
+    match taceo_groth16_sol::verify_prepared(&vk, &public_input, &proof) {
+        Ok(()) => {
+            println!("proof is valid");
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            println!("proof is invalid: {err}");
+            Ok(ExitCode::FAILURE)
+        }
+    }
+}
+
+fn lint_vk(config: LintVkConfig) -> eyre::Result<ExitCode> {
+    let LintVkConfig { vk, format } = config;
+
+    let (_, findings) = lint_vk_file(vk, format)?;
+    if findings.is_empty() {
+        println!("no issues found");
+    }
+    for finding in &findings {
+        println!("{}: {finding}", finding.severity());
+    }
+    if findings
+        .iter()
+        .any(|finding| finding.severity() == Severity::Critical)
+    {
+        Ok(ExitCode::FAILURE)
+    } else {
+        Ok(ExitCode::SUCCESS)
+    }
+}
+
 fn main() -> eyre::Result<ExitCode> {
     let config = Config::parse();
     match config.subcommand {
         SubCommand::GenerateCall(config) => generate_call(config),
         SubCommand::ExtractVerifier(config) => extract_verifier(config),
+        SubCommand::Convert(config) => convert(config),
+        SubCommand::Unwrap(config) => unwrap(config),
+        SubCommand::Verify(config) => verify(config),
+        SubCommand::LintVk(config) => lint_vk(config),
     }
 }
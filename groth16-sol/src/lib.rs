@@ -42,12 +42,12 @@
 //! ```
 #![deny(missing_docs)]
 
-use std::io::{self, Read};
+use std::io::{self, Read, Write};
 
 use alloy_primitives::U256;
-use ark_ec::AffineRepr;
+use ark_ec::{AffineRepr, pairing::Pairing};
 use ark_groth16::Proof;
-use ark_serialize::CanonicalDeserialize;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 
 /// Re-export askama for users of this crate
 #[cfg(feature = "template")]
@@ -198,18 +198,16 @@ pub fn prepare_uncompressed_proof(proof: &Proof<ark_bn254::Bn254>) -> [U256; 8]
 }
 
 /// Read a [verifying key](ark_groth16::VerifyingKey) in Bellman format.
-pub fn read_bellman_vk<R: Read>(
+pub fn read_bellman_vk<P: Pairing, R: Read>(
     mut reader: R,
-) -> Result<ark_groth16::VerifyingKey<ark_bn254::Bn254>, ark_serialize::SerializationError> {
-    let read_g1 =
-        |reader: &mut R| -> Result<ark_bn254::G1Affine, ark_serialize::SerializationError> {
-            <ark_bn254::G1Affine as CanonicalDeserialize>::deserialize_uncompressed(reader)
-        };
+) -> Result<ark_groth16::VerifyingKey<P>, ark_serialize::SerializationError> {
+    let read_g1 = |reader: &mut R| -> Result<P::G1Affine, ark_serialize::SerializationError> {
+        <P::G1Affine as CanonicalDeserialize>::deserialize_uncompressed(reader)
+    };
 
-    let read_g2 =
-        |reader: &mut R| -> Result<ark_bn254::G2Affine, ark_serialize::SerializationError> {
-            <ark_bn254::G2Affine as CanonicalDeserialize>::deserialize_uncompressed(reader)
-        };
+    let read_g2 = |reader: &mut R| -> Result<P::G2Affine, ark_serialize::SerializationError> {
+        <P::G2Affine as CanonicalDeserialize>::deserialize_uncompressed(reader)
+    };
 
     let alpha_g1 = read_g1(&mut reader)?;
     let _beta_g1 = read_g1(&mut reader)?;
@@ -230,7 +228,7 @@ pub fn read_bellman_vk<R: Read>(
 
     for _ in 0..ic_len {
         let g1 = read_g1(&mut reader).and_then(|e| {
-            if e == ark_bn254::G1Affine::identity() {
+            if e.is_zero() {
                 Err(ark_serialize::SerializationError::IoError(io::Error::new(
                     io::ErrorKind::InvalidData,
                     "point at infinity",
@@ -253,82 +251,211 @@ pub fn read_bellman_vk<R: Read>(
 }
 
 /// Read a [Groth16 proof](ark_groth16::Proof) in Bellman format.
-pub fn read_bellman_proof<R: Read>(
+pub fn read_bellman_proof<P: Pairing, R: Read>(
     mut reader: R,
-) -> Result<ark_groth16::Proof<ark_bn254::Bn254>, ark_serialize::SerializationError> {
-    let read_g1 =
-        |reader: &mut R| -> Result<ark_bn254::G1Affine, ark_serialize::SerializationError> {
-            let point =
-                <ark_bn254::G1Affine as CanonicalDeserialize>::deserialize_compressed(reader)?;
-            if point == ark_bn254::G1Affine::identity() {
-                return Err(ark_serialize::SerializationError::IoError(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "point at infinity",
-                )));
-            }
-            Ok(point)
-        };
-
-    let read_g2 =
-        |reader: &mut R| -> Result<ark_bn254::G2Affine, ark_serialize::SerializationError> {
-            let point =
-                <ark_bn254::G2Affine as CanonicalDeserialize>::deserialize_compressed(reader)?;
-            if point == ark_bn254::G2Affine::identity() {
-                return Err(ark_serialize::SerializationError::IoError(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "point at infinity",
-                )));
-            }
-            Ok(point)
-        };
+) -> Result<ark_groth16::Proof<P>, ark_serialize::SerializationError> {
+    fn read_point<A: AffineRepr, R: Read>(
+        reader: &mut R,
+    ) -> Result<A, ark_serialize::SerializationError> {
+        let point = A::deserialize_compressed(reader)?;
+        if point.is_zero() {
+            return Err(ark_serialize::SerializationError::IoError(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "point at infinity",
+            )));
+        }
+        Ok(point)
+    }
 
-    let a = read_g1(&mut reader)?;
-    let b = read_g2(&mut reader)?;
-    let c = read_g1(&mut reader)?;
+    let a = read_point(&mut reader)?;
+    let b = read_point(&mut reader)?;
+    let c = read_point(&mut reader)?;
 
     Ok(ark_groth16::Proof { a, b, c })
 }
 
-pub use gnark::{read_gnark_proof, read_gnark_vk};
+/// Write a [verifying key](ark_groth16::VerifyingKey) in Bellman format, the inverse of [`read_bellman_vk`].
+///
+/// As arkworks keys do not contain β and δ in G1, which are not needed for verification, they
+/// are written as the point at infinity.
+pub fn write_bellman_vk<P: Pairing, W: Write>(
+    vk: &ark_groth16::VerifyingKey<P>,
+    mut writer: W,
+) -> Result<(), ark_serialize::SerializationError> {
+    vk.alpha_g1.serialize_uncompressed(&mut writer)?;
+    P::G1Affine::zero().serialize_uncompressed(&mut writer)?;
+    vk.beta_g2.serialize_uncompressed(&mut writer)?;
+    vk.gamma_g2.serialize_uncompressed(&mut writer)?;
+    P::G1Affine::zero().serialize_uncompressed(&mut writer)?;
+    vk.delta_g2.serialize_uncompressed(&mut writer)?;
+    let ic_len = u32::try_from(vk.gamma_abc_g1.len())
+        .map_err(|_| ark_serialize::SerializationError::InvalidData)?;
+    writer.write_all(&ic_len.to_be_bytes())?;
+    for p in &vk.gamma_abc_g1 {
+        p.serialize_uncompressed(&mut writer)?;
+    }
+    Ok(())
+}
+
+/// Write a [Groth16 proof](ark_groth16::Proof) in Bellman format, the inverse of [`read_bellman_proof`].
+pub fn write_bellman_proof<P: Pairing, W: Write>(
+    proof: &ark_groth16::Proof<P>,
+    mut writer: W,
+) -> Result<(), ark_serialize::SerializationError> {
+    proof.a.serialize_compressed(&mut writer)?;
+    proof.b.serialize_compressed(&mut writer)?;
+    proof.c.serialize_compressed(&mut writer)?;
+    Ok(())
+}
+
+pub use zkvm::{WrappedProof, ZkvmProofError, read_risc0_seal, read_sp1_proof, risc0_claim_digest};
+
+mod zkvm;
+
+pub use verify::{VerifyError, verify_prepared};
+
+mod verify;
+
+pub use lint::{Finding, Severity, VkPoint, lint_public_input_count, lint_verifying_key};
+
+mod lint;
+
+#[cfg(feature = "zokrates")]
+pub use zokrates::{
+    ZokratesError, read_zokrates_proof, read_zokrates_vk, write_zokrates_proof, write_zokrates_vk,
+};
+
+#[cfg(feature = "zokrates")]
+mod zokrates;
+
+pub use gnark::{GnarkCurve, read_gnark_proof, read_gnark_vk, write_gnark_proof, write_gnark_vk};
 
 mod gnark {
-    use ark_bn254::{Bn254, Fq, Fq2, G1Affine, G2Affine};
-    use ark_ff::PrimeField;
+    use ark_ec::{
+        AffineRepr,
+        pairing::Pairing,
+        short_weierstrass::{Affine, SWCurveConfig},
+    };
+    use ark_ff::{BigInteger, Field, PrimeField, Zero};
     use ark_groth16::{Proof, VerifyingKey};
-    use ark_serialize::SerializationError;
-    use std::io::Read;
+    use ark_serialize::{Compress, SerializationError};
+    use std::io::{Read, Write};
+
+    /// The metadata gnark stores in the most significant bits of a serialized point.
+    struct Flags {
+        mask: u8,
+        uncompressed: u8,
+        /// `None` if infinity is encoded as (0, 0)
+        uncompressed_infinity: Option<u8>,
+        compressed_smallest: u8,
+        compressed_largest: u8,
+        compressed_infinity: u8,
+    }
+
+    // BN254 coordinates leave two bits for the metadata
+    const BN254_FLAGS: Flags = Flags {
+        mask: 0b11 << 6,
+        uncompressed: 0b00 << 6,
+        uncompressed_infinity: None,
+        compressed_smallest: 0b10 << 6,
+        compressed_largest: 0b11 << 6,
+        compressed_infinity: 0b01 << 6,
+    };
 
-    // Gnark Flag Constants
-    const M_MASK: u8 = 0b11 << 6;
-    const M_UNCOMPRESSED: u8 = 0b00 << 6;
-    //const M_COMPRESSED_SMALLEST: u8 = 0b10 << 6;
-    const M_COMPRESSED_LARGEST: u8 = 0b11 << 6;
-    const M_COMPRESSED_INFINITY: u8 = 0b01 << 6;
+    // BLS12-381 coordinates leave three bits for the metadata
+    const BLS12_381_FLAGS: Flags = Flags {
+        mask: 0b111 << 5,
+        uncompressed: 0b000 << 5,
+        uncompressed_infinity: Some(0b010 << 5),
+        compressed_smallest: 0b100 << 5,
+        compressed_largest: 0b101 << 5,
+        compressed_infinity: 0b110 << 5,
+    };
+
+    /// A curve whose points can be de/serialized in the format of gnark.
+    ///
+    /// Implemented for BN254 and BLS12-381.
+    pub trait GnarkCurve: Pairing {
+        /// Reads a point in G1 in compressed or uncompressed form.
+        fn read_gnark_g1<R: Read>(reader: R) -> Result<Self::G1Affine, SerializationError>;
+        /// Reads a point in G2 in compressed or uncompressed form.
+        fn read_gnark_g2<R: Read>(reader: R) -> Result<Self::G2Affine, SerializationError>;
+        /// Writes a point in G1.
+        fn write_gnark_g1<W: Write>(
+            p: &Self::G1Affine,
+            writer: W,
+            compress: Compress,
+        ) -> Result<(), SerializationError>;
+        /// Writes a point in G2.
+        fn write_gnark_g2<W: Write>(
+            p: &Self::G2Affine,
+            writer: W,
+            compress: Compress,
+        ) -> Result<(), SerializationError>;
+    }
+
+    macro_rules! impl_gnark_curve {
+        ($pairing: ty, $curve: ident, $flags: expr) => {
+            impl GnarkCurve for $pairing {
+                fn read_gnark_g1<R: Read>(reader: R) -> Result<Self::G1Affine, SerializationError> {
+                    read_point::<$curve::g1::Config, _>(reader, &$flags)
+                }
+
+                fn read_gnark_g2<R: Read>(reader: R) -> Result<Self::G2Affine, SerializationError> {
+                    read_point::<$curve::g2::Config, _>(reader, &$flags)
+                }
+
+                fn write_gnark_g1<W: Write>(
+                    p: &Self::G1Affine,
+                    writer: W,
+                    compress: Compress,
+                ) -> Result<(), SerializationError> {
+                    write_point(p, writer, compress, &$flags)
+                }
+
+                fn write_gnark_g2<W: Write>(
+                    p: &Self::G2Affine,
+                    writer: W,
+                    compress: Compress,
+                ) -> Result<(), SerializationError> {
+                    write_point(p, writer, compress, &$flags)
+                }
+            }
+        };
+    }
+
+    impl_gnark_curve!(ark_bn254::Bn254, ark_bn254, BN254_FLAGS);
+    impl_gnark_curve!(ark_bls12_381::Bls12_381, ark_bls12_381, BLS12_381_FLAGS);
 
     /// Read a [verifying key](ark_groth16::VerifyingKey) in Gnark format.
-    pub fn read_gnark_vk<R: Read>(
+    ///
+    /// The points of the key may be compressed or uncompressed. Gnark appends one point per
+    /// Pedersen commitment of the circuit to the linear combination of the public inputs,
+    /// these points are dropped.
+    pub fn read_gnark_vk<P: GnarkCurve, R: Read>(
         mut reader: R,
-    ) -> Result<VerifyingKey<Bn254>, SerializationError> {
-        let alpha_g1 = read_gnark_g1(&mut reader)?;
-        let _beta_g1 = read_gnark_g1(&mut reader)?;
-        let beta_g2 = read_gnark_g2(&mut reader)?;
-        let gamma_g2 = read_gnark_g2(&mut reader)?;
-        let _delta_g1 = read_gnark_g1(&mut reader)?;
-        let delta_g2 = read_gnark_g2(&mut reader)?;
-
-        let mut buf_len = [0u8; 4];
-        reader.read_exact(&mut buf_len)?;
-
-        // Subtract one from the linear comb vector to
-        // exclude the gnark commitment point, which
-        // we don't use
-        let ic_len = u32::from_be_bytes(buf_len) - 1;
-
-        let mut ic = Vec::with_capacity(ic_len as usize);
-        for _ in 0..ic_len {
-            ic.push(read_gnark_g1(&mut reader)?);
+    ) -> Result<VerifyingKey<P>, SerializationError> {
+        let alpha_g1 = P::read_gnark_g1(&mut reader)?;
+        let _beta_g1 = P::read_gnark_g1(&mut reader)?;
+        let beta_g2 = P::read_gnark_g2(&mut reader)?;
+        let gamma_g2 = P::read_gnark_g2(&mut reader)?;
+        let _delta_g1 = P::read_gnark_g1(&mut reader)?;
+        let delta_g2 = P::read_gnark_g2(&mut reader)?;
+
+        let k_len = read_u32(&mut reader)?;
+        let mut ic = Vec::with_capacity(k_len.min(1 << 16) as usize);
+        for _ in 0..k_len {
+            ic.push(P::read_gnark_g1(&mut reader)?);
         }
 
+        // keys written before gnark v0.9 end after the points and do not have commitments
+        let num_commitments = read_num_commitments(&mut reader)?.unwrap_or(0);
+        let ic_len = ic
+            .len()
+            .checked_sub(num_commitments as usize)
+            .ok_or(SerializationError::InvalidData)?;
+        ic.truncate(ic_len);
+
         Ok(VerifyingKey {
             alpha_g1,
             beta_g2,
@@ -339,111 +466,475 @@ mod gnark {
     }
 
     /// Read a [Groth16 proof](ark_groth16::Proof) in Gnark format.
-    pub fn read_gnark_proof<R: Read>(mut reader: R) -> Result<Proof<Bn254>, SerializationError> {
-        let a = read_gnark_g1(&mut reader)?;
-        let b = read_gnark_g2(&mut reader)?;
-        let c = read_gnark_g1(&mut reader)?;
+    pub fn read_gnark_proof<P: GnarkCurve, R: Read>(
+        mut reader: R,
+    ) -> Result<Proof<P>, SerializationError> {
+        let a = P::read_gnark_g1(&mut reader)?;
+        let b = P::read_gnark_g2(&mut reader)?;
+        let c = P::read_gnark_g1(&mut reader)?;
 
         Ok(Proof { a, b, c })
     }
 
-    fn read_gnark_g1<R: Read>(mut reader: R) -> Result<G1Affine, SerializationError> {
-        let mut buf = [0u8; 32];
+    /// Write a [verifying key](ark_groth16::VerifyingKey) in Gnark format, as written by
+    /// `WriteTo` (compressed) or `WriteRawTo` (uncompressed) of gnark.
+    ///
+    /// The key is written without commitments. As arkworks keys do not contain β and δ in G1,
+    /// which are not needed for verification, they are written as the point at infinity.
+    pub fn write_gnark_vk<P: GnarkCurve, W: Write>(
+        vk: &VerifyingKey<P>,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        P::write_gnark_g1(&vk.alpha_g1, &mut writer, compress)?;
+        P::write_gnark_g1(&P::G1Affine::zero(), &mut writer, compress)?;
+        P::write_gnark_g2(&vk.beta_g2, &mut writer, compress)?;
+        P::write_gnark_g2(&vk.gamma_g2, &mut writer, compress)?;
+        P::write_gnark_g1(&P::G1Affine::zero(), &mut writer, compress)?;
+        P::write_gnark_g2(&vk.delta_g2, &mut writer, compress)?;
+        write_u32(&mut writer, vk.gamma_abc_g1.len())?;
+        for p in &vk.gamma_abc_g1 {
+            P::write_gnark_g1(p, &mut writer, compress)?;
+        }
+        // no public and committed wires and no commitment keys
+        write_u32(&mut writer, 0)?;
+        write_u32(&mut writer, 0)?;
+        Ok(())
+    }
+
+    /// Write a [Groth16 proof](ark_groth16::Proof) in Gnark format, as written by `WriteTo`
+    /// (compressed) or `WriteRawTo` (uncompressed) of gnark.
+    ///
+    /// The proof is written without commitments.
+    pub fn write_gnark_proof<P: GnarkCurve, W: Write>(
+        proof: &Proof<P>,
+        mut writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        P::write_gnark_g1(&proof.a, &mut writer, compress)?;
+        P::write_gnark_g2(&proof.b, &mut writer, compress)?;
+        P::write_gnark_g1(&proof.c, &mut writer, compress)?;
+        // no commitments and the proof of knowledge of the commitments is the point at infinity
+        write_u32(&mut writer, 0)?;
+        P::write_gnark_g1(&P::G1Affine::zero(), &mut writer, compress)?;
+        Ok(())
+    }
+
+    /// Reads the public and committed wires of every commitment, followed by the number of commitment keys.
+    /// Returns `None` if the reader is already at its end.
+    fn read_num_commitments<R: Read>(mut reader: R) -> Result<Option<u32>, SerializationError> {
+        let mut buf = [0u8; 4];
+        if reader.read(&mut buf[..1])? == 0 {
+            return Ok(None);
+        }
+        reader.read_exact(&mut buf[1..])?;
+        let num_committed = u32::from_be_bytes(buf);
+        for _ in 0..num_committed {
+            let len = read_u32(&mut reader)?;
+            for _ in 0..len {
+                reader.read_exact(&mut [0u8; 8])?;
+            }
+        }
+        read_u32(&mut reader).map(Some)
+    }
+
+    fn read_u32<R: Read>(mut reader: R) -> Result<u32, SerializationError> {
+        let mut buf = [0u8; 4];
+        reader.read_exact(&mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn write_u32<W: Write>(mut writer: W, value: usize) -> Result<(), SerializationError> {
+        let value = u32::try_from(value).map_err(|_| SerializationError::InvalidData)?;
+        writer.write_all(&value.to_be_bytes())?;
+        Ok(())
+    }
+
+    /// The byte size of a coordinate of a point on the curve.
+    fn coordinate_size<C: SWCurveConfig>() -> usize {
+        let n8 = <C::BaseField as Field>::BasePrimeField::MODULUS_BIT_SIZE.div_ceil(8) as usize;
+        n8 * usize::try_from(C::BaseField::extension_degree()).expect("degree fits into usize")
+    }
+
+    /// Decodes a big-endian coordinate. Extension field elements start with the highest coefficient.
+    fn coordinate_from_bytes<C: SWCurveConfig>(
+        bytes: &[u8],
+    ) -> Result<C::BaseField, SerializationError> {
+        let degree = usize::try_from(C::BaseField::extension_degree()).expect("degree fits");
+        let elements = bytes
+            .chunks_exact(bytes.len() / degree)
+            .rev()
+            .map(<C::BaseField as Field>::BasePrimeField::from_be_bytes_mod_order);
+        C::BaseField::from_base_prime_field_elems(elements).ok_or(SerializationError::InvalidData)
+    }
+
+    /// Encodes a coordinate, the inverse of [`coordinate_from_bytes`].
+    fn coordinate_to_bytes<C: SWCurveConfig>(x: &C::BaseField) -> Vec<u8> {
+        let elements = x.to_base_prime_field_elements().collect::<Vec<_>>();
+        elements
+            .into_iter()
+            .rev()
+            .flat_map(|e| e.into_bigint().to_bytes_be())
+            .collect()
+    }
+
+    /// The sign of a coordinate as defined by gnark: whether it is larger than its negation,
+    /// deciding by the first non-zero coefficient, i.e. for Fq2 by the real part unless zero.
+    fn is_lexicographically_largest<F: Field>(x: &F) -> bool {
+        x.to_base_prime_field_elements()
+            .find(|e| !e.is_zero())
+            .is_some_and(|e| e.into_bigint() > F::BasePrimeField::MODULUS_MINUS_ONE_DIV_TWO)
+    }
+
+    fn read_point<C: SWCurveConfig, R: Read>(
+        mut reader: R,
+        flags: &Flags,
+    ) -> Result<Affine<C>, SerializationError> {
+        let mut buf = vec![0u8; coordinate_size::<C>()];
         reader.read_exact(&mut buf)?;
 
         // Read metadata from MSB
-        let m_data = buf[0] & M_MASK;
+        let m_data = buf[0] & flags.mask;
 
         // Handle Infinity
-        if m_data == M_COMPRESSED_INFINITY {
-            return Ok(G1Affine::identity());
+        if m_data == flags.compressed_infinity {
+            return Ok(Affine::identity());
         }
 
         // Clear flags to recover the X coordinate bytes
-        buf[0] &= !M_MASK;
-        let x = Fq::from_be_bytes_mod_order(&buf);
+        buf[0] &= !flags.mask;
+        let x = coordinate_from_bytes::<C>(&buf)?;
 
-        if m_data == M_UNCOMPRESSED {
-            // Read Y coordinate (next 32 bytes)
+        if m_data == flags.uncompressed || Some(m_data) == flags.uncompressed_infinity {
+            // Read Y coordinate
             reader.read_exact(&mut buf)?;
-            let y = Fq::from_be_bytes_mod_order(&buf);
+            let y = coordinate_from_bytes::<C>(&buf)?;
 
-            let p = G1Affine::new(x, y);
+            // Without a dedicated flag, gnark encodes infinity as (0, 0)
+            if Some(m_data) == flags.uncompressed_infinity || (x.is_zero() && y.is_zero()) {
+                return Ok(Affine::identity());
+            }
+            let p = Affine::new_unchecked(x, y);
 
             // Ensure point is valid
             if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
                 return Err(SerializationError::InvalidData);
             }
             Ok(p)
-        } else {
+        } else if m_data == flags.compressed_smallest || m_data == flags.compressed_largest {
             // Compressed: Recover Y
             // m_data determines if we want the lexicographically largest Y
-            let greatest = m_data == M_COMPRESSED_LARGEST;
+            let greatest = m_data == flags.compressed_largest;
 
             // Use unchecked + manual subgroup check for safety
-            let p = G1Affine::get_point_from_x_unchecked(x, greatest)
+            let p = Affine::get_point_from_x_unchecked(x, greatest)
                 .ok_or(SerializationError::InvalidData)?;
+            // arkworks orders Fq2 by the imaginary part first, gnark by the real part
+            let p = if is_lexicographically_largest(&p.y) == greatest {
+                p
+            } else {
+                -p
+            };
 
             if !p.is_in_correct_subgroup_assuming_on_curve() {
                 return Err(SerializationError::InvalidData);
             }
-
             Ok(p)
+        } else {
+            Err(SerializationError::UnexpectedFlags)
         }
     }
 
-    fn read_gnark_g2<R: Read>(mut reader: R) -> Result<G2Affine, SerializationError> {
-        let mut buf_a1 = [0u8; 32];
-        let mut buf_a0 = [0u8; 32];
+    fn write_point<C: SWCurveConfig, W: Write>(
+        p: &Affine<C>,
+        mut writer: W,
+        compress: Compress,
+        flags: &Flags,
+    ) -> Result<(), SerializationError> {
+        let size = coordinate_size::<C>();
+        let Some((x, y)) = p.xy() else {
+            let (mut buf, flag) = match compress {
+                Compress::Yes => (vec![0u8; size], flags.compressed_infinity),
+                Compress::No => (
+                    vec![0u8; 2 * size],
+                    flags.uncompressed_infinity.unwrap_or(flags.uncompressed),
+                ),
+            };
+            buf[0] = flag;
+            return Ok(writer.write_all(&buf)?);
+        };
+        let mut buf = coordinate_to_bytes::<C>(&x);
+        match compress {
+            Compress::Yes => {
+                buf[0] |= if is_lexicographically_largest(&y) {
+                    flags.compressed_largest
+                } else {
+                    flags.compressed_smallest
+                };
+                writer.write_all(&buf)?;
+            }
+            Compress::No => {
+                buf[0] |= flags.uncompressed;
+                writer.write_all(&buf)?;
+                writer.write_all(&coordinate_to_bytes::<C>(&y))?;
+            }
+        }
+        Ok(())
+    }
+}
 
-        // Read X.A1 (High part with flags)
-        reader.read_exact(&mut buf_a1)?;
-        let m_data = buf_a1[0] & M_MASK;
+#[cfg(test)]
+mod tests {
+    use ark_bls12_381::Bls12_381;
+    use ark_bn254::Bn254;
+    use ark_ec::{AffineRepr, CurveGroup, pairing::Pairing};
+    use ark_groth16::{Proof, VerifyingKey};
+    use ark_serialize::Compress;
+    use circom_types::groth16::VerificationKey;
 
-        if m_data == M_COMPRESSED_INFINITY {
-            reader.read_exact(&mut buf_a0)?; // Consume the rest
-            return Ok(G2Affine::identity());
-        }
+    use super::GnarkCurve;
 
-        // Read X.A0
-        reader.read_exact(&mut buf_a0)?;
+    const TEST_VK_BN254: &str = include_str!("../data/test_verification_key.json");
 
-        // Clear flags
-        buf_a1[0] &= !M_MASK;
+    fn proofs<P: Pairing>() -> Vec<Proof<P>> {
+        (1..32u64)
+            .map(|i| Proof {
+                a: (P::G1Affine::generator() * P::ScalarField::from(i)).into_affine(),
+                b: (P::G2Affine::generator() * P::ScalarField::from(i + 100)).into_affine(),
+                c: (P::G1Affine::generator() * P::ScalarField::from(i + 200)).into_affine(),
+            })
+            .collect()
+    }
 
-        let x_a1 = Fq::from_be_bytes_mod_order(&buf_a1);
-        let x_a0 = Fq::from_be_bytes_mod_order(&buf_a0);
+    fn vk_bn254() -> VerifyingKey<Bn254> {
+        serde_json::from_str::<VerificationKey<Bn254>>(TEST_VK_BN254)
+            .unwrap()
+            .into()
+    }
 
-        // Arkworks Fq2 = c0 + u*c1, where c0 is real(A0), c1 is imag(A1)
-        let x = Fq2::new(x_a0, x_a1);
+    fn vk_from_proofs<P: Pairing>() -> VerifyingKey<P> {
+        let proofs = proofs::<P>();
+        VerifyingKey {
+            alpha_g1: proofs[0].a,
+            beta_g2: proofs[1].b,
+            gamma_g2: proofs[2].b,
+            delta_g2: proofs[3].b,
+            gamma_abc_g1: proofs.iter().take(4).map(|p| p.c).collect(),
+        }
+    }
 
-        if m_data == M_UNCOMPRESSED {
-            // Uncompressed: Read Y.A1 | Y.A0
-            reader.read_exact(&mut buf_a1)?;
-            reader.read_exact(&mut buf_a0)?;
+    fn gnark_round_trip<P: GnarkCurve>(vk: VerifyingKey<P>) {
+        for compress in [Compress::Yes, Compress::No] {
+            for proof in proofs::<P>() {
+                let mut buf = Vec::new();
+                super::write_gnark_proof(&proof, &mut buf, compress).unwrap();
+                assert_eq!(
+                    super::read_gnark_proof::<P, _>(buf.as_slice()).unwrap(),
+                    proof
+                );
+            }
+            let infinity = Proof::<P> {
+                a: P::G1Affine::zero(),
+                b: P::G2Affine::zero(),
+                c: P::G1Affine::zero(),
+            };
+            let mut buf = Vec::new();
+            super::write_gnark_proof(&infinity, &mut buf, compress).unwrap();
+            assert_eq!(
+                super::read_gnark_proof::<P, _>(buf.as_slice()).unwrap(),
+                infinity
+            );
+
+            let mut buf = Vec::new();
+            super::write_gnark_vk(&vk, &mut buf, compress).unwrap();
+            assert_eq!(super::read_gnark_vk::<P, _>(buf.as_slice()).unwrap(), vk);
+        }
+    }
 
-            let y_a1 = Fq::from_be_bytes_mod_order(&buf_a1);
-            let y_a0 = Fq::from_be_bytes_mod_order(&buf_a0);
-            let y = Fq2::new(y_a0, y_a1);
+    fn bellman_round_trip<P: Pairing>(vk: VerifyingKey<P>) {
+        for proof in proofs::<P>() {
+            let mut buf = Vec::new();
+            super::write_bellman_proof(&proof, &mut buf).unwrap();
+            assert_eq!(
+                super::read_bellman_proof::<P, _>(buf.as_slice()).unwrap(),
+                proof
+            );
+        }
+        let mut buf = Vec::new();
+        super::write_bellman_vk(&vk, &mut buf).unwrap();
+        assert_eq!(super::read_bellman_vk::<P, _>(buf.as_slice()).unwrap(), vk);
+    }
 
-            let p = G2Affine::new(x, y);
+    #[test]
+    fn gnark_round_trip_bn254() {
+        gnark_round_trip(vk_bn254());
+    }
 
-            if !p.is_on_curve() || !p.is_in_correct_subgroup_assuming_on_curve() {
-                return Err(SerializationError::InvalidData);
-            }
-            Ok(p)
-        } else {
-            // Compressed: Recover Y
-            let greatest = m_data == M_COMPRESSED_LARGEST;
+    #[test]
+    fn gnark_round_trip_bls12_381() {
+        gnark_round_trip(vk_from_proofs::<Bls12_381>());
+    }
 
-            let p = G2Affine::get_point_from_x_unchecked(x, greatest)
-                .ok_or(SerializationError::InvalidData)?;
+    #[test]
+    fn gnark_point_sizes() {
+        let mut buf = Vec::new();
+        Bn254::write_gnark_g2(&ark_bn254::G2Affine::generator(), &mut buf, Compress::Yes).unwrap();
+        assert_eq!(buf.len(), 64);
+        let mut buf = Vec::new();
+        Bls12_381::write_gnark_g1(
+            &ark_bls12_381::G1Affine::generator(),
+            &mut buf,
+            Compress::No,
+        )
+        .unwrap();
+        assert_eq!(buf.len(), 96);
+        let mut buf = Vec::new();
+        Bls12_381::write_gnark_g2(
+            &ark_bls12_381::G2Affine::generator(),
+            &mut buf,
+            Compress::Yes,
+        )
+        .unwrap();
+        assert_eq!(buf.len(), 96);
+    }
 
-            // Crucial for G2: Check subgroup!
-            if !p.is_in_correct_subgroup_assuming_on_curve() {
-                return Err(SerializationError::InvalidData);
+    #[test]
+    fn gnark_g2_sign_follows_real_part() {
+        use ark_ff::PrimeField;
+
+        let is_largest =
+            |x: ark_bn254::Fq| x.into_bigint() > ark_bn254::Fq::MODULUS_MINUS_ONE_DIV_TWO;
+        // points for which the ordering of arkworks (imaginary part first) differs from gnark
+        let points = (1..64u64)
+            .map(|i| (ark_bn254::G2Affine::generator() * ark_bn254::Fr::from(i)).into_affine())
+            .filter(|p| is_largest(p.y.c0) != is_largest(p.y.c1))
+            .collect::<Vec<_>>();
+        assert!(!points.is_empty());
+        for p in points {
+            let mut buf = Vec::new();
+            Bn254::write_gnark_g2(&p, &mut buf, Compress::Yes).unwrap();
+            let flag = if is_largest(p.y.c0) { 0b11 } else { 0b10 };
+            assert_eq!(buf[0] >> 6, flag);
+            assert_eq!(Bn254::read_gnark_g2(buf.as_slice()).unwrap(), p);
+            // the same x with the other sign decodes to the negated point
+            buf[0] ^= 0b01 << 6;
+            assert_eq!(Bn254::read_gnark_g2(buf.as_slice()).unwrap(), -p);
+        }
+    }
+
+    #[test]
+    fn gnark_rejects_point_not_on_curve() {
+        let mut buf = Vec::new();
+        Bls12_381::write_gnark_g1(
+            &ark_bls12_381::G1Affine::generator(),
+            &mut buf,
+            Compress::No,
+        )
+        .unwrap();
+        buf[95] ^= 1;
+        assert!(Bls12_381::read_gnark_g1(buf.as_slice()).is_err());
+    }
+
+    #[test]
+    fn gnark_vk_drops_commitments() {
+        let vk = vk_bn254();
+        let mut with_commitment = vk.clone();
+        with_commitment
+            .gamma_abc_g1
+            .push(ark_bn254::G1Affine::generator());
+        let mut buf = Vec::new();
+        super::write_gnark_vk(&with_commitment, &mut buf, Compress::Yes).unwrap();
+        // replace the empty commitment information with one commitment to the first public input
+        buf.truncate(buf.len() - 8);
+        buf.extend(1u32.to_be_bytes());
+        buf.extend(1u32.to_be_bytes());
+        buf.extend(1u64.to_be_bytes());
+        buf.extend(1u32.to_be_bytes());
+        assert_eq!(
+            super::read_gnark_vk::<Bn254, _>(buf.as_slice()).unwrap(),
+            vk
+        );
+    }
+
+    /// Encodes a key in the layout of `VerifyingKey.WriteRawTo` of gnark for BN254, independently
+    /// of [`super::write_gnark_vk`]. `None` gives the layout before gnark v0.9, which ends after
+    /// the public input points. Otherwise, one point per commitment is appended to the public
+    /// input points, followed by the wires of every commitment, the number of commitment keys
+    /// and the Pedersen key (two points in G2) of every commitment.
+    fn gnark_raw_vk(vk: &VerifyingKey<Bn254>, commitments: Option<&[&[u64]]>) -> Vec<u8> {
+        use ark_ff::{BigInteger, PrimeField};
+
+        let fq = |x: ark_bn254::Fq| x.into_bigint().to_bytes_be();
+        let g1 = |p: ark_bn254::G1Affine| [fq(p.x), fq(p.y)].concat();
+        let g2 = |p: ark_bn254::G2Affine| [fq(p.x.c1), fq(p.x.c0), fq(p.y.c1), fq(p.y.c0)].concat();
+        let g1_generator = ark_bn254::G1Affine::generator();
+        let g2_generator = ark_bn254::G2Affine::generator();
+
+        let mut bytes = [
+            g1(vk.alpha_g1),
+            g1(g1_generator),
+            g2(vk.beta_g2),
+            g2(vk.gamma_g2),
+            g1(g1_generator),
+            g2(vk.delta_g2),
+        ]
+        .concat();
+        let num_commitments = commitments.map_or(0, <[_]>::len);
+        bytes.extend(((vk.gamma_abc_g1.len() + num_commitments) as u32).to_be_bytes());
+        for p in &vk.gamma_abc_g1 {
+            bytes.extend(g1(*p));
+        }
+        let Some(commitments) = commitments else {
+            return bytes;
+        };
+        for _ in commitments {
+            bytes.extend(g1(g1_generator));
+        }
+        bytes.extend((commitments.len() as u32).to_be_bytes());
+        for wires in commitments {
+            bytes.extend((wires.len() as u32).to_be_bytes());
+            for wire in *wires {
+                bytes.extend(wire.to_be_bytes());
             }
-            Ok(p)
         }
+        bytes.extend((commitments.len() as u32).to_be_bytes());
+        for _ in commitments {
+            bytes.extend(g2(g2_generator));
+            bytes.extend(g2(-g2_generator));
+        }
+        bytes
+    }
+
+    #[test]
+    fn gnark_vk_commitment_layouts() {
+        let vk = vk_bn254();
+        let read = |bytes: Vec<u8>| super::read_gnark_vk::<Bn254, _>(bytes.as_slice());
+        // before gnark v0.9
+        assert_eq!(read(gnark_raw_vk(&vk, None)).unwrap(), vk);
+        // without commitments
+        assert_eq!(read(gnark_raw_vk(&vk, Some(&[]))).unwrap(), vk);
+        // with one and two commitments
+        assert_eq!(read(gnark_raw_vk(&vk, Some(&[&[1, 2]]))).unwrap(), vk);
+        assert_eq!(read(gnark_raw_vk(&vk, Some(&[&[1], &[]]))).unwrap(), vk);
+
+        // more commitments than public input points
+        let mut bytes = gnark_raw_vk(&vk, Some(&[]));
+        let len = bytes.len();
+        bytes[len - 4..].copy_from_slice(&8u32.to_be_bytes());
+        assert!(read(bytes).is_err());
+        // truncated commitment information
+        let mut bytes = gnark_raw_vk(&vk, Some(&[&[1, 2]]));
+        bytes.truncate(bytes.len() - 2 * 128 - 6);
+        assert!(read(bytes).is_err());
+    }
+
+    #[test]
+    fn bellman_round_trip_bn254() {
+        bellman_round_trip(vk_bn254());
+    }
+
+    #[test]
+    fn bellman_round_trip_bls12_381() {
+        bellman_round_trip(vk_from_proofs::<Bls12_381>());
     }
 }
@@ -0,0 +1,255 @@
+//! Security checks for verifying keys, to catch keys of insecure setups before deploying them.
+use std::fmt;
+
+use ark_ec::{AffineRepr, pairing::Pairing};
+use ark_ff::{PrimeField, Zero};
+use ark_groth16::VerifyingKey;
+
+/// A point of a [verifying key](ark_groth16::VerifyingKey).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VkPoint {
+    /// α in G1
+    Alpha,
+    /// β in G2
+    Beta,
+    /// γ in G2
+    Gamma,
+    /// δ in G2
+    Delta,
+    /// The `i`-th point of the linear combination of the public inputs (`IC`)
+    Ic(usize),
+}
+
+impl fmt::Display for VkPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Alpha => write!(f, "alpha"),
+            Self::Beta => write!(f, "beta"),
+            Self::Gamma => write!(f, "gamma"),
+            Self::Delta => write!(f, "delta"),
+            Self::Ic(i) => write!(f, "IC[{i}]"),
+        }
+    }
+}
+
+/// The severity of a [`Finding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The key is unusual, but not necessarily insecure
+    Warning,
+    /// The key breaks the soundness of the verifier
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "warning"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A security issue of a [verifying key](ark_groth16::VerifyingKey), see [`lint_verifying_key`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Finding {
+    /// γ is the generator of G2. snarkjs never contributes to γ, so this is expected for its
+    /// keys and only a problem if δ is the generator as well
+    GammaIsGenerator,
+    /// δ is the generator of G2, i.e., the key is from a setup without phase 2 contributions
+    DeltaIsGenerator,
+    /// γ equals δ, which allows forging proofs for arbitrary public inputs
+    GammaEqualsDelta,
+    /// A point is the point at infinity
+    Identity(VkPoint),
+    /// Two points of the linear combination of the public inputs are equal
+    DuplicateIc(usize, usize),
+    /// A point is not in the prime order subgroup
+    NotInSubgroup(VkPoint),
+    /// The number of public inputs announced by the key does not match the length of `IC`
+    PublicInputCountMismatch {
+        /// The number of public inputs of the key
+        n_public: usize,
+        /// The number of points of `IC`
+        ic_len: usize,
+    },
+}
+
+impl Finding {
+    /// Returns the [`Severity`] of the finding.
+    pub fn severity(&self) -> Severity {
+        match self {
+            Self::GammaIsGenerator => Severity::Warning,
+            _ => Severity::Critical,
+        }
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GammaIsGenerator => write!(f, "gamma is the generator of G2"),
+            Self::DeltaIsGenerator => write!(
+                f,
+                "delta is the generator of G2, the key is missing phase 2 contributions"
+            ),
+            Self::GammaEqualsDelta => write!(f, "gamma equals delta"),
+            Self::Identity(p) => write!(f, "{p} is the point at infinity"),
+            Self::DuplicateIc(i, j) => write!(f, "IC[{i}] equals IC[{j}]"),
+            Self::NotInSubgroup(p) => write!(f, "{p} is not in the prime order subgroup"),
+            Self::PublicInputCountMismatch { n_public, ic_len } => write!(
+                f,
+                "the key has {n_public} public inputs, but IC has {ic_len} points"
+            ),
+        }
+    }
+}
+
+/// Checks a [verifying key](ark_groth16::VerifyingKey) for conditions that break the soundness of
+/// the verifier, e.g., keys of development setups whose γ and δ are the generator of G2.
+///
+/// Returns an empty list if no issues were found. Findings with [`Severity::Warning`] do not
+/// break soundness on their own, see [`Finding::severity`].
+pub fn lint_verifying_key<P: Pairing>(vk: &VerifyingKey<P>) -> Vec<Finding> {
+    let mut findings = vec![];
+    let generator = P::G2Affine::generator();
+    if vk.gamma_g2 == generator {
+        findings.push(Finding::GammaIsGenerator);
+    }
+    if vk.delta_g2 == generator {
+        findings.push(Finding::DeltaIsGenerator);
+    }
+    if vk.gamma_g2 == vk.delta_g2 {
+        findings.push(Finding::GammaEqualsDelta);
+    }
+
+    let mut check_point = |point: VkPoint, is_zero: bool, in_subgroup: bool| {
+        if is_zero {
+            findings.push(Finding::Identity(point));
+        } else if !in_subgroup {
+            findings.push(Finding::NotInSubgroup(point));
+        }
+    };
+    check_point(
+        VkPoint::Alpha,
+        vk.alpha_g1.is_zero(),
+        in_subgroup(&vk.alpha_g1),
+    );
+    for (point, p) in [
+        (VkPoint::Beta, &vk.beta_g2),
+        (VkPoint::Gamma, &vk.gamma_g2),
+        (VkPoint::Delta, &vk.delta_g2),
+    ] {
+        check_point(point, p.is_zero(), in_subgroup(p));
+    }
+    for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+        check_point(VkPoint::Ic(i), p.is_zero(), in_subgroup(p));
+    }
+
+    for (i, p) in vk.gamma_abc_g1.iter().enumerate() {
+        if let Some(j) = vk.gamma_abc_g1[..i].iter().position(|q| q == p) {
+            // identity points are already reported
+            if !p.is_zero() {
+                findings.push(Finding::DuplicateIc(j, i));
+            }
+        }
+    }
+    findings
+}
+
+/// Checks that the number of public inputs `n_public` announced by a key, e.g., the `nPublic`
+/// of a Circom verification key, matches the length of the linear combination of the public
+/// inputs of `vk`.
+pub fn lint_public_input_count<P: Pairing>(
+    vk: &VerifyingKey<P>,
+    n_public: usize,
+) -> Option<Finding> {
+    let ic_len = vk.gamma_abc_g1.len();
+    (ic_len != n_public + 1).then_some(Finding::PublicInputCountMismatch { n_public, ic_len })
+}
+
+fn in_subgroup<A: AffineRepr>(p: &A) -> bool {
+    p.mul_bigint(A::ScalarField::MODULUS).is_zero()
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_bn254::{Bn254, Fq, Fr, G1Affine, G2Affine};
+    use ark_ec::CurveGroup;
+    use circom_types::groth16::VerificationKey;
+
+    use super::*;
+
+    const TEST_VK_BN254: &str = include_str!("../data/test_verification_key.json");
+
+    fn vk() -> VerifyingKey<Bn254> {
+        serde_json::from_str::<VerificationKey<Bn254>>(TEST_VK_BN254)
+            .unwrap()
+            .into()
+    }
+
+    #[test]
+    fn accepts_secure_key() {
+        let vk = vk();
+        // the key is from snarkjs, which leaves gamma at the generator
+        assert_eq!(lint_verifying_key(&vk), vec![Finding::GammaIsGenerator]);
+        assert_eq!(Finding::GammaIsGenerator.severity(), Severity::Warning);
+        assert_eq!(
+            lint_public_input_count(&vk, vk.gamma_abc_g1.len() - 1),
+            None
+        );
+        assert_eq!(
+            lint_public_input_count(&vk, 1),
+            Some(Finding::PublicInputCountMismatch {
+                n_public: 1,
+                ic_len: vk.gamma_abc_g1.len()
+            })
+        );
+    }
+
+    #[test]
+    fn flags_dev_keys() {
+        let mut vk = vk();
+        vk.delta_g2 = G2Affine::generator();
+        assert_eq!(
+            lint_verifying_key(&vk),
+            vec![
+                Finding::GammaIsGenerator,
+                Finding::DeltaIsGenerator,
+                Finding::GammaEqualsDelta
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_invalid_points() {
+        let mut vk = vk();
+        vk.alpha_g1 = G1Affine::zero();
+        vk.gamma_abc_g1[2] = vk.gamma_abc_g1[1];
+        vk.gamma_abc_g1[3] = G1Affine::zero();
+        // a point on the twist which is not in the prime order subgroup
+        let mut x = ark_bn254::Fq2::new(Fq::from(1u64), Fq::from(0u64));
+        let outside = loop {
+            if let Some(p) = G2Affine::get_point_from_x_unchecked(x, false)
+                && !in_subgroup(&p)
+            {
+                break p;
+            }
+            x += ark_bn254::Fq2::new(Fq::from(1u64), Fq::from(0u64));
+        };
+        vk.beta_g2 = outside;
+        assert!(in_subgroup(
+            &(G2Affine::generator() * Fr::from(3u64)).into_affine()
+        ));
+        assert_eq!(
+            lint_verifying_key(&vk),
+            vec![
+                Finding::GammaIsGenerator,
+                Finding::Identity(VkPoint::Alpha),
+                Finding::NotInSubgroup(VkPoint::Beta),
+                Finding::Identity(VkPoint::Ic(3)),
+                Finding::DuplicateIc(1, 2),
+            ]
+        );
+    }
+}
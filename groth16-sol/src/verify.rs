@@ -0,0 +1,183 @@
+//! Verification of prepared proofs, performing the same checks as the generated verifier.
+use alloy_primitives::U256;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Projective};
+use ark_ec::{CurveGroup, pairing::Pairing};
+use ark_ff::Zero;
+use ark_groth16::VerifyingKey;
+use thiserror::Error;
+
+use crate::zkvm::{fq_from_be_bytes, fr_from_be_bytes, g1_from_xy, g2_from_xy};
+
+/// Error type describing at which stage the verification of a prepared proof failed.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum VerifyError {
+    /// The verifying key has no `gamma_abc_g1` points, not even the constant term
+    #[error("verifying key has no gamma_abc_g1 points")]
+    EmptyGammaAbc,
+    /// The number of public inputs does not match the verifying key, the call cannot be encoded
+    #[error("expected {expected} public inputs, but got {actual}")]
+    InvalidPublicInputCount {
+        /// The number of public inputs of the verifying key
+        expected: usize,
+        /// The number of provided public inputs
+        actual: usize,
+    },
+    /// A public input is not reduced, the contract reverts with `PublicInputNotInField`
+    #[error("public input {0} is not in the scalar field")]
+    PublicInputNotInField(usize),
+    /// A word of the proof is not reduced, the pairing precompile fails
+    #[error("proof element {0} is not in the base field")]
+    ProofNotInField(usize),
+    /// A point of the proof is not on the curve or not in the correct subgroup, the pairing precompile fails
+    #[error("proof point {0} is not a valid curve point")]
+    InvalidProofPoint(&'static str),
+    /// The pairing equation does not hold, the contract reverts with `ProofInvalid`
+    #[error("pairing check failed")]
+    PairingCheckFailed,
+}
+
+/// Verifies a proof prepared with [`prepare_uncompressed_proof`](crate::prepare_uncompressed_proof)
+/// for the public inputs `input`, exactly like `verifyProof` of the contract generated for `vk`.
+///
+/// The public inputs must be reduced and the proof must consist of reduced coordinates of valid
+/// points, where the point at infinity is encoded as zeros. The proof is valid if
+/// `e(A, B) * e(C, -δ) * e(α, -β) * e(L, -γ) = 1`, where `L` is the linear combination of the
+/// public inputs with `vk.gamma_abc_g1`, starting with `vk.gamma_abc_g1[0]`.
+pub fn verify_prepared(
+    vk: &VerifyingKey<Bn254>,
+    input: &[U256],
+    proof: &[U256; 8],
+) -> Result<(), VerifyError> {
+    let Some((gamma_abc_0, gamma_abc)) = vk.gamma_abc_g1.split_first() else {
+        return Err(VerifyError::EmptyGammaAbc);
+    };
+    let expected = gamma_abc.len();
+    if input.len() != expected {
+        return Err(VerifyError::InvalidPublicInputCount {
+            expected,
+            actual: input.len(),
+        });
+    }
+    let input = input
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            fr_from_be_bytes(&x.to_be_bytes::<32>()).ok_or(VerifyError::PublicInputNotInField(i))
+        })
+        .collect::<Result<Vec<Fr>, _>>()?;
+    let words = proof
+        .iter()
+        .enumerate()
+        .map(|(i, x)| {
+            fq_from_be_bytes(&x.to_be_bytes::<32>()).ok_or(VerifyError::ProofNotInField(i))
+        })
+        .collect::<Result<Vec<Fq>, _>>()?;
+
+    // the EVM encodes the coefficients of Fq2 with the imaginary part first
+    let a = g1_from_xy(words[0], words[1]).ok_or(VerifyError::InvalidProofPoint("A"))?;
+    let b = g2_from_xy(Fq2::new(words[3], words[2]), Fq2::new(words[5], words[4]))
+        .ok_or(VerifyError::InvalidProofPoint("B"))?;
+    let c = g1_from_xy(words[6], words[7]).ok_or(VerifyError::InvalidProofPoint("C"))?;
+
+    let l = gamma_abc
+        .iter()
+        .zip(input)
+        .fold(G1Projective::from(*gamma_abc_0), |acc, (p, x)| acc + *p * x)
+        .into_affine();
+
+    let pairing = Bn254::multi_pairing(
+        [a, c, vk.alpha_g1, l],
+        [b, -vk.delta_g2, -vk.beta_g2, -vk.gamma_g2],
+    );
+    if !pairing.is_zero() {
+        return Err(VerifyError::PairingCheckFailed);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, path::PathBuf};
+
+    use ark_ff::{BigInteger, PrimeField};
+    use circom_types::{
+        Witness,
+        groth16::{Proof, VerificationKey},
+    };
+
+    use super::*;
+
+    fn kat() -> (VerifyingKey<Bn254>, Vec<U256>, [U256; 8]) {
+        let kats =
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../circom-types/kats/groth16/bn254");
+        let vk: VerificationKey<Bn254> =
+            serde_json::from_reader(File::open(kats.join("verification_key.json")).unwrap())
+                .unwrap();
+        let proof: Proof<Bn254> =
+            serde_json::from_reader(File::open(kats.join("circom.proof")).unwrap()).unwrap();
+        let witness =
+            Witness::<Fr>::from_reader(File::open(kats.join("witness.wtns")).unwrap()).unwrap();
+        let input = witness.values[1..=vk.n_public]
+            .iter()
+            .map(|x| -> U256 { (*x).into() })
+            .collect();
+        (
+            vk.into(),
+            input,
+            crate::prepare_uncompressed_proof(&proof.into()),
+        )
+    }
+
+    #[test]
+    fn can_verify_prepared() {
+        let (vk, input, proof) = kat();
+        verify_prepared(&vk, &input, &proof).unwrap();
+    }
+
+    #[test]
+    fn reports_failing_stage() {
+        let (vk, input, proof) = kat();
+        let mut empty = vk.clone();
+        empty.gamma_abc_g1.clear();
+        assert_eq!(
+            verify_prepared(&empty, &[], &proof),
+            Err(VerifyError::EmptyGammaAbc)
+        );
+
+        assert_eq!(
+            verify_prepared(&vk, &input[1..], &proof),
+            Err(VerifyError::InvalidPublicInputCount {
+                expected: input.len(),
+                actual: input.len() - 1
+            })
+        );
+
+        let mut invalid = input.clone();
+        invalid[0] += U256::from_be_bytes::<32>(Fr::MODULUS.to_bytes_be().try_into().unwrap());
+        assert_eq!(
+            verify_prepared(&vk, &invalid, &proof),
+            Err(VerifyError::PublicInputNotInField(0))
+        );
+
+        let mut invalid = proof;
+        invalid[3] = U256::MAX;
+        assert_eq!(
+            verify_prepared(&vk, &input, &invalid),
+            Err(VerifyError::ProofNotInField(3))
+        );
+
+        let mut invalid = proof;
+        invalid[7] += U256::from(1);
+        assert_eq!(
+            verify_prepared(&vk, &input, &invalid),
+            Err(VerifyError::InvalidProofPoint("C"))
+        );
+
+        let mut invalid = input;
+        invalid[0] += U256::from(1);
+        assert_eq!(
+            verify_prepared(&vk, &invalid, &proof),
+            Err(VerifyError::PairingCheckFailed)
+        );
+    }
+}
@@ -0,0 +1,302 @@
+//! Readers for the Groth16 proofs of zkVMs, which wrap a BN254 proof in a vendor-specific
+//! envelope.
+//!
+//! Both [SP1](https://github.com/succinctlabs/sp1) and [RISC Zero](https://github.com/risc0/risc0)
+//! prefix the proof with a 4-byte selector identifying the verifier, followed by the eight
+//! 32-byte big-endian words of the proof in the order of [`prepare_uncompressed_proof`](crate::prepare_uncompressed_proof).
+//! The public inputs are not part of the envelope and are derived from the statement instead.
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_groth16::Proof;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// Length of an envelope: the selector followed by eight 32-byte words.
+const ENVELOPE_LEN: usize = 4 + 8 * 32;
+
+/// Digest of the zero `SystemState` of RISC Zero, the post state of a halted execution.
+const RISC0_SYSTEM_STATE_ZERO_DIGEST: [u8; 32] = [
+    0xa3, 0xac, 0xc2, 0x71, 0x17, 0x41, 0x89, 0x96, 0x34, 0x0b, 0x84, 0xe5, 0xa9, 0x0f, 0x3e, 0xf4,
+    0xc4, 0x9d, 0x22, 0xc7, 0x9e, 0x44, 0xaa, 0xd8, 0x22, 0xec, 0x9c, 0x31, 0x3e, 0x1e, 0xb8, 0xe2,
+];
+
+/// Error type describing why a wrapped zkVM proof cannot be read.
+#[derive(Debug, Error)]
+pub enum ZkvmProofError {
+    /// The envelope does not have the expected length
+    #[error("expected {expected} bytes, but got {actual}")]
+    InvalidLength {
+        /// The expected length
+        expected: usize,
+        /// The actual length
+        actual: usize,
+    },
+    /// The selector does not match the selector of the verifier
+    #[error("selector 0x{} does not match expected selector 0x{}", hex(.actual), hex(.expected))]
+    SelectorMismatch {
+        /// The selector of the verifier
+        expected: [u8; 4],
+        /// The selector of the proof
+        actual: [u8; 4],
+    },
+    /// A word is not a canonical field element
+    #[error("word {0} is not a canonical field element")]
+    NonCanonical(usize),
+    /// A public input is not a canonical field element
+    #[error("{0} is not a canonical field element")]
+    InvalidPublicInput(&'static str),
+    /// A point is not on the curve or not in the correct subgroup
+    #[error("point {0} is not a valid curve point")]
+    InvalidPoint(&'static str),
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// A Groth16 proof unwrapped from the envelope of a zkVM, together with its public inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WrappedProof {
+    /// The Groth16 proof
+    pub proof: Proof<Bn254>,
+    /// The public inputs of the proof
+    pub public_inputs: Vec<Fr>,
+}
+
+/// Reads the proof bytes of an SP1 Groth16 proof, as returned by `SP1ProofWithPublicValues::bytes`
+/// and passed to `SP1VerifierGroth16.verifyProof`.
+///
+/// The `selector` of the envelope must match the selector of the verifier, the first four bytes
+/// of the hash of its verifying key. The public inputs are the verifying key of the program `program_vkey` and the digest of the
+/// `public_values` committed by the program, which is its SHA-256 hash truncated to 253 bits.
+pub fn read_sp1_proof(
+    proof_bytes: &[u8],
+    selector: [u8; 4],
+    program_vkey: [u8; 32],
+    public_values: &[u8],
+) -> Result<WrappedProof, ZkvmProofError> {
+    let proof = read_envelope(proof_bytes, selector)?;
+    let program_vkey = fr_from_be_bytes(&program_vkey)
+        .ok_or(ZkvmProofError::InvalidPublicInput("program vkey"))?;
+    let mut digest: [u8; 32] = Sha256::digest(public_values).into();
+    digest[0] &= 0x1f;
+    Ok(WrappedProof {
+        proof,
+        public_inputs: vec![program_vkey, Fr::from_be_bytes_mod_order(&digest)],
+    })
+}
+
+/// Reads the seal of a RISC Zero Groth16 receipt, as passed to `RiscZeroGroth16Verifier.verify`.
+///
+/// The `selector` of the seal must match the selector of the verifier. The public inputs are the `control_root` and the `claim_digest` (see
+/// [`risc0_claim_digest`]), each split into two 128-bit halves, followed by the
+/// `bn254_control_id`. All digests are given as the `bytes32` of the Solidity verifier.
+pub fn read_risc0_seal(
+    seal: &[u8],
+    selector: [u8; 4],
+    control_root: [u8; 32],
+    bn254_control_id: [u8; 32],
+    claim_digest: [u8; 32],
+) -> Result<WrappedProof, ZkvmProofError> {
+    let proof = read_envelope(seal, selector)?;
+    let (control_root_0, control_root_1) = split_digest(&control_root);
+    let (claim_0, claim_1) = split_digest(&claim_digest);
+    let control_id = fr_from_be_bytes(&bn254_control_id)
+        .ok_or(ZkvmProofError::InvalidPublicInput("BN254 control id"))?;
+    Ok(WrappedProof {
+        proof,
+        public_inputs: vec![control_root_0, control_root_1, claim_0, claim_1, control_id],
+    })
+}
+
+/// Computes the digest of the `ReceiptClaim` of a successful RISC Zero execution of the program
+/// `image_id` that committed `journal`, like `ReceiptClaimLib.ok(imageId, sha256(journal)).digest()`.
+pub fn risc0_claim_digest(image_id: [u8; 32], journal: &[u8]) -> [u8; 32] {
+    let journal_digest = Sha256::digest(journal);
+    let output = tagged_struct(b"risc0.Output", &[&journal_digest, &[0; 32]], &[]);
+    tagged_struct(
+        b"risc0.ReceiptClaim",
+        &[
+            &[0; 32],
+            &image_id,
+            &RISC0_SYSTEM_STATE_ZERO_DIGEST,
+            &output,
+        ],
+        // halted with exit code 0
+        &[0, 0],
+    )
+}
+
+/// Hashes a struct the way RISC Zero does: the digest of the tag, the digests of the members,
+/// the data as little-endian words and the number of digests as little-endian u16.
+fn tagged_struct(tag: &[u8], digests: &[&[u8]], data: &[u32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(Sha256::digest(tag));
+    digests.iter().for_each(|d| hasher.update(d));
+    data.iter().for_each(|d| hasher.update(d.to_le_bytes()));
+    hasher.update(u16::try_from(digests.len()).expect("fits").to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Splits a digest into two field elements, like `splitDigest` of the RISC Zero verifier: the
+/// byte-reversed digest is split into its lower and upper 128 bits.
+fn split_digest(digest: &[u8; 32]) -> (Fr, Fr) {
+    (
+        Fr::from_le_bytes_mod_order(&digest[..16]),
+        Fr::from_le_bytes_mod_order(&digest[16..]),
+    )
+}
+
+fn read_envelope(bytes: &[u8], expected: [u8; 4]) -> Result<Proof<Bn254>, ZkvmProofError> {
+    if bytes.len() != ENVELOPE_LEN {
+        return Err(ZkvmProofError::InvalidLength {
+            expected: ENVELOPE_LEN,
+            actual: bytes.len(),
+        });
+    }
+    let selector: [u8; 4] = bytes[..4].try_into().expect("checked length");
+    if selector != expected {
+        return Err(ZkvmProofError::SelectorMismatch {
+            expected,
+            actual: selector,
+        });
+    }
+    let words = bytes[4..]
+        .chunks_exact(32)
+        .enumerate()
+        .map(|(i, word)| fq_from_be_bytes(word).ok_or(ZkvmProofError::NonCanonical(i)))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let a = g1_from_xy(words[0], words[1]).ok_or(ZkvmProofError::InvalidPoint("A"))?;
+    let b = g2_from_xy(Fq2::new(words[3], words[2]), Fq2::new(words[5], words[4]))
+        .ok_or(ZkvmProofError::InvalidPoint("B"))?;
+    let c = g1_from_xy(words[6], words[7]).ok_or(ZkvmProofError::InvalidPoint("C"))?;
+    Ok(Proof { a, b, c })
+}
+
+pub(crate) fn fq_from_be_bytes(bytes: &[u8]) -> Option<Fq> {
+    let x = Fq::from_be_bytes_mod_order(bytes);
+    (x.into_bigint().to_bytes_be() == bytes).then_some(x)
+}
+
+pub(crate) fn fr_from_be_bytes(bytes: &[u8]) -> Option<Fr> {
+    let x = Fr::from_be_bytes_mod_order(bytes);
+    (x.into_bigint().to_bytes_be() == bytes).then_some(x)
+}
+
+// Infinity is represented as (0, 0)
+pub(crate) fn g1_from_xy(x: Fq, y: Fq) -> Option<G1Affine> {
+    if x.is_zero() && y.is_zero() {
+        return Some(G1Affine::zero());
+    }
+    let p = G1Affine::new_unchecked(x, y);
+    (p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()).then_some(p)
+}
+
+// Infinity is represented as (0, 0, 0, 0)
+pub(crate) fn g2_from_xy(x: Fq2, y: Fq2) -> Option<G2Affine> {
+    if x.is_zero() && y.is_zero() {
+        return Some(G2Affine::zero());
+    }
+    let p = G2Affine::new_unchecked(x, y);
+    (p.is_on_curve() && p.is_in_correct_subgroup_assuming_on_curve()).then_some(p)
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::CurveGroup;
+    use ark_ff::Field;
+
+    use super::*;
+
+    fn proof() -> Proof<Bn254> {
+        Proof {
+            a: (G1Affine::generator() * Fr::from(3u64)).into_affine(),
+            b: (G2Affine::generator() * Fr::from(5u64)).into_affine(),
+            c: (G1Affine::generator() * Fr::from(7u64)).into_affine(),
+        }
+    }
+
+    fn envelope(selector: [u8; 4], proof: &Proof<Bn254>) -> Vec<u8> {
+        let mut bytes = selector.to_vec();
+        for word in crate::prepare_uncompressed_proof(proof) {
+            bytes.extend(word.to_be_bytes::<32>());
+        }
+        bytes
+    }
+
+    #[test]
+    fn can_read_sp1_proof() {
+        let proof = proof();
+        let selector = [0x11, 0xb6, 0xa0, 0x9d];
+        let bytes = envelope(selector, &proof);
+        let mut vkey = [0u8; 32];
+        vkey[31] = 42;
+        let wrapped = read_sp1_proof(&bytes, selector, vkey, b"hello").unwrap();
+        assert_eq!(wrapped.proof, proof);
+        assert!(matches!(
+            read_sp1_proof(&bytes, [0; 4], vkey, b"hello"),
+            Err(ZkvmProofError::SelectorMismatch { expected, actual }) if expected == [0; 4] && actual == selector
+        ));
+        let mut digest: [u8; 32] = Sha256::digest(b"hello").into();
+        digest[0] &= 0x1f;
+        assert_eq!(
+            wrapped.public_inputs,
+            vec![Fr::from(42u64), Fr::from_be_bytes_mod_order(&digest)]
+        );
+        // 253 bits
+        assert!(wrapped.public_inputs[1].into_bigint().num_bits() <= 253);
+
+        assert!(matches!(
+            read_sp1_proof(&bytes[1..], selector, vkey, b""),
+            Err(ZkvmProofError::InvalidLength { .. })
+        ));
+        let mut invalid = bytes.clone();
+        invalid[4 + 31] ^= 1;
+        assert!(matches!(
+            read_sp1_proof(&invalid, selector, vkey, b""),
+            Err(ZkvmProofError::InvalidPoint("A"))
+        ));
+        let mut invalid = bytes;
+        invalid[4 + 64..4 + 96].fill(0xff);
+        assert!(matches!(
+            read_sp1_proof(&invalid, selector, vkey, b""),
+            Err(ZkvmProofError::NonCanonical(2))
+        ));
+    }
+
+    #[test]
+    fn can_read_risc0_seal() {
+        let proof = proof();
+        let bytes = envelope([1, 2, 3, 4], &proof);
+        let mut claim = [0u8; 32];
+        claim[0] = 1;
+        claim[16] = 2;
+        let mut control_root = [0u8; 32];
+        control_root[15] = 1;
+        control_root[31] = 1;
+        let mut control_id = [0u8; 32];
+        control_id[31] = 9;
+        let wrapped =
+            read_risc0_seal(&bytes, [1, 2, 3, 4], control_root, control_id, claim).unwrap();
+        assert!(matches!(
+            read_risc0_seal(&bytes, [4, 3, 2, 1], control_root, control_id, claim),
+            Err(ZkvmProofError::SelectorMismatch { .. })
+        ));
+        assert_eq!(wrapped.proof, proof);
+        let shift = Fr::from(2u64).pow([120]);
+        assert_eq!(
+            wrapped.public_inputs,
+            vec![shift, shift, Fr::from(1u64), Fr::from(2u64), Fr::from(9u64)]
+        );
+    }
+
+    #[test]
+    fn risc0_system_state_zero_digest() {
+        assert_eq!(
+            tagged_struct(b"risc0.SystemState", &[&[0; 32]], &[0]),
+            RISC0_SYSTEM_STATE_ZERO_DIGEST
+        );
+    }
+}
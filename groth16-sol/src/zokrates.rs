@@ -0,0 +1,285 @@
+//! Readers and writers for the Groth16 `proof.json` and `verification.key` of
+//! [ZoKrates](https://github.com/Zokrates/ZoKrates) on BN254.
+//!
+//! ZoKrates encodes coordinates as `0x`-prefixed big-endian hex strings. The coordinates of G2
+//! points are given with the real part first, i.e., `[[x.c0, x.c1], [y.c0, y.c1]]`, the order in
+//! which arkworks serializes them. Note that this is the reverse of the order of the pairing
+//! precompile of the EVM.
+use std::io::{Read, Write};
+use std::str::FromStr;
+
+use alloy_primitives::U256;
+use ark_bn254::{Bn254, Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_groth16::{Proof, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::zkvm::{fq_from_be_bytes, fr_from_be_bytes, g1_from_xy, g2_from_xy};
+
+const SCHEME: &str = "g16";
+const CURVE: &str = "bn128";
+
+type JsonG1 = [String; 2];
+type JsonG2 = [[String; 2]; 2];
+
+/// Error type describing why a ZoKrates file cannot be read.
+#[derive(Debug, Error)]
+pub enum ZokratesError {
+    /// The file is not valid JSON of the expected shape
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    /// The proving scheme is not Groth16
+    #[error("unsupported scheme {0}, only {SCHEME} is supported")]
+    UnsupportedScheme(String),
+    /// The curve is not BN254
+    #[error("unsupported curve {0}, only {CURVE} is supported")]
+    UnsupportedCurve(String),
+    /// A value is not a hex encoded canonical field element
+    #[error("{0} is not a canonical field element")]
+    InvalidFieldElement(String),
+    /// A point is not on the curve or not in the correct subgroup
+    #[error("{0} is not a valid curve point")]
+    InvalidPoint(&'static str),
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonVerificationKey {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    curve: Option<String>,
+    alpha: JsonG1,
+    beta: JsonG2,
+    gamma: JsonG2,
+    delta: JsonG2,
+    gamma_abc: Vec<JsonG1>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonProofPoints {
+    a: JsonG1,
+    b: JsonG2,
+    c: JsonG1,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JsonProof {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    scheme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    curve: Option<String>,
+    proof: JsonProofPoints,
+    inputs: Vec<String>,
+}
+
+/// Read a [verifying key](ark_groth16::VerifyingKey) from a ZoKrates `verification.key`.
+pub fn read_zokrates_vk<R: Read>(reader: R) -> Result<VerifyingKey<Bn254>, ZokratesError> {
+    let vk: JsonVerificationKey = serde_json::from_reader(reader)?;
+    check_scheme(vk.scheme, vk.curve)?;
+    Ok(VerifyingKey {
+        alpha_g1: parse_g1(&vk.alpha, "alpha")?,
+        beta_g2: parse_g2(&vk.beta, "beta")?,
+        gamma_g2: parse_g2(&vk.gamma, "gamma")?,
+        delta_g2: parse_g2(&vk.delta, "delta")?,
+        gamma_abc_g1: vk
+            .gamma_abc
+            .iter()
+            .map(|p| parse_g1(p, "gamma_abc"))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Read a [Groth16 proof](ark_groth16::Proof) and its public inputs from a ZoKrates `proof.json`.
+pub fn read_zokrates_proof<R: Read>(reader: R) -> Result<(Proof<Bn254>, Vec<Fr>), ZokratesError> {
+    let proof: JsonProof = serde_json::from_reader(reader)?;
+    check_scheme(proof.scheme, proof.curve)?;
+    let inputs = proof
+        .inputs
+        .iter()
+        .map(|x| parse_field(x, fr_from_be_bytes))
+        .collect::<Result<_, _>>()?;
+    let proof = Proof {
+        a: parse_g1(&proof.proof.a, "a")?,
+        b: parse_g2(&proof.proof.b, "b")?,
+        c: parse_g1(&proof.proof.c, "c")?,
+    };
+    Ok((proof, inputs))
+}
+
+/// Write a [verifying key](ark_groth16::VerifyingKey) as ZoKrates `verification.key`, the inverse of [`read_zokrates_vk`].
+pub fn write_zokrates_vk<W: Write>(
+    vk: &VerifyingKey<Bn254>,
+    writer: W,
+) -> Result<(), ZokratesError> {
+    let vk = JsonVerificationKey {
+        scheme: Some(SCHEME.to_owned()),
+        curve: Some(CURVE.to_owned()),
+        alpha: g1_to_json(&vk.alpha_g1),
+        beta: g2_to_json(&vk.beta_g2),
+        gamma: g2_to_json(&vk.gamma_g2),
+        delta: g2_to_json(&vk.delta_g2),
+        gamma_abc: vk.gamma_abc_g1.iter().map(g1_to_json).collect(),
+    };
+    Ok(serde_json::to_writer_pretty(writer, &vk)?)
+}
+
+/// Write a [Groth16 proof](ark_groth16::Proof) and its public `inputs` as ZoKrates `proof.json`, the inverse of [`read_zokrates_proof`].
+pub fn write_zokrates_proof<W: Write>(
+    proof: &Proof<Bn254>,
+    inputs: &[Fr],
+    writer: W,
+) -> Result<(), ZokratesError> {
+    let proof = JsonProof {
+        scheme: Some(SCHEME.to_owned()),
+        curve: Some(CURVE.to_owned()),
+        proof: JsonProofPoints {
+            a: g1_to_json(&proof.a),
+            b: g2_to_json(&proof.b),
+            c: g1_to_json(&proof.c),
+        },
+        inputs: inputs.iter().map(|x| to_hex(*x)).collect(),
+    };
+    Ok(serde_json::to_writer_pretty(writer, &proof)?)
+}
+
+// Files of older ZoKrates versions do not contain the scheme and curve
+fn check_scheme(scheme: Option<String>, curve: Option<String>) -> Result<(), ZokratesError> {
+    if let Some(scheme) = scheme.filter(|scheme| scheme != SCHEME) {
+        return Err(ZokratesError::UnsupportedScheme(scheme));
+    }
+    if let Some(curve) = curve.filter(|curve| curve != CURVE) {
+        return Err(ZokratesError::UnsupportedCurve(curve));
+    }
+    Ok(())
+}
+
+fn parse_field<F>(
+    hex: &str,
+    from_be_bytes: impl Fn(&[u8]) -> Option<F>,
+) -> Result<F, ZokratesError> {
+    U256::from_str(hex)
+        .ok()
+        .and_then(|x| from_be_bytes(&x.to_be_bytes::<32>()))
+        .ok_or_else(|| ZokratesError::InvalidFieldElement(hex.to_owned()))
+}
+
+fn parse_g1([x, y]: &JsonG1, name: &'static str) -> Result<G1Affine, ZokratesError> {
+    let x = parse_field(x, fq_from_be_bytes)?;
+    let y = parse_field(y, fq_from_be_bytes)?;
+    g1_from_xy(x, y).ok_or(ZokratesError::InvalidPoint(name))
+}
+
+fn parse_g2([[x0, x1], [y0, y1]]: &JsonG2, name: &'static str) -> Result<G2Affine, ZokratesError> {
+    let x = Fq2::new(
+        parse_field(x0, fq_from_be_bytes)?,
+        parse_field(x1, fq_from_be_bytes)?,
+    );
+    let y = Fq2::new(
+        parse_field(y0, fq_from_be_bytes)?,
+        parse_field(y1, fq_from_be_bytes)?,
+    );
+    g2_from_xy(x, y).ok_or(ZokratesError::InvalidPoint(name))
+}
+
+fn to_hex<F: Into<U256>>(x: F) -> String {
+    format!("{:#066x}", x.into())
+}
+
+// Infinity is represented as (0, 0)
+fn g1_to_json(p: &G1Affine) -> JsonG1 {
+    let (x, y) = p.xy().unwrap_or_default();
+    [to_hex::<Fq>(x), to_hex::<Fq>(y)]
+}
+
+// Infinity is represented as (0, 0, 0, 0)
+fn g2_to_json(p: &G2Affine) -> JsonG2 {
+    let (x, y) = p.xy().unwrap_or_default();
+    [
+        [to_hex::<Fq>(x.c0), to_hex::<Fq>(x.c1)],
+        [to_hex::<Fq>(y.c0), to_hex::<Fq>(y.c1)],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use ark_ec::CurveGroup;
+
+    use super::*;
+
+    // the G2 generator as ZoKrates writes it
+    const G2_GENERATOR: &str = r#"[
+        ["0x1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed", "0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"],
+        ["0x12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa", "0x090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"]
+    ]"#;
+
+    fn proof_json(b: &str, inputs: &str) -> String {
+        format!(
+            r#"{{
+                "scheme": "g16",
+                "curve": "bn128",
+                "proof": {{
+                    "a": ["0x01", "0x02"],
+                    "b": {b},
+                    "c": ["0x0000000000000000000000000000000000000000000000000000000000000001", "0x02"]
+                }},
+                "inputs": {inputs}
+            }}"#
+        )
+    }
+
+    #[test]
+    fn can_read_zokrates_proof() {
+        let json = proof_json(G2_GENERATOR, r#"["0x2a", "0x00"]"#);
+        let (proof, inputs) = read_zokrates_proof(json.as_bytes()).unwrap();
+        assert_eq!(proof.a, G1Affine::generator());
+        assert_eq!(proof.b, G2Affine::generator());
+        assert_eq!(proof.c, G1Affine::generator());
+        assert_eq!(inputs, vec![Fr::from(42u64), Fr::from(0u64)]);
+
+        let mut written = Vec::new();
+        write_zokrates_proof(&proof, &inputs, &mut written).unwrap();
+        let (read, read_inputs) = read_zokrates_proof(written.as_slice()).unwrap();
+        assert_eq!(read, proof);
+        assert_eq!(read_inputs, inputs);
+    }
+
+    #[test]
+    fn rejects_invalid_zokrates_proofs() {
+        // real and imaginary part swapped
+        let swapped = G2_GENERATOR
+            .replace("0x198e", "0xtmp")
+            .replace("0x1800", "0x198e");
+        let swapped = swapped.replace("0xtmp", "0x1800");
+        assert!(matches!(
+            read_zokrates_proof(proof_json(&swapped, "[]").as_bytes()),
+            Err(ZokratesError::InvalidPoint("b"))
+        ));
+        let modulus = "0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001";
+        assert!(matches!(
+            read_zokrates_proof(proof_json(G2_GENERATOR, &format!(r#"["{modulus}"]"#)).as_bytes()),
+            Err(ZokratesError::InvalidFieldElement(_))
+        ));
+        let gm17 = proof_json(G2_GENERATOR, "[]").replace("g16", "gm17");
+        assert!(matches!(
+            read_zokrates_proof(gm17.as_bytes()),
+            Err(ZokratesError::UnsupportedScheme(_))
+        ));
+    }
+
+    #[test]
+    fn zokrates_vk_round_trip() {
+        let g1 = |i: u64| (G1Affine::generator() * Fr::from(i)).into_affine();
+        let g2 = |i: u64| (G2Affine::generator() * Fr::from(i)).into_affine();
+        let vk = VerifyingKey::<Bn254> {
+            alpha_g1: g1(2),
+            beta_g2: g2(3),
+            gamma_g2: g2(4),
+            delta_g2: g2(5),
+            gamma_abc_g1: vec![g1(6), g1(7), G1Affine::zero()],
+        };
+        let mut written = Vec::new();
+        write_zokrates_vk(&vk, &mut written).unwrap();
+        assert_eq!(read_zokrates_vk(written.as_slice()).unwrap(), vk);
+    }
+}